@@ -0,0 +1,669 @@
+//! Audio File Ingestion for SaturatedTranscriber
+//!
+//! SaturatedTranscriber works on mono `&[f32]` at one sample rate.
+//! This module turns files into exactly that:
+//!
+//! - WAV: 8/16/24/32-bit integer PCM and 32/64-bit float (incl. WAVE_FORMAT_EXTENSIBLE)
+//! - FLAC: any bit depth, decoded with the `claxon` crate
+//! - Raw PCM: headerless, described by RawPcmFormat
+//!
+//! Multi-channel audio is downmixed (channel mean) and resampled to the
+//! target rate.
+//!
+//! transcribe_directory / run_transcribe_dir: transcribe every audio file
+//! in a directory and write `<name>.txt` (text) and `<name>.segments.tsv`
+//! (LocatedSegment timings) per file.
+
+use super::lattice::{nbest_tsv, LatticeFormat};
+use super::lexicon::Lexicon;
+use super::phonotactics::PhonotacticRules;
+use super::saturated_transcriber::{LocatedSegment, SaturatedTranscriber};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Sample rate the transcriber's bands are laid out for (HIGH band = 2-8 kHz = Nyquist)
+pub const TARGET_SAMPLE_RATE: u32 = 16000;
+
+// ============================================================================
+// ERRORS AND FORMATS
+// ============================================================================
+
+#[derive(Debug)]
+pub enum AudioError {
+    Io(io::Error),
+    /// Malformed or unsupported file contents
+    Format(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::Io(e) => write!(f, "I/O error: {}", e),
+            AudioError::Format(msg) => write!(f, "format error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+impl From<io::Error> for AudioError {
+    fn from(e: io::Error) -> Self {
+        AudioError::Io(e)
+    }
+}
+
+fn format_error<T>(msg: impl Into<String>) -> Result<T, AudioError> {
+    Err(AudioError::Format(msg.into()))
+}
+
+/// Sample encoding of PCM data (always little-endian)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleEncoding {
+    U8,
+    I16,
+    I24,
+    I32,
+    F32,
+    F64,
+}
+
+impl SampleEncoding {
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            SampleEncoding::U8 => 1,
+            SampleEncoding::I16 => 2,
+            SampleEncoding::I24 => 3,
+            SampleEncoding::I32 | SampleEncoding::F32 => 4,
+            SampleEncoding::F64 => 8,
+        }
+    }
+
+    /// Parse a CLI name: u8, s16, s24, s32, f32, f64
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "u8" => Some(SampleEncoding::U8),
+            "s16" | "i16" => Some(SampleEncoding::I16),
+            "s24" | "i24" => Some(SampleEncoding::I24),
+            "s32" | "i32" => Some(SampleEncoding::I32),
+            "f32" => Some(SampleEncoding::F32),
+            "f64" => Some(SampleEncoding::F64),
+            _ => None,
+        }
+    }
+
+    /// Decode one sample to [-1, 1]
+    fn decode(self, b: &[u8]) -> f32 {
+        match self {
+            SampleEncoding::U8 => (b[0] as f32 - 128.0) / 128.0,
+            SampleEncoding::I16 => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            // Sign-extend by placing the 24 bits in the top of an i32
+            SampleEncoding::I24 => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+            SampleEncoding::I32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+            SampleEncoding::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            SampleEncoding::F64 => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+        }
+    }
+}
+
+/// Layout of headerless PCM data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawPcmFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub encoding: SampleEncoding,
+}
+
+impl Default for RawPcmFormat {
+    fn default() -> Self {
+        RawPcmFormat { sample_rate: TARGET_SAMPLE_RATE, channels: 1, encoding: SampleEncoding::I16 }
+    }
+}
+
+/// Decoded mono audio
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioBuffer {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl AudioBuffer {
+    pub fn duration_ms(&self) -> usize {
+        (self.samples.len() as u64 * 1000 / self.sample_rate.max(1) as u64) as usize
+    }
+
+    /// Resample to `rate` (no-op if already there)
+    pub fn resampled(self, rate: u32) -> AudioBuffer {
+        if rate == self.sample_rate {
+            return self;
+        }
+        AudioBuffer { samples: resample(&self.samples, self.sample_rate, rate), sample_rate: rate }
+    }
+}
+
+// ============================================================================
+// DECODING
+// ============================================================================
+
+/// Decode interleaved PCM bytes and downmix to mono
+pub fn decode_raw(bytes: &[u8], format: &RawPcmFormat) -> Result<AudioBuffer, AudioError> {
+    if format.channels == 0 || format.sample_rate == 0 {
+        return format_error("raw PCM needs channels > 0 and sample_rate > 0");
+    }
+
+    let width = format.encoding.bytes_per_sample();
+    let interleaved: Vec<f32> = bytes.chunks_exact(width).map(|b| format.encoding.decode(b)).collect();
+
+    Ok(AudioBuffer {
+        samples: downmix(&interleaved, format.channels as usize),
+        sample_rate: format.sample_rate,
+    })
+}
+
+/// Decode a RIFF/WAVE file
+pub fn decode_wav(bytes: &[u8]) -> Result<AudioBuffer, AudioError> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return format_error("not a RIFF/WAVE file");
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut pos = 12;
+
+    // Walk the chunk list: fmt and data may appear in any order among others
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+        let body = &bytes[pos + 8..(pos + 8 + size).min(bytes.len())];
+
+        match id {
+            b"fmt " => format = Some(parse_wav_format(body)?),
+            b"data" => data = Some(body),
+            _ => {}
+        }
+
+        // Chunks are word-aligned
+        pos += 8 + size + (size & 1);
+    }
+
+    match (format, data) {
+        (Some(format), Some(data)) => decode_raw(data, &format),
+        (None, _) => format_error("WAV has no fmt chunk"),
+        (_, None) => format_error("WAV has no data chunk"),
+    }
+}
+
+/// Decode a FLAC stream
+pub fn decode_flac(bytes: &[u8]) -> Result<AudioBuffer, AudioError> {
+    let flac_error = |e: claxon::Error| AudioError::Format(format!("FLAC: {}", e));
+
+    let mut reader = claxon::FlacReader::new(io::Cursor::new(bytes)).map_err(flac_error)?;
+    let info = reader.streaminfo();
+    let scale = (1u64 << (info.bits_per_sample - 1)) as f32;
+
+    // Samples come interleaved, integers at the stream's bit depth
+    let interleaved = reader.samples()
+        .map(|sample| sample.map(|s| s as f32 / scale))
+        .collect::<Result<Vec<f32>, _>>()
+        .map_err(flac_error)?;
+
+    Ok(AudioBuffer {
+        samples: downmix(&interleaved, info.channels as usize),
+        sample_rate: info.sample_rate,
+    })
+}
+
+fn parse_wav_format(body: &[u8]) -> Result<RawPcmFormat, AudioError> {
+    if body.len() < 16 {
+        return format_error("WAV fmt chunk too short");
+    }
+
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    let mut tag = u16_at(0);
+    let channels = u16_at(2);
+    let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+    let bits = u16_at(14);
+
+    // WAVE_FORMAT_EXTENSIBLE: the real tag is the first 2 bytes of the SubFormat GUID
+    if tag == 0xFFFE {
+        if body.len() < 26 {
+            return format_error("WAVE_FORMAT_EXTENSIBLE fmt chunk too short");
+        }
+        tag = u16_at(24);
+    }
+
+    let encoding = match (tag, bits) {
+        (1, 8) => SampleEncoding::U8,
+        (1, 16) => SampleEncoding::I16,
+        (1, 24) => SampleEncoding::I24,
+        (1, 32) => SampleEncoding::I32,
+        (3, 32) => SampleEncoding::F32,
+        (3, 64) => SampleEncoding::F64,
+        _ => return format_error(format!("unsupported WAV encoding (tag {}, {} bits)", tag, bits)),
+    };
+
+    Ok(RawPcmFormat { sample_rate, channels, encoding })
+}
+
+/// Average interleaved channels into one
+pub fn downmix(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return interleaved.to_vec();
+    }
+    interleaved.chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Resample by linear interpolation
+///
+/// When downsampling, a moving average over one source period of the new
+/// rate is applied first so content above the new Nyquist does not alias
+/// into the LOW/MID bands.
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if samples.is_empty() || from_rate == to_rate || from_rate == 0 || to_rate == 0 {
+        return samples.to_vec();
+    }
+
+    let ratio = from_rate as f64 / to_rate as f64;
+    let source: Vec<f32> = if ratio > 1.0 {
+        let width = ratio.round() as usize;
+        let mut acc = 0.0f32;
+        samples.iter().enumerate().map(|(i, &x)| {
+            acc += x;
+            if i >= width {
+                acc -= samples[i - width];
+            }
+            acc / (i + 1).min(width) as f32
+        }).collect()
+    } else {
+        samples.to_vec()
+    };
+
+    let out_len = ((samples.len() as u64 * to_rate as u64) / from_rate as u64) as usize;
+    (0..out_len).map(|i| {
+        let t = i as f64 * ratio;
+        let idx = t as usize;
+        let frac = (t - idx as f64) as f32;
+        let a = source[idx.min(source.len() - 1)];
+        let b = source[(idx + 1).min(source.len() - 1)];
+        a + (b - a) * frac
+    }).collect()
+}
+
+/// Load an audio file as mono at `target_rate`
+///
+/// `.wav` and `.flac` are decoded from their headers; `.raw`/`.pcm` use
+/// `raw` (or the RawPcmFormat default: 16 kHz mono s16).
+pub fn load_audio(path: &Path, raw: Option<&RawPcmFormat>, target_rate: u32) -> Result<AudioBuffer, AudioError> {
+    let bytes = fs::read(path)?;
+
+    let buffer = if bytes.starts_with(b"RIFF") {
+        decode_wav(&bytes)?
+    } else if bytes.starts_with(b"fLaC") {
+        decode_flac(&bytes)?
+    } else {
+        match extension(path).as_deref() {
+            Some("raw") | Some("pcm") => decode_raw(&bytes, &raw.copied().unwrap_or_default())?,
+            _ => return format_error(format!("{}: unrecognized audio file", path.display())),
+        }
+    };
+
+    Ok(buffer.resampled(target_rate))
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase())
+}
+
+/// File extensions picked up by transcribe_directory
+pub fn is_audio_file(path: &Path) -> bool {
+    matches!(extension(path).as_deref(), Some("wav") | Some("flac") | Some("raw") | Some("pcm"))
+}
+
+// ============================================================================
+// DIRECTORY TRANSCRIPTION
+// ============================================================================
+
+/// Transcription of one file
+pub struct FileTranscript {
+    pub path: PathBuf,
+    pub text: String,
+    pub segments: Vec<LocatedSegment>,
+    pub duration_ms: usize,
+}
+
+/// Options for transcribe_directory
+#[derive(Clone, Debug)]
+pub struct TranscribeOptions {
+    pub target_rate: u32,
+    /// Format of headerless .raw/.pcm files
+    pub raw: RawPcmFormat,
+    /// Jointly saturate over all files before transcribing (saturate_all)
+    pub saturate_first: bool,
+    /// Also write the N-best list per file (0 = off)
+    pub nbest: usize,
+    /// Also write phoneme and word lattices per file
+    pub lattice: Option<LatticeFormat>,
+}
+
+impl Default for TranscribeOptions {
+    fn default() -> Self {
+        TranscribeOptions {
+            target_rate: TARGET_SAMPLE_RATE,
+            raw: RawPcmFormat::default(),
+            saturate_first: false,
+            nbest: 0,
+            lattice: None,
+        }
+    }
+}
+
+/// Tab-separated LocatedSegment timings, one segment per line
+pub fn segments_tsv(segments: &[LocatedSegment]) -> String {
+    let mut out = String::from("start_ms\tend_ms\tpitch\tenergy_db\tvoiced\tsilence\tlow\tmid\thigh\n");
+    for seg in segments {
+        out.push_str(&format!("{}\t{}\t{}\t{:.2}\t{}\t{}\t{:.2}\t{:.2}\t{:.2}\n",
+            seg.start_ms, seg.end_ms,
+            seg.pitch.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
+            seg.energy_db, seg.is_voiced, seg.is_silence,
+            seg.energy_low, seg.energy_mid, seg.energy_high));
+    }
+    out
+}
+
+/// Transcribe every audio file in `input_dir` (sorted by name)
+///
+/// Writes `<stem>.txt` and `<stem>.segments.tsv` into `output_dir`.
+/// Files that fail to load are reported on stderr and skipped.
+pub fn transcribe_directory(
+    transcriber: &mut SaturatedTranscriber,
+    input_dir: &Path,
+    output_dir: &Path,
+    options: &TranscribeOptions,
+) -> Result<Vec<FileTranscript>, AudioError> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(input_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_audio_file(p))
+        .collect();
+    paths.sort();
+
+    let mut loaded = Vec::new();
+    for path in paths {
+        match load_audio(&path, Some(&options.raw), options.target_rate) {
+            Ok(buffer) => loaded.push((path, buffer)),
+            Err(e) => eprintln!("skipping {}: {}", path.display(), e),
+        }
+    }
+
+    if options.saturate_first && !loaded.is_empty() {
+        let all: Vec<&[f32]> = loaded.iter().map(|(_, b)| b.samples.as_slice()).collect();
+        transcriber.saturate_all(&all, options.target_rate);
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let mut results = Vec::new();
+
+    for (path, buffer) in loaded {
+        let (text, segments, _) = transcriber.transcribe_detailed(&buffer.samples, buffer.sample_rate);
+
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        fs::write(output_dir.join(format!("{}.txt", stem)), format!("{}\n", text))?;
+        fs::write(output_dir.join(format!("{}.segments.tsv", stem)), segments_tsv(&segments))?;
+
+        if options.nbest > 0 {
+            let nbest = transcriber.nbest(&buffer.samples, buffer.sample_rate, options.nbest);
+            fs::write(output_dir.join(format!("{}.nbest.tsv", stem)), nbest_tsv(&nbest))?;
+        }
+        if let Some(format) = options.lattice {
+            let (phonemes, words) = transcriber.lattices(&buffer.samples, buffer.sample_rate, options.nbest.max(1));
+            fs::write(output_dir.join(format!("{}.phonemes.{}", stem, format.extension())), format.render(&phonemes, &stem))?;
+            fs::write(output_dir.join(format!("{}.words.{}", stem, format.extension())), format.render(&words, &stem))?;
+        }
+
+        results.push(FileTranscript { duration_ms: buffer.duration_ms(), path, text, segments });
+    }
+
+    Ok(results)
+}
+
+/// CLI: `<input_dir> [output_dir] [--rate HZ] [--saturate] [--model PATH]
+///        [--lexicon CMUDICT] [--word-freqs PATH] [--rules RULES.json]
+///        [--nbest N] [--lattice slf|json]
+///        [--raw-rate HZ] [--raw-channels N] [--raw-format u8|s16|s24|s32|f32|f64]`
+///
+/// Output defaults to the input directory. `--model` loads a saved
+/// saturated state, or with `--saturate` saves the new one there.
+/// `--lexicon` replaces the built-in word list (`--word-freqs`: `word count` lines),
+/// `--rules` the built-in English phonotactics.
+/// `--nbest` writes `<stem>.nbest.tsv`; `--lattice` writes `<stem>.phonemes.*`
+/// and `<stem>.words.*` (word lattice over the N-best, at least the 1-best).
+const TRANSCRIBE_USAGE: &str = "usage: <input_dir> [output_dir] [--rate HZ] [--saturate] [--model PATH] [--lexicon CMUDICT] [--word-freqs PATH] [--rules RULES.json] [--nbest N] [--lattice slf|json] [--raw-rate HZ] [--raw-channels N] [--raw-format FMT]";
+
+pub fn run_transcribe_dir(args: &[String]) -> Result<(), AudioError> {
+    let mut options = TranscribeOptions::default();
+    let mut positional = Vec::new();
+    let mut model_path = None;
+    let mut lexicon_path = None;
+    let mut freqs_path = None;
+    let mut rules_path = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().cloned().ok_or_else(|| AudioError::Format(format!("{} needs a value", name)));
+        match arg.as_str() {
+            "--rate" => options.target_rate = parse_rate(&value(arg)?)?,
+            "--raw-rate" => options.raw.sample_rate = parse_rate(&value(arg)?)?,
+            "--raw-channels" => options.raw.channels = parse_number(&value(arg)?)?,
+            "--raw-format" => {
+                let name = value(arg)?;
+                options.raw.encoding = SampleEncoding::parse(&name)
+                    .ok_or_else(|| AudioError::Format(format!("unknown raw format '{}'", name)))?;
+            }
+            "--saturate" => options.saturate_first = true,
+            "--nbest" => options.nbest = parse_number(&value(arg)?)?,
+            "--lattice" => {
+                let name = value(arg)?;
+                options.lattice = Some(LatticeFormat::parse(&name)
+                    .ok_or_else(|| AudioError::Format(format!("unknown lattice format '{}'", name)))?);
+            }
+            "--model" => model_path = Some(PathBuf::from(value(arg)?)),
+            "--lexicon" => lexicon_path = Some(PathBuf::from(value(arg)?)),
+            "--word-freqs" => freqs_path = Some(PathBuf::from(value(arg)?)),
+            "--rules" => rules_path = Some(PathBuf::from(value(arg)?)),
+            flag if flag.starts_with("--") => return format_error(format!("unknown option '{}'\n{}", flag, TRANSCRIBE_USAGE)),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    let Some(input_dir) = positional.first() else {
+        return format_error(TRANSCRIBE_USAGE);
+    };
+    let output_dir = positional.get(1).unwrap_or(input_dir);
+
+    let mut transcriber = match &model_path {
+        Some(path) if !options.saturate_first => SaturatedTranscriber::load_model(path)
+            .map_err(|e| AudioError::Format(format!("{}: {}", path.display(), e)))?,
+        _ => SaturatedTranscriber::new(),
+    };
+    if let Some(path) = &lexicon_path {
+        let (lexicon, skipped) = Lexicon::load_cmudict(path, freqs_path.as_deref())?;
+        if skipped > 0 {
+            eprintln!("{}: skipped {} unparsable entries", path.display(), skipped);
+        }
+        transcriber.set_lexicon(lexicon);
+    }
+    if let Some(path) = &rules_path {
+        PhonotacticRules::load(path)
+            .and_then(|rules| transcriber.set_rules(&rules))
+            .map_err(|e| AudioError::Format(format!("{}: {}", path.display(), e)))?;
+    }
+
+    for result in transcribe_directory(&mut transcriber, input_dir, output_dir, &options)? {
+        println!("{} ({} ms, {} segments): {}",
+            result.path.display(), result.duration_ms, result.segments.len(), result.text);
+    }
+
+    if let (Some(path), true) = (&model_path, options.saturate_first) {
+        transcriber.save_model(path).map_err(|e| AudioError::Format(format!("{}: {}", path.display(), e)))?;
+    }
+
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, AudioError> {
+    text.parse().map_err(|_| AudioError::Format(format!("not a number: '{}'", text)))
+}
+
+/// A sample rate in Hz; zero would divide every sample-to-time conversion by zero
+fn parse_rate(text: &str) -> Result<u32, AudioError> {
+    match parse_number(text)? {
+        0 => format_error("sample rate must be positive"),
+        rate => Ok(rate),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal WAV writer for round-trip tests
+    fn wav_bytes(tag: u16, channels: u16, rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        out.extend_from_slice(b"WAVE");
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&channels.to_le_bytes());
+        out.extend_from_slice(&rate.to_le_bytes());
+        let block = channels * bits / 8;
+        out.extend_from_slice(&(rate * block as u32).to_le_bytes());
+        out.extend_from_slice(&block.to_le_bytes());
+        out.extend_from_slice(&bits.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn test_decode_wav_encodings() {
+        // 16-bit stereo: L = 0.5, R = -0.5 -> mono 0
+        let data: Vec<u8> = [16384i16, -16384].iter().flat_map(|s| s.to_le_bytes()).collect();
+        let buffer = decode_wav(&wav_bytes(1, 2, 8000, 16, &data)).unwrap();
+        assert_eq!(buffer.sample_rate, 8000);
+        assert_eq!(buffer.samples, vec![0.0]);
+
+        // 24-bit mono, negative value sign-extended
+        let data = [0x00, 0x00, 0xC0]; // -0.5
+        let buffer = decode_wav(&wav_bytes(1, 1, 16000, 24, &data)).unwrap();
+        assert!((buffer.samples[0] + 0.5).abs() < 1e-6);
+
+        // 32-bit float
+        let data: Vec<u8> = [0.25f32, -1.0].iter().flat_map(|s| s.to_le_bytes()).collect();
+        let buffer = decode_wav(&wav_bytes(3, 1, 16000, 32, &data)).unwrap();
+        assert_eq!(buffer.samples, vec![0.25, -1.0]);
+
+        assert!(decode_wav(b"RIFF\0\0\0\0WAVEjunk").is_err());
+    }
+
+    /// Minimal FLAC writer: one STREAMINFO block, VERBATIM subframes,
+    /// 16-bit samples, `block` samples per frame
+    fn flac_bytes(channels: &[Vec<i16>], rate: u32, block: usize) -> Vec<u8> {
+        fn crc(data: &[u8], poly: u16, bits: u32) -> u16 {
+            let top = 1u16 << (bits - 1);
+            let mask = if bits == 16 { 0xFFFF } else { (1 << bits) - 1 };
+            data.iter().fold(0u16, |mut crc, &byte| {
+                crc ^= (byte as u16) << (bits - 8);
+                for _ in 0..8 {
+                    crc = if crc & top != 0 { (crc << 1) ^ poly } else { crc << 1 } & mask;
+                }
+                crc
+            })
+        }
+
+        let total = channels[0].len();
+        let mut out = b"fLaC".to_vec();
+        out.extend_from_slice(&[0x80, 0, 0, 34]);
+        out.extend_from_slice(&(block as u16).to_be_bytes());
+        out.extend_from_slice(&(block as u16).to_be_bytes());
+        out.extend_from_slice(&[0; 6]);
+        let packed = (rate as u64) << 44 | ((channels.len() as u64 - 1) << 41) | (15 << 36) | total as u64;
+        out.extend_from_slice(&packed.to_be_bytes());
+        out.extend_from_slice(&[0; 16]);
+
+        for (number, start) in (0..total).step_by(block).enumerate() {
+            let len = block.min(total - start);
+            let mut frame = vec![0xFF, 0xF8, 0x60, ((channels.len() as u8 - 1) << 4) | 0x08, number as u8, (len - 1) as u8];
+            frame.push(crc(&frame, 0x07, 8) as u8);
+            for channel in channels {
+                frame.push(0x02);
+                frame.extend(channel[start..start + len].iter().flat_map(|s| s.to_be_bytes()));
+            }
+            let footer = crc(&frame, 0x8005, 16);
+            frame.extend_from_slice(&footer.to_be_bytes());
+            out.extend(frame);
+        }
+        out
+    }
+
+    #[test]
+    fn test_decode_flac() {
+        // Stereo, two frames (32 + 8 samples); must match the same PCM as WAV
+        let left: Vec<i16> = (0..40).map(|i| (i * 800 - 16000) as i16).collect();
+        let right: Vec<i16> = (0..40).map(|i| (i * i * 7) as i16).collect();
+        let flac = decode_flac(&flac_bytes(&[left.clone(), right.clone()], 22050, 32)).unwrap();
+
+        let pcm: Vec<u8> = left.iter().zip(&right).flat_map(|(l, r)| [l.to_le_bytes(), r.to_le_bytes()]).flatten().collect();
+        let wav = decode_wav(&wav_bytes(1, 2, 22050, 16, &pcm)).unwrap();
+        assert_eq!(flac, wav);
+        assert_eq!(flac.samples.len(), 40);
+
+        assert!(decode_flac(b"fLaC\0\0").is_err(), "Truncated stream is a format error");
+        assert!(is_audio_file(Path::new("a/take1.FLAC")));
+    }
+
+    #[test]
+    fn test_decode_raw() {
+        let format = RawPcmFormat { sample_rate: 16000, channels: 1, encoding: SampleEncoding::I16 };
+        let bytes: Vec<u8> = [0i16, 32767, -32768].iter().flat_map(|s| s.to_le_bytes()).collect();
+        let buffer = decode_raw(&bytes, &format).unwrap();
+        assert_eq!(buffer.samples.len(), 3);
+        assert_eq!(buffer.samples[2], -1.0);
+    }
+
+    #[test]
+    fn test_transcribe_dir_rejects_bad_arguments() {
+        let run = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            match run_transcribe_dir(&args) {
+                Err(AudioError::Format(msg)) => msg,
+                other => panic!("expected a usage error, got {:?}", other),
+            }
+        };
+
+        assert!(run(&["in", "--saturat"]).starts_with("unknown option '--saturat'"));
+        assert_eq!(run(&["in", "--rate", "0"]), "sample rate must be positive");
+        assert_eq!(run(&["in", "--raw-rate", "0"]), "sample rate must be positive");
+        assert_eq!(run(&["in", "--rate", "fast"]), "not a number: 'fast'");
+        assert!(run(&[]).starts_with("usage:"));
+    }
+
+    #[test]
+    fn test_resample_preserves_duration_and_dc() {
+        let samples = vec![0.5f32; 48000];
+        let down = resample(&samples, 48000, 16000);
+        assert_eq!(down.len(), 16000);
+        assert!(down.iter().all(|&x| (x - 0.5).abs() < 1e-5));
+
+        let up = resample(&samples[..8000], 8000, 16000);
+        assert_eq!(up.len(), 16000);
+    }
+}
@@ -0,0 +1,626 @@
+//! Voice Activity and Speaker Turns - Meeting Recordings
+//!
+//! saturate_segment_boundaries marks silence with a FIXED -35 dB threshold.
+//! On a meeting recording that fails both ways: background noise above -35 dB
+//! is never silence, and two speakers with no pause between them become one
+//! utterance. This module runs BEFORE the transcriber:
+//!
+//! - AdaptiveVad: the noise floor is DERIVED from the recording (a low
+//!   percentile of frame energy), the speech threshold from its distance to
+//!   the speech level
+//! - Speaker change: pitch and band-tilt statistics of adjacent windows,
+//!   compared in units of their own within-window spread
+//! - Turns: clustered into speakers, gated, and sent to transcribe one by one
+//!
+//! Band tilt (LOW - MID, MID - HIGH) instead of band level: it describes the
+//! voice, not how close the speaker sits to the microphone.
+
+use super::attack_pitch::detect_pitch;
+use super::audio_io::{load_audio, TARGET_SAMPLE_RATE};
+use super::saturated_transcriber::{SaturatedTranscriber, TimedWord};
+use super::spectrum::{SpectralAnalyzer, WindowFunction};
+use super::streaming::SILENCE_DB;
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Analysis frames match saturate_segment_boundaries (20ms window, 10ms hop)
+const WINDOW_MS: usize = 20;
+const HOP_MS: usize = 10;
+
+/// Noise floor / speech level percentiles: robust to speech filling most of the recording
+const FLOOR_PERCENTILE: f32 = 0.10;
+const SPEECH_PERCENTILE: f32 = 0.90;
+
+/// Spread floors: pure tones and digital silence have (near) zero spread
+const MIN_TILT_SPREAD_DB: f32 = 1.0;
+const MIN_PITCH_SPREAD_SEMITONES: f32 = 1.0;
+
+#[derive(Clone, Debug)]
+pub struct DiarizationConfig {
+    /// Threshold never closer than this to the noise floor
+    pub min_margin_db: f32,
+    /// Speech regions shorter than this are dropped (clicks, door slams)
+    pub min_speech_ms: usize,
+    /// Silences shorter than this stay inside a region (hangover)
+    pub min_silence_ms: usize,
+    /// Window for speaker statistics
+    pub window_ms: usize,
+    /// Voice-profile distance (in within-window spreads) that separates speakers
+    pub speaker_distance: f32,
+}
+
+impl Default for DiarizationConfig {
+    fn default() -> Self {
+        // Hangover: longer than a stop closure (~100ms), shorter than a turn-taking pause
+        DiarizationConfig {
+            min_margin_db: 6.0,
+            min_speech_ms: 100,
+            min_silence_ms: 200,
+            window_ms: 1000,
+            speaker_distance: 2.0,
+        }
+    }
+}
+
+/// One analysis frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub start_ms: usize,
+    pub energy_db: f32,
+    /// MIDI note (semitones) of voiced frames
+    pub pitch: Option<u8>,
+    /// LOW - MID band energy (dB)
+    pub tilt_low: f32,
+    /// MID - HIGH band energy (dB)
+    pub tilt_high: f32,
+}
+
+/// Frame features over the whole recording
+pub fn analyze_frames(samples: &[f32], sample_rate: u32) -> Vec<Frame> {
+    let window_size = (sample_rate as usize * WINDOW_MS) / 1000;
+    let hop_size = (sample_rate as usize * HOP_MS) / 1000;
+    if window_size == 0 || hop_size == 0 {
+        return Vec::new();
+    }
+
+    let analyzer = SpectralAnalyzer::with_window(window_size, sample_rate, WindowFunction::Hann);
+    (0..)
+        .map(|i| i * hop_size)
+        .take_while(|&pos| pos + window_size <= samples.len())
+        .map(|pos| {
+            let window = &samples[pos..pos + window_size];
+            let (low, mid, high) = analyzer.power_spectrum(window).band_energies_db();
+            Frame {
+                start_ms: (pos * 1000) / sample_rate as usize,
+                energy_db: energy_db(window),
+                pitch: detect_pitch(window, sample_rate).map(|p| p.midi_note),
+                tilt_low: low - mid,
+                tilt_high: mid - high,
+            }
+        })
+        .collect()
+}
+
+/// Mean power in dB (-60 for digital silence, as in the segmenter)
+fn energy_db(window: &[f32]) -> f32 {
+    let energy = window.iter().map(|x| x * x).sum::<f32>() / window.len().max(1) as f32;
+    if energy > 0.0 { 10.0 * energy.log10() } else { -60.0 }
+}
+
+fn percentile(values: &[f32], q: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted[((sorted.len() - 1) as f32 * q).round() as usize]
+}
+
+// ============================================================================
+// ADAPTIVE VAD
+// ============================================================================
+
+/// Energy levels derived from the recording
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseFloor {
+    pub floor_db: f32,
+    pub speech_db: f32,
+    pub threshold_db: f32,
+}
+
+/// Threshold halfway (in dB) between noise floor and speech level
+///
+/// Derived, not hardcoded: digital silence (-60 dB) under speech at -10 dB
+/// gives -35 dB, the segmenter's fixed threshold. Noise at -30 dB moves it up.
+pub fn estimate_noise_floor(frames: &[Frame], min_margin_db: f32) -> NoiseFloor {
+    let energies: Vec<f32> = frames.iter().map(|f| f.energy_db).collect();
+    let floor_db = percentile(&energies, FLOOR_PERCENTILE);
+    let speech_db = percentile(&energies, SPEECH_PERCENTILE);
+    let margin = ((speech_db - floor_db) / 2.0).max(min_margin_db);
+    NoiseFloor { floor_db, speech_db, threshold_db: floor_db + margin }
+}
+
+/// Speech regions as frame ranges: above-threshold runs, short gaps bridged, short runs dropped
+pub fn speech_regions(frames: &[Frame], threshold_db: f32, config: &DiarizationConfig) -> Vec<Range<usize>> {
+    let min_gap = config.min_silence_ms.div_ceil(HOP_MS);
+    let min_len = config.min_speech_ms.div_ceil(HOP_MS);
+
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    let mut i = 0;
+    while i < frames.len() {
+        if frames[i].energy_db < threshold_db {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < frames.len() && frames[i].energy_db >= threshold_db {
+            i += 1;
+        }
+        match runs.last_mut() {
+            Some(last) if start - last.end < min_gap => last.end = i,
+            _ => runs.push(start..i),
+        }
+    }
+
+    runs.retain(|r| r.len() >= min_len);
+    runs
+}
+
+// ============================================================================
+// SPEAKER CHANGE
+// ============================================================================
+
+/// Mean pitch and band tilt over a stretch of speech
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoiceProfile {
+    /// Mean MIDI note over the voiced frames
+    pub pitch: Option<f32>,
+    pub tilt_low: f32,
+    pub tilt_high: f32,
+    pub frames: usize,
+    pub voiced: usize,
+}
+
+/// Within-speaker spread of each feature (the distance unit)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeatureSpread {
+    pub pitch: f32,
+    pub tilt_low: f32,
+    pub tilt_high: f32,
+}
+
+impl VoiceProfile {
+    pub fn of(frames: &[Frame]) -> Self {
+        let n = frames.len().max(1) as f32;
+        let pitches: Vec<f32> = frames.iter().filter_map(|f| f.pitch).map(f32::from).collect();
+        VoiceProfile {
+            pitch: (!pitches.is_empty()).then(|| pitches.iter().sum::<f32>() / pitches.len() as f32),
+            tilt_low: frames.iter().map(|f| f.tilt_low).sum::<f32>() / n,
+            tilt_high: frames.iter().map(|f| f.tilt_high).sum::<f32>() / n,
+            frames: frames.len(),
+            voiced: pitches.len(),
+        }
+    }
+
+    /// Frame-weighted union of two profiles
+    pub fn merge(&mut self, other: &VoiceProfile) {
+        let total = (self.frames + other.frames).max(1) as f32;
+        let (a, b) = (self.frames as f32 / total, other.frames as f32 / total);
+        self.tilt_low = a * self.tilt_low + b * other.tilt_low;
+        self.tilt_high = a * self.tilt_high + b * other.tilt_high;
+        self.pitch = match (self.pitch, other.pitch) {
+            (Some(p), Some(q)) => {
+                let voiced = (self.voiced + other.voiced) as f32;
+                Some((p * self.voiced as f32 + q * other.voiced as f32) / voiced)
+            }
+            (p, q) => p.or(q),
+        };
+        self.frames += other.frames;
+        self.voiced += other.voiced;
+    }
+
+    /// RMS of the feature differences, each in units of its spread
+    ///
+    /// Pitch only counts when both sides are voiced: an unvoiced stretch
+    /// says nothing about who is talking.
+    pub fn distance(&self, other: &VoiceProfile, spread: &FeatureSpread) -> f32 {
+        let mut sum = ((self.tilt_low - other.tilt_low) / spread.tilt_low).powi(2)
+            + ((self.tilt_high - other.tilt_high) / spread.tilt_high).powi(2);
+        let mut dims = 2.0;
+        if let (Some(p), Some(q)) = (self.pitch, other.pitch) {
+            sum += ((p - q) / spread.pitch).powi(2);
+            dims += 1.0;
+        }
+        (sum / dims).sqrt()
+    }
+}
+
+fn std_dev(values: &[f32]) -> Option<f32> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    Some((values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32).sqrt())
+}
+
+/// Median within-window standard deviation of each feature
+///
+/// Derived, not hardcoded: the median ignores the few windows that straddle
+/// a speaker change, so the spread is that of ONE voice.
+pub fn feature_spread(windows: &[&[Frame]]) -> FeatureSpread {
+    let median_std = |feature: &dyn Fn(&Frame) -> Option<f32>, min: f32| {
+        let stds: Vec<f32> = windows.iter()
+            .filter_map(|w| std_dev(&w.iter().filter_map(feature).collect::<Vec<_>>()))
+            .collect();
+        if stds.is_empty() { min } else { percentile(&stds, 0.5).max(min) }
+    };
+    FeatureSpread {
+        pitch: median_std(&|f| f.pitch.map(f32::from), MIN_PITCH_SPREAD_SEMITONES),
+        tilt_low: median_std(&|f| Some(f.tilt_low), MIN_TILT_SPREAD_DB),
+        tilt_high: median_std(&|f| Some(f.tilt_high), MIN_TILT_SPREAD_DB),
+    }
+}
+
+/// Frame ranges of `window_frames` within a region; a short tail joins the last window
+fn windows_of(region: &Range<usize>, window_frames: usize) -> Vec<Range<usize>> {
+    let mut windows: Vec<Range<usize>> = Vec::new();
+    let mut start = region.start;
+    while start < region.end {
+        let end = (start + window_frames).min(region.end);
+        match windows.last_mut() {
+            Some(last) if end - start < window_frames / 2 => last.end = end,
+            _ => windows.push(start..end),
+        }
+        start = end;
+    }
+    windows
+}
+
+/// Split point within frames[range] that best separates the two sides
+fn refine_change(frames: &[Frame], range: Range<usize>, min_frames: usize, spread: &FeatureSpread) -> usize {
+    let lo = range.start + min_frames;
+    let hi = range.end.saturating_sub(min_frames);
+    if lo >= hi {
+        return (range.start + range.end) / 2;
+    }
+    (lo..hi)
+        .map(|k| (k, VoiceProfile::of(&frames[range.start..k]).distance(&VoiceProfile::of(&frames[k..range.end]), spread)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(k, _)| k)
+        .unwrap_or(lo)
+}
+
+// ============================================================================
+// TURNS
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeakerTurn {
+    /// 0-based speaker index, in order of first appearance
+    pub speaker: usize,
+    pub start_ms: usize,
+    pub end_ms: usize,
+    pub profile: VoiceProfile,
+}
+
+/// Time range of a frame span
+///
+/// A span ends where the next frame starts: at rates where the hop is not a
+/// whole number of milliseconds (22050 Hz: 220 samples = 9.98 ms), start +
+/// HOP_MS would overlap the next span.
+fn span_ms(frames: &[Frame], span: &Range<usize>) -> (usize, usize) {
+    let start_ms = frames[span.start].start_ms;
+    let end_ms = frames.get(span.end).map_or(frames[span.end - 1].start_ms + HOP_MS, |f| f.start_ms);
+    (start_ms, end_ms)
+}
+
+#[derive(Clone, Debug)]
+pub struct Diarization {
+    pub noise: NoiseFloor,
+    pub spread: FeatureSpread,
+    pub turns: Vec<SpeakerTurn>,
+    pub speakers: usize,
+}
+
+pub fn speaker_label(speaker: usize) -> String {
+    format!("SPEAKER_{:02}", speaker)
+}
+
+/// VAD, speaker change, and speaker clustering over a whole recording
+pub fn diarize(samples: &[f32], sample_rate: u32, config: &DiarizationConfig) -> Diarization {
+    let frames = analyze_frames(samples, sample_rate);
+    let noise = estimate_noise_floor(&frames, config.min_margin_db);
+    let regions = speech_regions(&frames, noise.threshold_db, config);
+
+    let window_frames = (config.window_ms / HOP_MS).max(2);
+    let windows: Vec<Vec<Range<usize>>> = regions.iter().map(|r| windows_of(r, window_frames)).collect();
+    let window_slices: Vec<&[Frame]> = windows.iter().flatten().map(|w| &frames[w.clone()]).collect();
+    let spread = feature_spread(&window_slices);
+
+    // Step 1: cut each region where adjacent windows sound like different voices
+    let min_frames = config.min_speech_ms.div_ceil(HOP_MS);
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    for (region, region_windows) in regions.iter().zip(&windows) {
+        let mut turn_start = region.start;
+        for pair in region_windows.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            let distance = VoiceProfile::of(&frames[a.clone()]).distance(&VoiceProfile::of(&frames[b.clone()]), &spread);
+            if distance > config.speaker_distance {
+                let cut = refine_change(&frames, turn_start.max(a.start)..b.end, min_frames, &spread);
+                if cut > turn_start {
+                    spans.push(turn_start..cut);
+                    turn_start = cut;
+                }
+            }
+        }
+        spans.push(turn_start..region.end);
+    }
+
+    // Step 2: greedy nearest-centroid clustering, in time order
+    let mut centroids: Vec<VoiceProfile> = Vec::new();
+    let mut turns: Vec<SpeakerTurn> = Vec::new();
+    for span in spans {
+        let profile = VoiceProfile::of(&frames[span.clone()]);
+        let nearest = centroids.iter().enumerate()
+            .map(|(i, c)| (i, c.distance(&profile, &spread)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let speaker = match nearest {
+            Some((i, d)) if d <= config.speaker_distance => {
+                centroids[i].merge(&profile);
+                i
+            }
+            _ => {
+                centroids.push(profile);
+                centroids.len() - 1
+            }
+        };
+        let (start_ms, end_ms) = span_ms(&frames, &span);
+
+        // Step 3: a change that clusters back onto the same speaker was a false alarm
+        match turns.last_mut() {
+            Some(last) if last.speaker == speaker && start_ms.saturating_sub(last.end_ms) < config.min_silence_ms => {
+                last.end_ms = end_ms;
+                last.profile.merge(&profile);
+            }
+            _ => turns.push(SpeakerTurn { speaker, start_ms, end_ms, profile }),
+        }
+    }
+
+    Diarization { noise, spread, turns, speakers: centroids.len() }
+}
+
+/// Transcript of one speaker turn
+#[derive(Clone, Debug)]
+pub struct TurnTranscript {
+    pub speaker: usize,
+    pub start_ms: usize,
+    pub end_ms: usize,
+    pub text: String,
+    /// Word times relative to the start of the recording
+    pub words: Vec<TimedWord>,
+}
+
+/// Gate and level one turn for the fixed-threshold segmenter
+///
+/// Hops below the adaptive threshold become digital silence, and the gain
+/// moves the adaptive threshold onto SILENCE_DB: what the VAD calls speech,
+/// saturate_segment_boundaries calls speech too.
+fn gated_turn(samples: &[f32], sample_rate: u32, turn: &SpeakerTurn, noise: &NoiseFloor) -> Vec<f32> {
+    let to_sample = |ms: usize| (ms * sample_rate as usize / 1000).min(samples.len());
+    let gain = 10f32.powf((SILENCE_DB - noise.threshold_db) / 20.0);
+    let hop = (sample_rate as usize * HOP_MS / 1000).max(1);
+
+    let mut audio = samples[to_sample(turn.start_ms)..to_sample(turn.end_ms)].to_vec();
+    for chunk in audio.chunks_mut(hop) {
+        let keep = energy_db(chunk) >= noise.threshold_db;
+        for x in chunk.iter_mut() {
+            *x = if keep { *x * gain } else { 0.0 };
+        }
+    }
+    audio
+}
+
+/// Transcribe each turn separately (times relative to the recording)
+pub fn transcribe_turns(
+    transcriber: &mut SaturatedTranscriber,
+    samples: &[f32],
+    sample_rate: u32,
+    diarization: &Diarization,
+) -> Vec<TurnTranscript> {
+    diarization.turns.iter().map(|turn| {
+        let audio = gated_turn(samples, sample_rate, turn, &diarization.noise);
+        let (text, _, phonemes) = transcriber.transcribe_detailed(&audio, sample_rate);
+        let shifted: Vec<_> = phonemes.iter().map(|&(p, s, e)| (p, s + turn.start_ms, e + turn.start_ms)).collect();
+        TurnTranscript {
+            speaker: turn.speaker,
+            start_ms: turn.start_ms,
+            end_ms: turn.end_ms,
+            text,
+            words: transcriber.words_with_timings(&shifted),
+        }
+    }).collect()
+}
+
+fn stamp(ms: usize) -> String {
+    format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
+
+/// One line per turn: `[start - end] SPEAKER_nn: TEXT`
+pub fn labelled_transcript(turns: &[TurnTranscript]) -> String {
+    turns.iter()
+        .map(|t| format!("[{} - {}] {}: {}\n", stamp(t.start_ms), stamp(t.end_ms), speaker_label(t.speaker), t.text))
+        .collect()
+}
+
+/// NIST RTTM speaker segments (the format diarization scorers read)
+pub fn rttm(turns: &[SpeakerTurn], file_id: &str) -> String {
+    let mut out = String::new();
+    for t in turns {
+        let _ = writeln!(out, "SPEAKER {} 1 {:.3} {:.3} <NA> <NA> {} <NA> <NA>",
+            file_id, t.start_ms as f32 / 1000.0, (t.end_ms - t.start_ms) as f32 / 1000.0, speaker_label(t.speaker));
+    }
+    out
+}
+
+/// CLI: `<audio> [--rate HZ] [--model PATH] [--rttm PATH]`
+///
+/// Prints the speaker-labelled transcript (see labelled_transcript).
+pub fn run_diarize(args: &[String]) -> Result<Vec<TurnTranscript>, Box<dyn std::error::Error>> {
+    let mut positional = Vec::new();
+    let mut target_rate = TARGET_SAMPLE_RATE;
+    let mut model_path = None;
+    let mut rttm_path = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--rate" => target_rate = iter.next().ok_or("--rate needs a value")?.parse()?,
+            "--model" => model_path = Some(PathBuf::from(iter.next().ok_or("--model needs a value")?)),
+            "--rttm" => rttm_path = Some(PathBuf::from(iter.next().ok_or("--rttm needs a value")?)),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [audio] = &positional[..] else {
+        return Err("usage: <audio> [--rate HZ] [--model PATH] [--rttm PATH]".into());
+    };
+
+    let mut transcriber = match &model_path {
+        Some(path) => SaturatedTranscriber::load_model(path)?,
+        None => SaturatedTranscriber::new(),
+    };
+
+    let buffer = load_audio(audio, None, target_rate)?;
+    let diarization = diarize(&buffer.samples, buffer.sample_rate, &DiarizationConfig::default());
+    let turns = transcribe_turns(&mut transcriber, &buffer.samples, buffer.sample_rate, &diarization);
+    print!("{}", labelled_transcript(&turns));
+
+    if let Some(path) = &rttm_path {
+        let file_id = audio.file_stem().and_then(|s| s.to_str()).unwrap_or("audio");
+        std::fs::write(path, rttm(&diarization.turns, file_id))?;
+    }
+    Ok(turns)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const RATE: u32 = 16_000;
+
+    fn tone(freq: f32, amplitude: f32, ms: usize) -> Vec<f32> {
+        tone_at(RATE, freq, amplitude, ms)
+    }
+
+    fn tone_at(rate: u32, freq: f32, amplitude: f32, ms: usize) -> Vec<f32> {
+        (0..rate as usize * ms / 1000)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    /// Voice A (low band) hands straight over to voice B (mid band), then A again after a pause
+    fn handover(rate: u32) -> Vec<f32> {
+        let mut audio = tone_at(rate, 200.0, 0.4, 1500);
+        audio.extend(tone_at(rate, 1200.0, 0.4, 1500));
+        audio.extend(vec![0.0; rate as usize / 2]);
+        audio.extend(tone_at(rate, 200.0, 0.4, 1000));
+        audio
+    }
+
+    /// Deterministic uniform noise in [-amplitude, amplitude]
+    fn add_noise(samples: &mut [f32], amplitude: f32) {
+        let mut state: u32 = 12345;
+        for x in samples.iter_mut() {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            *x += amplitude * ((state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0);
+        }
+    }
+
+    #[test]
+    fn test_noise_floor_adapts() {
+        let mut audio = vec![0.0; RATE as usize / 2];
+        audio.extend(tone(300.0, 0.5, 600));
+        audio.extend(vec![0.0; RATE as usize / 2]);
+        audio.extend(tone(300.0, 0.5, 400));
+        audio.extend(vec![0.0; RATE as usize / 2]);
+        add_noise(&mut audio, 0.05);
+
+        let config = DiarizationConfig::default();
+        let frames = analyze_frames(&audio, RATE);
+        let noise = estimate_noise_floor(&frames, config.min_margin_db);
+        // Noise sits above the fixed threshold: every frame would be "speech"
+        assert!(noise.floor_db > SILENCE_DB, "floor {}", noise.floor_db);
+        assert!(noise.threshold_db > noise.floor_db + config.min_margin_db - 1e-3);
+
+        let regions = speech_regions(&frames, noise.threshold_db, &config);
+        let spans: Vec<(usize, usize)> = regions.iter()
+            .map(|r| (frames[r.start].start_ms, frames[r.end - 1].start_ms + HOP_MS))
+            .collect();
+        assert_eq!(spans.len(), 2, "{:?}", spans);
+        assert!(spans[0].0.abs_diff(500) <= 20 && spans[0].1.abs_diff(1100) <= 20, "{:?}", spans);
+        assert!(spans[1].0.abs_diff(1600) <= 20 && spans[1].1.abs_diff(2000) <= 20, "{:?}", spans);
+    }
+
+    #[test]
+    fn test_speaker_change_without_pause() {
+        let audio = handover(RATE);
+        let result = diarize(&audio, RATE, &DiarizationConfig::default());
+        let speakers: Vec<usize> = result.turns.iter().map(|t| t.speaker).collect();
+        assert_eq!(speakers, vec![0, 1, 0], "{:?}", result.turns);
+        assert_eq!(result.speakers, 2);
+        assert!(result.turns[0].end_ms.abs_diff(1500) <= 30, "{:?}", result.turns);
+        assert!(result.turns[2].start_ms.abs_diff(3500) <= 30, "{:?}", result.turns);
+        assert!(result.turns.windows(2).all(|w| w[0].end_ms <= w[1].start_ms));
+
+        let transcripts = transcribe_turns(&mut SaturatedTranscriber::new(), &audio, RATE, &result);
+        assert_eq!(transcripts.len(), result.turns.len());
+        for t in &transcripts {
+            assert!(t.words.iter().all(|w| w.start_ms >= t.start_ms && w.end_ms <= t.end_ms + HOP_MS), "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn test_turns_at_non_integer_hop() {
+        // 22050 Hz: the 220-sample hop is 9.98 ms, so frame starts drift from i * HOP_MS
+        let result = diarize(&handover(22_050), 22_050, &DiarizationConfig::default());
+        let speakers: Vec<usize> = result.turns.iter().map(|t| t.speaker).collect();
+        assert_eq!(speakers, vec![0, 1, 0], "{:?}", result.turns);
+        assert_eq!(result.turns[0].end_ms, result.turns[1].start_ms, "Back-to-back turns share a boundary");
+        assert!(result.turns[0].end_ms.abs_diff(1500) <= 30, "{:?}", result.turns);
+        assert!(result.turns[1].end_ms < result.turns[2].start_ms);
+    }
+
+    #[test]
+    fn test_spans_never_overlap_at_22050() {
+        let frames = analyze_frames(&vec![0.0; 22_050], 22_050);
+        for cut in 1..frames.len() {
+            let (_, end_ms) = span_ms(&frames, &(0..cut));
+            let (start_ms, _) = span_ms(&frames, &(cut..frames.len()));
+            assert_eq!(end_ms, start_ms, "Span cut at frame {cut}");
+        }
+    }
+
+    #[test]
+    fn test_labelled_output() {
+        let profile = VoiceProfile { pitch: None, tilt_low: 0.0, tilt_high: 0.0, frames: 1, voiced: 0 };
+        let turns = vec![
+            SpeakerTurn { speaker: 0, start_ms: 500, end_ms: 1750, profile },
+            SpeakerTurn { speaker: 1, start_ms: 1750, end_ms: 62_000, profile },
+        ];
+        assert_eq!(rttm(&turns, "meeting"),
+            "SPEAKER meeting 1 0.500 1.250 <NA> <NA> SPEAKER_00 <NA> <NA>\n\
+             SPEAKER meeting 1 1.750 60.250 <NA> <NA> SPEAKER_01 <NA> <NA>\n");
+
+        let transcripts: Vec<TurnTranscript> = turns.iter().map(|t| TurnTranscript {
+            speaker: t.speaker, start_ms: t.start_ms, end_ms: t.end_ms, text: "HI".to_string(), words: Vec::new(),
+        }).collect();
+        assert_eq!(labelled_transcript(&transcripts).lines().nth(1),
+            Some("[00:00:01.750 - 00:01:02.000] SPEAKER_01: HI"));
+    }
+}
@@ -0,0 +1,473 @@
+//! EPD Test-Suite Runner: Saturation vs Fixed-Depth Search
+//!
+//! Loads EPD suites (WAC, STS, Bratko-Kopec) with `bm` / `am` operations and
+//! runs every position through:
+//! - search_until_saturated   (depth chosen by saturation / node bound)
+//! - search_to_depth_analyzed (fixed target depth, saturation only observed)
+//!
+//! Reports solve rate, nodes, time and the depth where saturation was
+//! declared. Positions the fixed-depth search solves but saturation misses
+//! are listed separately: they show where derive_epsilon or
+//! PositionComplexity::recommended_depth stop too early on tactics.
+
+use super::bitboard::Square;
+use super::framework_search::FrameworkSearchEngine;
+use super::movegen::Move;
+use super::position::{PieceType, Position};
+use super::saturation::{
+    derive_epsilon, search_to_depth_analyzed, search_with_limits, PositionComplexity, SearchLimits,
+    SilentObserver, StopReason, TraceObserver,
+};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+// ============================================================================
+// EPD PARSING
+// ============================================================================
+
+/// One EPD record: position + expected moves (SAN or UCI, as in the file)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpdEntry {
+    pub id: Option<String>,
+    /// Full FEN (EPD's 4 fields + halfmove/fullmove counters)
+    pub fen: String,
+    pub best_moves: Vec<String>,
+    pub avoid_moves: Vec<String>,
+}
+
+impl EpdEntry {
+    /// Parse one EPD line. Blank lines and `#` comments return None.
+    pub fn parse(line: &str) -> Option<Result<EpdEntry, String>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        // Four FEN fields separated by any run of whitespace, then operations
+        let mut fields = Vec::with_capacity(4);
+        let mut rest = line;
+        while fields.len() < 4 {
+            let Some(field) = rest.split_whitespace().next() else { break };
+            let start = rest.len() - rest.trim_start().len();
+            fields.push(field);
+            rest = &rest[start + field.len()..];
+        }
+        if fields.len() < 4 {
+            return Some(Err(format!("expected 4 EPD fields: '{}'", line)));
+        }
+
+        let mut entry = EpdEntry {
+            id: None,
+            fen: String::new(),
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+        };
+        let (mut halfmove, mut fullmove) = ("0".to_string(), "1".to_string());
+
+        for (opcode, operands) in parse_operations(rest) {
+            match opcode.as_str() {
+                "bm" => entry.best_moves = operands,
+                "am" => entry.avoid_moves = operands,
+                "id" => entry.id = Some(operands.join(" ")),
+                "hmvc" => halfmove = operands.first().cloned().unwrap_or(halfmove),
+                "fmvn" => fullmove = operands.first().cloned().unwrap_or(fullmove),
+                _ => {}
+            }
+        }
+
+        entry.fen = format!("{} {} {} {} {} {}", fields[0], fields[1], fields[2], fields[3], halfmove, fullmove);
+        Some(Ok(entry))
+    }
+
+    /// Parse a whole suite (one record per line)
+    pub fn parse_suite(text: &str) -> Result<Vec<EpdEntry>, String> {
+        text.lines()
+            .enumerate()
+            .filter_map(|(i, line)| Self::parse(line).map(|r| r.map_err(|e| format!("line {}: {}", i + 1, e))))
+            .collect()
+    }
+
+    /// Is `mv` a correct answer for this position?
+    /// bm: must be one of the best moves; am: must avoid all listed moves.
+    pub fn is_solved_by(&self, pos: &Position, mv: Option<Move>) -> bool {
+        let Some(mv) = mv else { return false };
+        let matches = |list: &[String]| list.iter().any(|text| matches_notation(pos, mv, text));
+
+        (self.best_moves.is_empty() || matches(&self.best_moves))
+            && !matches(&self.avoid_moves)
+    }
+}
+
+/// Split EPD operations into (opcode, operands)
+///
+/// Operations end at `;` and operands at whitespace, except inside double
+/// quotes: `id "WAC; 001"` is one operation with the operand `WAC; 001`.
+fn parse_operations(text: &str) -> Vec<(String, Vec<String>)> {
+    let mut operations = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    let mut in_token = false;
+
+    for c in text.chars().chain(std::iter::once(';')) {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_token = true;
+            }
+            c if quoted => token.push(c),
+            c if c.is_whitespace() || c == ';' => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut token));
+                    in_token = false;
+                }
+                if c == ';' && !tokens.is_empty() {
+                    let opcode = tokens.remove(0);
+                    operations.push((opcode, std::mem::take(&mut tokens)));
+                }
+            }
+            c => {
+                token.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    operations
+}
+
+/// Does `mv` match a move written in SAN ("Qxf7+", "exd5", "O-O", "e8=Q")
+/// or UCI ("g1f3") notation?
+///
+/// Works on the UCI string of the move plus the piece on its origin square,
+/// so no SAN generator is required.
+pub fn matches_notation(pos: &Position, mv: Move, text: &str) -> bool {
+    let uci = mv.to_string();
+    let san = text.trim_end_matches(['+', '#', '!', '?']);
+    if uci == san {
+        return true;
+    }
+
+    let (from, to) = (&uci[0..2], &uci[2..4]);
+    let promotion = uci.get(4..5);
+    let Some(piece) = square_from_str(from).and_then(|sq| pos.piece_at(sq)) else { return false };
+
+    // Castling: king moves two files
+    if san == "O-O" || san == "0-0" {
+        return piece.piece_type == PieceType::King && from.starts_with('e') && to.starts_with('g');
+    }
+    if san == "O-O-O" || san == "0-0-0" {
+        return piece.piece_type == PieceType::King && from.starts_with('e') && to.starts_with('c');
+    }
+
+    // Promotion suffix: "=Q" or trailing "Q"
+    let (body, promo) = match san.split_once('=') {
+        Some((body, promo)) => (body, Some(promo)),
+        None if san.ends_with(['Q', 'R', 'B', 'N']) => (&san[..san.len() - 1], Some(&san[san.len() - 1..])),
+        None => (san, None),
+    };
+    if promo.map(|p| p.to_ascii_lowercase()).as_deref() != promotion {
+        return false;
+    }
+
+    // Piece letter (absent = pawn)
+    let (san_piece, rest) = match body.chars().next() {
+        Some('K') => (PieceType::King, &body[1..]),
+        Some('Q') => (PieceType::Queen, &body[1..]),
+        Some('R') => (PieceType::Rook, &body[1..]),
+        Some('B') => (PieceType::Bishop, &body[1..]),
+        Some('N') => (PieceType::Knight, &body[1..]),
+        _ => (PieceType::Pawn, body),
+    };
+    if san_piece != piece.piece_type || rest.len() < 2 {
+        return false;
+    }
+
+    // Destination = last two chars; anything before (minus 'x') disambiguates
+    let (disambiguation, dest) = rest.split_at(rest.len() - 2);
+    dest == to && disambiguation.chars().filter(|&c| c != 'x').all(|c| from.contains(c))
+}
+
+/// "e4" → Square
+fn square_from_str(s: &str) -> Option<Square> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 2 || !(b'a'..=b'h').contains(&bytes[0]) || !(b'1'..=b'8').contains(&bytes[1]) {
+        return None;
+    }
+    Some(Square::from_file_rank(bytes[0] - b'a', bytes[1] - b'1'))
+}
+
+// ============================================================================
+// SUITE RUNNER
+// ============================================================================
+
+/// Outcome of one search mode on one position
+#[derive(Clone, Debug)]
+pub struct RunResult {
+    pub best_move: Option<String>,
+    pub score: i32,
+    pub solved: bool,
+    pub nodes: u64,
+    pub time_ms: u64,
+    /// Deepest completed depth
+    pub final_depth: i32,
+    /// Depth at which saturation was declared (None = never saturated)
+    pub saturation_depth: Option<i32>,
+    /// Why the saturation search stopped (None for fixed depth)
+    pub stop_reason: Option<StopReason>,
+}
+
+/// Both modes on one EPD position
+#[derive(Clone, Debug)]
+pub struct PositionResult {
+    pub id: String,
+    pub expected: String,
+    pub epsilon: i32,
+    pub recommended_depth: i32,
+    pub saturation: RunResult,
+    pub fixed: RunResult,
+}
+
+/// Aggregate over a suite
+#[derive(Clone, Debug, Default)]
+pub struct SuiteReport {
+    pub target_depth: i32,
+    pub positions: Vec<PositionResult>,
+}
+
+/// Run one EPD entry through both search modes
+pub fn run_position(entry: &EpdEntry, index: usize, target_depth: i32) -> Result<PositionResult, String> {
+    let pos = Position::from_fen(&entry.fen).map_err(|e| format!("bad fen '{}': {:?}", entry.fen, e))?;
+    let id = entry.id.clone().unwrap_or_else(|| format!("#{}", index + 1));
+
+    // Mode 1: saturation decides the depth
+    let mut engine = FrameworkSearchEngine::new();
+    let mut observer = TraceObserver::new(SilentObserver);
+    let start = Instant::now();
+    let (mv, score, analyzer) = search_with_limits(&mut engine, &pos, &[], &SearchLimits::default(), &mut observer);
+    let trace = observer.into_trace();
+    let saturation = RunResult {
+        best_move: mv.map(|m| m.to_string()),
+        score,
+        solved: entry.is_solved_by(&pos, mv),
+        nodes: trace.total_nodes,
+        time_ms: start.elapsed().as_millis() as u64,
+        final_depth: trace.depths.last().map_or(0, |d| d.depth),
+        saturation_depth: analyzer.depths_until_saturation,
+        stop_reason: trace.stop_reason,
+    };
+
+    // Mode 2: fixed target depth (fresh engine, no shared TT)
+    let mut engine = FrameworkSearchEngine::new();
+    let start = Instant::now();
+    let (mv, score, analyzer) = search_to_depth_analyzed(&mut engine, &pos, target_depth);
+    let fixed = RunResult {
+        best_move: mv.map(|m| m.to_string()),
+        score,
+        solved: entry.is_solved_by(&pos, mv),
+        nodes: engine.nodes,
+        time_ms: start.elapsed().as_millis() as u64,
+        final_depth: analyzer.latest().map_or(0, |(d, _, _)| d),
+        saturation_depth: analyzer.depths_until_saturation,
+        stop_reason: None,
+    };
+
+    let expected = if entry.best_moves.is_empty() {
+        format!("am {}", entry.avoid_moves.join(" "))
+    } else {
+        format!("bm {}", entry.best_moves.join(" "))
+    };
+
+    Ok(PositionResult {
+        id,
+        expected,
+        epsilon: derive_epsilon(&pos),
+        recommended_depth: PositionComplexity::analyze(&pos).recommended_depth,
+        saturation,
+        fixed,
+    })
+}
+
+/// Run a parsed suite
+pub fn run_suite(entries: &[EpdEntry], target_depth: i32) -> Result<SuiteReport, String> {
+    let positions = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| run_position(entry, i, target_depth))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SuiteReport { target_depth, positions })
+}
+
+/// Load and run an EPD file
+pub fn run_epd_file(path: &Path, target_depth: i32) -> Result<SuiteReport, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    run_suite(&EpdEntry::parse_suite(&text)?, target_depth)
+}
+
+impl SuiteReport {
+    pub fn solved(&self, fixed: bool) -> usize {
+        self.positions.iter().filter(|p| if fixed { p.fixed.solved } else { p.saturation.solved }).count()
+    }
+
+    /// Positions the fixed-depth search solves but saturation misses
+    /// (candidates for "derive_epsilon / recommended_depth stop too early")
+    pub fn early_stop_misses(&self) -> Vec<&PositionResult> {
+        self.positions.iter().filter(|p| p.fixed.solved && !p.saturation.solved).collect()
+    }
+
+    /// Mean depth at which saturation was declared (saturated positions only)
+    pub fn mean_saturation_depth(&self) -> Option<f64> {
+        let depths: Vec<i32> = self.positions.iter().filter_map(|p| p.saturation.saturation_depth).collect();
+        if depths.is_empty() {
+            return None;
+        }
+        Some(depths.iter().sum::<i32>() as f64 / depths.len() as f64)
+    }
+}
+
+impl fmt::Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.positions.len().max(1);
+        let total = |fixed: bool| -> (u64, u64) {
+            self.positions.iter().fold((0, 0), |(nodes, ms), p| {
+                let r = if fixed { &p.fixed } else { &p.saturation };
+                (nodes + r.nodes, ms + r.time_ms)
+            })
+        };
+        let (sat_nodes, sat_ms) = total(false);
+        let (fix_nodes, fix_ms) = total(true);
+
+        writeln!(f, "EPD Suite: Saturation vs Fixed Depth {}", self.target_depth)?;
+        writeln!(f, "──────────────────────────────────────────")?;
+        for p in &self.positions {
+            writeln!(f, "{:<12} {:<16} sat: {:<6} {} d{} ({}) {:>9}n {:>6}ms | fixed: {:<6} {} {:>9}n {:>6}ms",
+                p.id, p.expected,
+                p.saturation.best_move.as_deref().unwrap_or("-"),
+                if p.saturation.solved { "✓" } else { "✗" },
+                p.saturation.final_depth,
+                p.saturation.stop_reason.map_or("-".to_string(), |r| format!("{:?}", r)),
+                p.saturation.nodes, p.saturation.time_ms,
+                p.fixed.best_move.as_deref().unwrap_or("-"),
+                if p.fixed.solved { "✓" } else { "✗" },
+                p.fixed.nodes, p.fixed.time_ms)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Saturation:  {}/{} solved ({:.1}%), {} nodes, {}ms",
+            self.solved(false), self.positions.len(), 100.0 * self.solved(false) as f64 / n as f64, sat_nodes, sat_ms)?;
+        writeln!(f, "Fixed depth: {}/{} solved ({:.1}%), {} nodes, {}ms",
+            self.solved(true), self.positions.len(), 100.0 * self.solved(true) as f64 / n as f64, fix_nodes, fix_ms)?;
+        match self.mean_saturation_depth() {
+            Some(d) => writeln!(f, "Mean saturation depth: {:.1}", d)?,
+            None => writeln!(f, "Mean saturation depth: never saturated")?,
+        }
+
+        let misses = self.early_stop_misses();
+        if !misses.is_empty() {
+            writeln!(f, "\nStopped too early ({} positions solved only at fixed depth):", misses.len())?;
+            for p in misses {
+                writeln!(f, "  {} epsilon={} recommended_depth={} stopped at d{} ({:?})",
+                    p.id, p.epsilon, p.recommended_depth, p.saturation.final_depth, p.saturation.stop_reason)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::movegen::generate_legal;
+
+    /// First positions of Win At Chess (Reinfeld)
+    const WAC_SAMPLE: &str = r#"
+2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";
+8/7p/5k2/5p2/p1p2P2/Pr1pPK2/1P1R3P/8 b - - bm Rxb2; id "WAC.002";
+5rk1/1ppb3p/p1pb4/6q1/3P1p1r/2P1R2P/PP1BQ1P1/5RKB w - - bm Rg3; id "WAC.003";
+"#;
+
+    #[test]
+    fn test_parse_epd() {
+        let entries = EpdEntry::parse_suite(WAC_SAMPLE).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].id.as_deref(), Some("WAC.001"));
+        assert_eq!(entries[0].best_moves, vec!["Qg6".to_string()]);
+        assert_eq!(entries[1].fen, "8/7p/5k2/5p2/p1p2P2/Pr1pPK2/1P1R3P/8 b - - 0 1");
+
+        let am = EpdEntry::parse("r1b1k2r/pp3ppp/8/8/8/8/PP3PPP/R3K2R w KQkq - am O-O; hmvc 4; fmvn 20;").unwrap().unwrap();
+        assert_eq!(am.avoid_moves, vec!["O-O".to_string()]);
+        assert!(am.fen.ends_with(" 4 20"));
+    }
+
+    #[test]
+    fn test_parse_epd_spacing_and_quotes() {
+        // Runs of spaces/tabs between fields, `;` and spaces inside a quoted id
+        let entry = EpdEntry::parse("8/7p/5k2/5p2/p1p2P2/Pr1pPK2/1P1R3P/8  b\t-   - bm Rxb2;id \"WAC; 002 b\"; c0 \"\";")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.fen, "8/7p/5k2/5p2/p1p2P2/Pr1pPK2/1P1R3P/8 b - - 0 1");
+        assert_eq!(entry.best_moves, vec!["Rxb2".to_string()]);
+        assert_eq!(entry.id.as_deref(), Some("WAC; 002 b"));
+
+        let ops = parse_operations(r#" bm Qg6 Qh5; c0 ""; id "a;b""#);
+        assert_eq!(ops, vec![
+            ("bm".to_string(), vec!["Qg6".to_string(), "Qh5".to_string()]),
+            ("c0".to_string(), vec![String::new()]),
+            ("id".to_string(), vec!["a;b".to_string()]),
+        ]);
+
+        assert!(EpdEntry::parse("8/8/8/8 w -").unwrap().is_err(), "Three fields are not a position");
+    }
+
+    #[test]
+    fn test_san_matching() {
+        let pos = Position::starting();
+        let legal = generate_legal(&pos);
+        let find = |uci: &str| legal.iter().copied().find(|m| m.to_string() == uci).unwrap();
+
+        assert!(matches_notation(&pos, find("e2e4"), "e4"));
+        assert!(matches_notation(&pos, find("g1f3"), "Nf3"));
+        assert!(matches_notation(&pos, find("g1f3"), "Ngf3"));
+        assert!(matches_notation(&pos, find("g1f3"), "g1f3"));
+        assert!(!matches_notation(&pos, find("b1c3"), "Nf3"));
+        assert!(!matches_notation(&pos, find("e2e3"), "e4"));
+    }
+
+    #[test]
+    fn test_wac_sample_regression() {
+        // Regression benchmark in place of the old "saturation saves time"
+        // smoke test. Solve counts depend on engine strength, so only the
+        // report's shape is checked here; read the printed report for rates
+        let suite = format!("{}{}", WAC_SAMPLE, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - am f3; id \"AM.001\";\n");
+        let entries = EpdEntry::parse_suite(&suite).unwrap();
+        let report = run_suite(&entries, 8).unwrap();
+
+        println!("{}", report);
+
+        assert_eq!(report.positions.len(), 4);
+        let ids: Vec<&str> = report.positions.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["WAC.001", "WAC.002", "WAC.003", "AM.001"]);
+        assert_eq!(report.positions[0].expected, "bm Qg6");
+        assert_eq!(report.positions[3].expected, "am f3");
+
+        for p in &report.positions {
+            assert!(p.saturation.best_move.is_some() && p.fixed.best_move.is_some());
+            assert!(p.fixed.final_depth <= 8);
+        }
+
+        // am: solved means the search played anything but the avoided move
+        let am = &report.positions[3].fixed;
+        assert_eq!(am.solved, am.best_move.as_deref() != Some("f2f3"));
+
+        assert!(report.solved(true) <= 4 && report.solved(false) <= 4);
+        assert!(report.early_stop_misses().len() <= report.solved(true));
+    }
+}
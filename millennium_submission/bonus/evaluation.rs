@@ -0,0 +1,478 @@
+//! Accuracy Evaluation - WER, CER and Phoneme Error Rate
+//!
+//! Changes to the phonotactic rules (constraints, boundaries) are only
+//! improvements if they lower the error rate on held-out speech.
+//! A manifest lists utterances with reference transcripts and a split:
+//!
+//! ```text
+//! # split  path            transcript           [phonemes]
+//! train    a/001.wav       the cat sat
+//! test     a/002.wav       hello world          HH AH0 L OW1 W ER1 L D
+//! ```
+//!
+//! Tab-separated; paths are relative to the manifest. `saturate_all` runs
+//! jointly on the train split, then every test utterance is transcribed.
+//! Reference phonemes come from the optional column, else from the
+//! transcriber's lexicon (utterances with unknown words get no PER).
+//!
+//! Errors are Levenshtein alignments (substitution = insertion = deletion = 1),
+//! kept per utterance so a regression can be traced to the words it broke.
+
+use super::audio_io::{load_audio, AudioError, RawPcmFormat, TARGET_SAMPLE_RATE};
+use super::lexicon::{parse_cmu_phoneme, Lexicon};
+use super::phoneme::Phoneme;
+use super::saturated_transcriber::SaturatedTranscriber;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// ALIGNMENT
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditOp<T> {
+    Match(T),
+    Substitution { reference: T, hypothesis: T },
+    Insertion(T),
+    Deletion(T),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ErrorCounts {
+    pub substitutions: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub reference_len: usize,
+}
+
+impl ErrorCounts {
+    pub fn errors(&self) -> usize {
+        self.substitutions + self.insertions + self.deletions
+    }
+
+    /// Errors / reference length (can exceed 1 with many insertions)
+    pub fn rate(&self) -> f32 {
+        if self.reference_len == 0 {
+            if self.errors() == 0 { 0.0 } else { 1.0 }
+        } else {
+            self.errors() as f32 / self.reference_len as f32
+        }
+    }
+
+    pub fn add(&mut self, other: &ErrorCounts) {
+        self.substitutions += other.substitutions;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+        self.reference_len += other.reference_len;
+    }
+}
+
+impl fmt::Display for ErrorCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:6.2}% (S={} I={} D={} N={})",
+            self.rate() * 100.0, self.substitutions, self.insertions, self.deletions, self.reference_len)
+    }
+}
+
+/// Minimum edit alignment of `hypothesis` against `reference`
+pub fn align<T: Clone + PartialEq>(reference: &[T], hypothesis: &[T]) -> (Vec<EditOp<T>>, ErrorCounts) {
+    let (n, m) = (reference.len(), hypothesis.len());
+
+    // cost[i][j] = edits to turn reference[..i] into hypothesis[..j]
+    let mut cost = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, c) in cost[0].iter_mut().enumerate() {
+        *c = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let diagonal = cost[i - 1][j - 1] + usize::from(reference[i - 1] != hypothesis[j - 1]);
+            cost[i][j] = diagonal.min(cost[i - 1][j] + 1).min(cost[i][j - 1] + 1);
+        }
+    }
+
+    // Backtrack, preferring match/substitution so errors line up visually
+    let mut ops = Vec::new();
+    let mut counts = ErrorCounts { reference_len: n, ..ErrorCounts::default() };
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && cost[i][j] == cost[i - 1][j - 1] + usize::from(reference[i - 1] != hypothesis[j - 1]) {
+            if reference[i - 1] == hypothesis[j - 1] {
+                ops.push(EditOp::Match(reference[i - 1].clone()));
+            } else {
+                counts.substitutions += 1;
+                ops.push(EditOp::Substitution { reference: reference[i - 1].clone(), hypothesis: hypothesis[j - 1].clone() });
+            }
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && cost[i][j] == cost[i - 1][j] + 1 {
+            counts.deletions += 1;
+            ops.push(EditOp::Deletion(reference[i - 1].clone()));
+            i -= 1;
+        } else {
+            counts.insertions += 1;
+            ops.push(EditOp::Insertion(hypothesis[j - 1].clone()));
+            j -= 1;
+        }
+    }
+    ops.reverse();
+
+    (ops, counts)
+}
+
+/// sclite-style three-line rendering: REF / HYP / error codes (S, I, D)
+pub fn format_alignment<T: fmt::Display>(ops: &[EditOp<T>]) -> String {
+    let (mut reference, mut hypothesis, mut codes) = (Vec::new(), Vec::new(), Vec::new());
+    for op in ops {
+        let (r, h, c) = match op {
+            EditOp::Match(x) => (x.to_string(), x.to_string(), ""),
+            EditOp::Substitution { reference, hypothesis } => (reference.to_string(), hypothesis.to_string(), "S"),
+            EditOp::Insertion(x) => ("***".to_string(), x.to_string(), "I"),
+            EditOp::Deletion(x) => (x.to_string(), "***".to_string(), "D"),
+        };
+        let width = r.chars().count().max(h.chars().count());
+        reference.push(format!("{:width$}", r));
+        hypothesis.push(format!("{:width$}", h));
+        codes.push(format!("{:width$}", c));
+    }
+    format!("REF: {}\nHYP: {}\n     {}", reference.join(" ").trim_end(), hypothesis.join(" ").trim_end(), codes.join(" ").trim_end())
+}
+
+/// Uppercase words, punctuation other than apostrophes removed
+pub fn normalize_transcript(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|w| w.chars().filter(|c| c.is_alphanumeric() || *c == '\'').collect::<String>().to_uppercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+// ============================================================================
+// MANIFEST
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Split {
+    Train,
+    Test,
+}
+
+#[derive(Clone, Debug)]
+pub struct ManifestEntry {
+    pub split: Split,
+    pub path: PathBuf,
+    pub transcript: String,
+    pub phonemes: Option<Vec<Phoneme>>,
+}
+
+/// Parse a manifest; relative paths are resolved against `base_dir`
+pub fn parse_manifest(text: &str, base_dir: &Path) -> Result<Vec<ManifestEntry>, AudioError> {
+    let mut entries = Vec::new();
+
+    for (line_no, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let bad = |msg: &str| AudioError::Format(format!("manifest line {}: {}", line_no + 1, msg));
+
+        if fields.len() < 3 {
+            return Err(bad("expected split, path, transcript"));
+        }
+        let split = match fields[0].to_ascii_lowercase().as_str() {
+            "train" => Split::Train,
+            "test" => Split::Test,
+            other => return Err(bad(&format!("unknown split '{}'", other))),
+        };
+        let phonemes = match fields.get(3).filter(|f| !f.is_empty()) {
+            Some(column) => Some(column.split_whitespace()
+                .map(|p| parse_cmu_phoneme(p).ok_or_else(|| bad(&format!("unknown phoneme '{}'", p))))
+                .collect::<Result<Vec<_>, _>>()?),
+            None => None,
+        };
+
+        entries.push(ManifestEntry {
+            split,
+            path: base_dir.join(fields[1]),
+            transcript: fields[2].to_string(),
+            phonemes,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Reference phonemes from the lexicon (first pronunciation of each word)
+pub fn lexicon_phonemes(lexicon: &Lexicon, words: &[String]) -> Option<Vec<Phoneme>> {
+    let mut phonemes = Vec::new();
+    for word in words {
+        phonemes.extend_from_slice(lexicon.pronunciation(word)?);
+    }
+    Some(phonemes)
+}
+
+// ============================================================================
+// EVALUATION
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct UtteranceResult {
+    pub path: PathBuf,
+    pub reference: String,
+    pub hypothesis: String,
+    pub words: ErrorCounts,
+    pub chars: ErrorCounts,
+    /// None if the reference pronunciation is unknown
+    pub phonemes: Option<ErrorCounts>,
+    pub word_alignment: Vec<EditOp<String>>,
+    pub phoneme_alignment: Vec<EditOp<Phoneme>>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EvaluationReport {
+    pub train_utterances: usize,
+    pub utterances: Vec<UtteranceResult>,
+    pub words: ErrorCounts,
+    pub chars: ErrorCounts,
+    pub phonemes: ErrorCounts,
+    /// Train and test utterances that could not be loaded (path, reason)
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+impl fmt::Display for EvaluationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for u in &self.utterances {
+            writeln!(f, "{}", u.path.display())?;
+            writeln!(f, "{}", format_alignment(&u.word_alignment))?;
+            writeln!(f, "  WER {}  CER {}", u.words, u.chars)?;
+            if let Some(per) = &u.phonemes {
+                writeln!(f, "  PER {}", per)?;
+            }
+            writeln!(f)?;
+        }
+        for (path, reason) in &self.skipped {
+            writeln!(f, "skipped {}: {}", path.display(), reason)?;
+        }
+        writeln!(f, "Evaluation: {} test utterances ({} train)", self.utterances.len(), self.train_utterances)?;
+        writeln!(f, "  WER {}", self.words)?;
+        writeln!(f, "  CER {}", self.chars)?;
+        write!(f, "  PER {}", self.phonemes)
+    }
+}
+
+/// Score one hypothesis against its reference
+pub fn score_utterance(
+    path: PathBuf,
+    reference: &str,
+    hypothesis: &str,
+    reference_phonemes: Option<&[Phoneme]>,
+    hypothesis_phonemes: &[Phoneme],
+) -> UtteranceResult {
+    let ref_words = normalize_transcript(reference);
+    let hyp_words = normalize_transcript(hypothesis);
+    let (word_alignment, words) = align(&ref_words, &hyp_words);
+
+    let ref_chars: Vec<char> = ref_words.join(" ").chars().collect();
+    let hyp_chars: Vec<char> = hyp_words.join(" ").chars().collect();
+    let (_, chars) = align(&ref_chars, &hyp_chars);
+
+    // Repeats collapsed on both sides, as in word decoding
+    let mut hyp_collapsed = hypothesis_phonemes.to_vec();
+    hyp_collapsed.dedup();
+    let (phoneme_alignment, phonemes) = match reference_phonemes {
+        Some(reference) => {
+            let mut ref_collapsed = reference.to_vec();
+            ref_collapsed.dedup();
+            let (ops, counts) = align(&ref_collapsed, &hyp_collapsed);
+            (ops, Some(counts))
+        }
+        None => (Vec::new(), None),
+    };
+
+    UtteranceResult {
+        path,
+        reference: ref_words.join(" "),
+        hypothesis: hyp_words.join(" "),
+        words,
+        chars,
+        phonemes,
+        word_alignment,
+        phoneme_alignment,
+    }
+}
+
+/// Saturate on the train split, transcribe and score the test split
+pub fn evaluate(
+    transcriber: &mut SaturatedTranscriber,
+    entries: &[ManifestEntry],
+    raw: &RawPcmFormat,
+    target_rate: u32,
+) -> EvaluationReport {
+    let mut report = EvaluationReport::default();
+
+    let mut train: Vec<Vec<f32>> = Vec::new();
+    for entry in entries.iter().filter(|e| e.split == Split::Train) {
+        match load_audio(&entry.path, Some(raw), target_rate) {
+            Ok(buffer) => train.push(buffer.samples),
+            Err(err) => report.skipped.push((entry.path.clone(), err.to_string())),
+        }
+    }
+    report.train_utterances = train.len();
+    if !train.is_empty() {
+        let all: Vec<&[f32]> = train.iter().map(|s| s.as_slice()).collect();
+        transcriber.saturate_all(&all, target_rate);
+    }
+
+    for entry in entries.iter().filter(|e| e.split == Split::Test) {
+        let buffer = match load_audio(&entry.path, Some(raw), target_rate) {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                report.skipped.push((entry.path.clone(), err.to_string()));
+                continue;
+            }
+        };
+
+        let (text, _, phoneme_sequence) = transcriber.transcribe_detailed(&buffer.samples, buffer.sample_rate);
+        let hyp_phonemes: Vec<Phoneme> = phoneme_sequence.iter().map(|(p, _, _)| *p).collect();
+        let ref_phonemes = entry.phonemes.clone()
+            .or_else(|| lexicon_phonemes(transcriber.lexicon(), &normalize_transcript(&entry.transcript)));
+
+        let result = score_utterance(entry.path.clone(), &entry.transcript, &text, ref_phonemes.as_deref(), &hyp_phonemes);
+        report.words.add(&result.words);
+        report.chars.add(&result.chars);
+        if let Some(per) = &result.phonemes {
+            report.phonemes.add(per);
+        }
+        report.utterances.push(result);
+    }
+
+    report
+}
+
+/// CLI: `<manifest.tsv> [--rate HZ] [--model PATH] [--lexicon CMUDICT] [--report PATH]`
+///
+/// Prints per-utterance alignments and corpus WER/CER/PER; `--report`
+/// also writes them to a file. `--model` starts from a saved state.
+pub fn run_evaluate(args: &[String]) -> Result<EvaluationReport, AudioError> {
+    let mut positional = Vec::new();
+    let mut target_rate = TARGET_SAMPLE_RATE;
+    let mut model_path = None;
+    let mut lexicon_path = None;
+    let mut report_path = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().cloned().ok_or_else(|| AudioError::Format(format!("{} needs a value", name)));
+        match arg.as_str() {
+            "--rate" => target_rate = value(arg)?.parse().map_err(|_| AudioError::Format("--rate needs a number".to_string()))?,
+            "--model" => model_path = Some(PathBuf::from(value(arg)?)),
+            "--lexicon" => lexicon_path = Some(PathBuf::from(value(arg)?)),
+            "--report" => report_path = Some(PathBuf::from(value(arg)?)),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    let Some(manifest) = positional.first() else {
+        return Err(AudioError::Format("usage: <manifest.tsv> [--rate HZ] [--model PATH] [--lexicon CMUDICT] [--report PATH]".to_string()));
+    };
+    let base_dir = manifest.parent().unwrap_or(Path::new("."));
+    let entries = parse_manifest(&fs::read_to_string(manifest)?, base_dir)?;
+
+    let mut transcriber = match &model_path {
+        Some(path) => SaturatedTranscriber::load_model(path)
+            .map_err(|e| AudioError::Format(format!("{}: {}", path.display(), e)))?,
+        None => SaturatedTranscriber::new(),
+    };
+    if let Some(path) = &lexicon_path {
+        let (lexicon, skipped) = Lexicon::load_cmudict(path, None)?;
+        if skipped > 0 {
+            eprintln!("{}: skipped {} unparsable entries", path.display(), skipped);
+        }
+        transcriber.set_lexicon(lexicon);
+    }
+
+    let report = evaluate(&mut transcriber, &entries, &RawPcmFormat::default(), target_rate);
+    println!("{}", report);
+    if let Some(path) = &report_path {
+        fs::write(path, format!("{}\n", report))?;
+    }
+
+    Ok(report)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        normalize_transcript(text)
+    }
+
+    #[test]
+    fn test_alignment_counts() {
+        let (ops, counts) = align(&words("the cat sat on the mat"), &words("the bat sat the mat down"));
+        assert_eq!((counts.substitutions, counts.deletions, counts.insertions), (1, 1, 1));
+        assert_eq!(counts.reference_len, 6);
+        assert!((counts.rate() - 0.5).abs() < 1e-6);
+        assert_eq!(ops[1], EditOp::Substitution { reference: "CAT".to_string(), hypothesis: "BAT".to_string() });
+        assert_eq!(ops[3], EditOp::Deletion("ON".to_string()));
+        assert_eq!(ops.last(), Some(&EditOp::Insertion("DOWN".to_string())));
+
+        let rendered = format_alignment(&ops);
+        assert!(rendered.starts_with("REF: THE CAT SAT ON  THE MAT ***\nHYP: THE BAT SAT *** THE MAT DOWN"));
+
+        // Empty reference: any output is 100% error
+        assert_eq!(align::<char>(&[], &[]).1.rate(), 0.0);
+        assert_eq!(align(&[], &['a']).1.rate(), 1.0);
+    }
+
+    #[test]
+    fn test_manifest_parsing() {
+        let text = "# split\tpath\ttranscript\ntrain\ta.wav\tHello, world!\n\ntest\tsub/b.wav\tthe cat\tDH AH0 K AE1 T\n";
+        let entries = parse_manifest(text, Path::new("/data")).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].split, Split::Train);
+        assert_eq!(entries[1].path, PathBuf::from("/data/sub/b.wav"));
+        assert_eq!(entries[1].phonemes.as_deref(), Some(&[Phoneme::DH, Phoneme::AH, Phoneme::K, Phoneme::AE, Phoneme::T][..]));
+        assert_eq!(normalize_transcript(&entries[0].transcript), vec!["HELLO", "WORLD"]);
+
+        assert!(parse_manifest("dev\ta.wav\tx\n", Path::new(".")).is_err());
+        assert!(parse_manifest("test\ta.wav\tx\tQQ\n", Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn test_score_utterance_rates() {
+        let lexicon = Lexicon::builtin();
+        let reference = lexicon_phonemes(&lexicon, &words("the love")).unwrap();
+        let hypothesis = [Phoneme::DH, Phoneme::AH, Phoneme::AH, Phoneme::L, Phoneme::AH, Phoneme::F];
+
+        let result = score_utterance(PathBuf::from("u.wav"), "The love", "THE LOSS", Some(&reference), &hypothesis);
+        assert_eq!(result.words.substitutions, 1);
+        assert!((result.words.rate() - 0.5).abs() < 1e-6);
+        // CER is over characters, including the word space
+        assert_eq!(result.chars.reference_len, "THE LOVE".len());
+        // DH AH L AH V vs DH AH L AH F (repeat collapsed): one substitution
+        assert_eq!(result.phonemes.map(|p| (p.substitutions, p.reference_len)), Some((1, 5)));
+
+        assert!(lexicon_phonemes(&lexicon, &words("xylophone")).is_none());
+    }
+
+    #[test]
+    fn test_unloadable_utterances_reported() {
+        let text = "train\tmissing_train.wav\tthe\ntest\tmissing_test.wav\tthe\n";
+        let entries = parse_manifest(text, Path::new("/nonexistent")).unwrap();
+        let report = evaluate(&mut SaturatedTranscriber::new(), &entries, &RawPcmFormat::default(), 16000);
+
+        assert_eq!(report.train_utterances, 0);
+        assert!(report.utterances.is_empty());
+        let skipped: Vec<_> = report.skipped.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(skipped, vec![PathBuf::from("/nonexistent/missing_train.wav"), PathBuf::from("/nonexistent/missing_test.wav")]);
+    }
+}
@@ -0,0 +1,325 @@
+//! Forced Alignment - Known Transcript to Audio
+//!
+//! Transcription searches for WHAT was said. For subtitling the words are
+//! known and only WHEN is missing, so the search collapses to one monotonic
+//! path: the transcript's phonemes (through the lexicon) laid over the
+//! LocatedSegments of saturate_segment_boundaries.
+//!
+//! Viterbi over (phoneme i, segment j), each step scored by the same
+//! acoustic_segment_score the decoder uses:
+//!
+//! - START:  phoneme i begins on segment j
+//! - EXTEND: phoneme i also covers segment j (long vowels, split segments)
+//! - SHARE:  phoneme i squeezes into the previous phoneme's segment
+//!   (fewer segments than phonemes; the segment's time is divided evenly)
+//!
+//! Confidence = posterior of the aligned phoneme among all phonemes
+//! (softmax of the acoustic scores), averaged over its segments.
+
+use super::phoneme::Phoneme;
+use super::audio_io::{load_audio, TARGET_SAMPLE_RATE};
+use super::lexicon::Lexicon;
+use super::saturated_transcriber::{LocatedSegment, SaturatedTranscriber};
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlignedPhoneme {
+    pub phoneme: Phoneme,
+    pub start_ms: usize,
+    pub end_ms: usize,
+    pub confidence: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlignedWord {
+    pub word: String,
+    pub start_ms: usize,
+    pub end_ms: usize,
+    pub confidence: f32,
+    /// Indices into ForcedAlignment::phonemes
+    pub phonemes: Range<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ForcedAlignment {
+    pub words: Vec<AlignedWord>,
+    pub phonemes: Vec<AlignedPhoneme>,
+    /// Path score (sum of acoustic scores minus share penalties)
+    pub score: f32,
+    /// Mean phoneme confidence
+    pub confidence: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlignmentError {
+    EmptyTranscript,
+    /// Word missing from the lexicon (load a larger one with set_lexicon)
+    UnknownWord(String),
+    /// No speech segments in the audio
+    NoSpeech,
+}
+
+impl fmt::Display for AlignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlignmentError::EmptyTranscript => write!(f, "empty transcript"),
+            AlignmentError::UnknownWord(w) => write!(f, "word not in lexicon: {}", w),
+            AlignmentError::NoSpeech => write!(f, "no speech segments"),
+        }
+    }
+}
+
+impl std::error::Error for AlignmentError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Start,
+    Extend,
+    Share,
+}
+
+/// Segment owners per phoneme: for each phoneme, the segments it covers
+///
+/// `score[i][j]` = acoustic score of phoneme i on segment j. Every segment
+/// is covered, every phoneme gets at least one (possibly shared) segment.
+fn monotonic_path(score: &[Vec<f32>]) -> (Vec<Vec<usize>>, f32) {
+    let n = score.len();
+    let m = score.first().map_or(0, |row| row.len());
+
+    // Derived, not hardcoded: sharing costs one full span of the acoustic
+    // scores, so it only wins when there are too few segments
+    let (lo, hi) = score.iter().flatten().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &s| (lo.min(s), hi.max(s)));
+    let share_penalty = (hi - lo).max(1.0);
+
+    // best[i][j]: phonemes 0..i placed over segments 0..j, phoneme i-1 last
+    let mut best = vec![vec![f32::NEG_INFINITY; m + 1]; n + 1];
+    let mut step = vec![vec![Step::Start; m + 1]; n + 1];
+    best[0][0] = 0.0;
+
+    for i in 1..=n {
+        for j in 1..=m {
+            let s = score[i - 1][j - 1];
+            let options = [
+                (best[i - 1][j - 1] + s, Step::Start),
+                (best[i][j - 1] + s, Step::Extend),
+                (if i >= 2 { best[i - 1][j] + s - share_penalty } else { f32::NEG_INFINITY }, Step::Share),
+            ];
+            for (value, kind) in options {
+                if value > best[i][j] {
+                    best[i][j] = value;
+                    step[i][j] = kind;
+                }
+            }
+        }
+    }
+
+    let mut owned = vec![Vec::new(); n];
+    let (mut i, mut j) = (n, m);
+    while i > 0 && j > 0 {
+        owned[i - 1].push(j - 1);
+        match step[i][j] {
+            Step::Start => { i -= 1; j -= 1; }
+            Step::Extend => j -= 1,
+            Step::Share => i -= 1,
+        }
+    }
+    for segments in &mut owned {
+        segments.reverse();
+    }
+
+    (owned, best[n][m])
+}
+
+/// Align `words` (each with its pronunciation) to `segments`
+///
+/// `score(p, seg)` is the acoustic fit; `alphabet` is what the confidence
+/// posterior normalizes over.
+pub fn force_align(
+    words: &[(String, Vec<Phoneme>)],
+    segments: &[LocatedSegment],
+    alphabet: &[Phoneme],
+    score: impl Fn(Phoneme, &LocatedSegment) -> f32,
+) -> Result<ForcedAlignment, AlignmentError> {
+    let phonemes: Vec<Phoneme> = words.iter().flat_map(|(_, pron)| pron.iter().copied()).collect();
+    if phonemes.is_empty() {
+        return Err(AlignmentError::EmptyTranscript);
+    }
+    if segments.is_empty() {
+        return Err(AlignmentError::NoSpeech);
+    }
+
+    let matrix: Vec<Vec<f32>> = phonemes.iter()
+        .map(|&p| segments.iter().map(|seg| score(p, seg)).collect())
+        .collect();
+    let (owned, path_score) = monotonic_path(&matrix);
+
+    // Log-sum-exp of every phoneme's score per segment (posterior denominator)
+    let normalizer: Vec<f32> = segments.iter().map(|seg| {
+        let scores: Vec<f32> = alphabet.iter().map(|&p| score(p, seg)).collect();
+        let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        max + scores.iter().map(|s| (s - max).exp()).sum::<f32>().ln()
+    }).collect();
+
+    // Shared segments are divided evenly among their phonemes, in order
+    let mut owners = vec![Vec::new(); segments.len()];
+    for (i, segs) in owned.iter().enumerate() {
+        for &j in segs {
+            owners[j].push(i);
+        }
+    }
+    let slice = |i: usize, j: usize| {
+        let seg = &segments[j];
+        let k = owners[j].len().max(1);
+        let idx = owners[j].iter().position(|&o| o == i).unwrap_or(0);
+        (seg.start_ms + seg.duration_ms() * idx / k, seg.start_ms + seg.duration_ms() * (idx + 1) / k)
+    };
+
+    let aligned: Vec<AlignedPhoneme> = phonemes.iter().enumerate().map(|(i, &p)| {
+        let segs = &owned[i];
+        let (start_ms, _) = slice(i, segs[0]);
+        let (_, end_ms) = slice(i, segs[segs.len() - 1]);
+        let confidence = segs.iter().map(|&j| (matrix[i][j] - normalizer[j]).exp()).sum::<f32>() / segs.len() as f32;
+        AlignedPhoneme { phoneme: p, start_ms, end_ms, confidence }
+    }).collect();
+
+    let mut aligned_words = Vec::new();
+    let mut at = 0;
+    for (word, pron) in words {
+        let range = at..at + pron.len();
+        at = range.end;
+        if range.is_empty() {
+            continue;
+        }
+        let members = &aligned[range.clone()];
+        aligned_words.push(AlignedWord {
+            word: word.clone(),
+            start_ms: members[0].start_ms,
+            end_ms: members[members.len() - 1].end_ms,
+            confidence: members.iter().map(|p| p.confidence).sum::<f32>() / members.len() as f32,
+            phonemes: range,
+        });
+    }
+
+    let confidence = aligned.iter().map(|p| p.confidence).sum::<f32>() / aligned.len() as f32;
+    Ok(ForcedAlignment { words: aligned_words, phonemes: aligned, score: path_score, confidence })
+}
+
+/// SRT-style subtitle timestamps, one word per line: `start --> end  WORD`
+pub fn word_timings_text(alignment: &ForcedAlignment) -> String {
+    let stamp = |ms: usize| format!("{:02}:{:02}:{:02},{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000);
+    alignment.words.iter()
+        .map(|w| format!("{} --> {}  {}  ({:.2})\n", stamp(w.start_ms), stamp(w.end_ms), w.word, w.confidence))
+        .collect()
+}
+
+/// CLI: `<audio> <transcript.txt> [--rate HZ] [--lexicon CMUDICT]`
+///
+/// Prints one timed word per line (see word_timings_text).
+pub fn run_force_align(args: &[String]) -> Result<ForcedAlignment, Box<dyn std::error::Error>> {
+    let mut positional = Vec::new();
+    let mut target_rate = TARGET_SAMPLE_RATE;
+    let mut lexicon_path = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--rate" => target_rate = iter.next().ok_or("--rate needs a value")?.parse()?,
+            "--lexicon" => lexicon_path = Some(PathBuf::from(iter.next().ok_or("--lexicon needs a value")?)),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [audio, transcript] = &positional[..] else {
+        return Err("usage: <audio> <transcript.txt> [--rate HZ] [--lexicon CMUDICT]".into());
+    };
+
+    let mut transcriber = SaturatedTranscriber::new();
+    if let Some(path) = &lexicon_path {
+        let (lexicon, skipped) = Lexicon::load_cmudict(path, None)?;
+        if skipped > 0 {
+            eprintln!("{}: skipped {} unparsable entries", path.display(), skipped);
+        }
+        transcriber.set_lexicon(lexicon);
+    }
+
+    let buffer = load_audio(audio, None, target_rate)?;
+    let alignment = transcriber.force_align(&buffer.samples, buffer.sample_rate, &std::fs::read_to_string(transcript)?)?;
+    print!("{}", word_timings_text(&alignment));
+    Ok(alignment)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: usize, end_ms: usize, is_voiced: bool) -> LocatedSegment {
+        LocatedSegment {
+            start_ms,
+            end_ms,
+            pitch: None,
+            energy_db: -20.0,
+            is_voiced,
+            is_silence: false,
+            energy_low: 0.0,
+            energy_mid: 0.0,
+            energy_high: 0.0,
+            mfcc: Vec::new(),
+            formants: None,
+        }
+    }
+
+    /// Vowels fit voiced segments, consonants unvoiced ones
+    fn voicing_score(p: Phoneme, seg: &LocatedSegment) -> f32 {
+        let vowel = matches!(p, Phoneme::AE | Phoneme::AH | Phoneme::IY);
+        if vowel == seg.is_voiced { 2.0 } else { -2.0 }
+    }
+
+    const ALPHABET: [Phoneme; 5] = [Phoneme::AE, Phoneme::AH, Phoneme::IY, Phoneme::K, Phoneme::T];
+
+    #[test]
+    fn test_alignment_follows_acoustics() {
+        // K AE T over: unvoiced, voiced, voiced, unvoiced
+        let words = vec![("CAT".to_string(), vec![Phoneme::K, Phoneme::AE, Phoneme::T])];
+        let segments = [segment(0, 50, false), segment(50, 120, true), segment(120, 200, true), segment(220, 300, false)];
+
+        let alignment = force_align(&words, &segments, &ALPHABET, voicing_score).unwrap();
+        let spans: Vec<(usize, usize)> = alignment.phonemes.iter().map(|p| (p.start_ms, p.end_ms)).collect();
+        assert_eq!(spans, vec![(0, 50), (50, 200), (220, 300)], "AE extends over both voiced segments");
+        assert_eq!((alignment.words[0].start_ms, alignment.words[0].end_ms), (0, 300));
+        assert!(word_timings_text(&alignment).starts_with("00:00:00,000 --> 00:00:00,300  CAT"));
+        assert!(alignment.phonemes.iter().all(|p| p.confidence > 0.0 && p.confidence <= 1.0));
+        // On voicing alone AE ties with AH and IY: posterior ~1/3
+        assert!((alignment.phonemes[1].confidence - 1.0 / 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_more_phonemes_than_segments() {
+        let words = vec![
+            ("A".to_string(), vec![Phoneme::AH]),
+            ("CAT".to_string(), vec![Phoneme::K, Phoneme::AE, Phoneme::T]),
+        ];
+        let segments = [segment(0, 100, true), segment(100, 300, true)];
+
+        let alignment = force_align(&words, &segments, &ALPHABET, voicing_score).unwrap();
+        assert_eq!(alignment.phonemes.len(), 4);
+        for pair in alignment.phonemes.windows(2) {
+            assert!(pair[0].end_ms <= pair[1].start_ms, "Monotonic, non-overlapping");
+        }
+        assert_eq!(alignment.words[1].phonemes, 1..4);
+        assert_eq!(alignment.phonemes[3].end_ms, 300);
+    }
+
+    #[test]
+    fn test_alignment_errors() {
+        let segments = [segment(0, 100, true)];
+        assert_eq!(force_align(&[], &segments, &ALPHABET, voicing_score).unwrap_err(), AlignmentError::EmptyTranscript);
+        let words = vec![("A".to_string(), vec![Phoneme::AH])];
+        assert_eq!(force_align(&words, &[], &ALPHABET, voicing_score).unwrap_err(), AlignmentError::NoSpeech);
+    }
+}
@@ -0,0 +1,309 @@
+//! Formant Tracking (F1/F2/F3) via LPC Root-Finding
+//!
+//! The LOW/MID/HIGH bands are a coarse proxy: a vowel's F1 and F2 can sit
+//! in the same band and the band energies cannot tell IY from IH.
+//! Formants are the poles of the vocal tract filter, so per voiced frame:
+//!
+//! 1. Pre-emphasis + Hamming window
+//! 2. LPC coefficients (autocorrelation + Levinson-Durbin), order 2 + fs/1kHz
+//! 3. Roots of the LPC polynomial (Durand-Kerner)
+//! 4. Each root above the real axis = resonance: freq = angle·fs/2π,
+//!    bandwidth = -ln|z|·fs/π. Narrow resonances, lowest first = F1, F2, F3.
+//!
+//! Vowels are then compared in the F1-F2 plane on the Bark scale, where
+//! equal distances are roughly equal perceptual differences.
+
+use super::phoneme::Phoneme;
+use std::f32::consts::PI;
+
+/// Pre-emphasis (+6 dB/octave) flattens the glottal spectral tilt
+const PRE_EMPHASIS: f32 = 0.97;
+/// Resonances wider than this are spectral shaping, not formants
+const MAX_FORMANT_BANDWIDTH_HZ: f32 = 400.0;
+/// Below this is voicing/pitch energy, not F1
+const MIN_FORMANT_HZ: f32 = 90.0;
+
+/// F1-F2 targets (Hz) of English vowels, adult speakers
+/// (Peterson & Barney / Hillenbrand averages). Diphthongs use their onset.
+const VOWEL_FORMANTS: [(Phoneme, f32, f32); 15] = [
+    (Phoneme::IY, 270.0, 2290.0),
+    (Phoneme::IH, 390.0, 1990.0),
+    (Phoneme::EY, 476.0, 2089.0),
+    (Phoneme::EH, 530.0, 1840.0),
+    (Phoneme::AE, 660.0, 1720.0),
+    (Phoneme::AH, 520.0, 1190.0),
+    (Phoneme::AA, 730.0, 1090.0),
+    (Phoneme::AO, 570.0, 840.0),
+    (Phoneme::OW, 497.0, 910.0),
+    (Phoneme::UH, 440.0, 1020.0),
+    (Phoneme::UW, 300.0, 870.0),
+    (Phoneme::ER, 490.0, 1350.0),
+    (Phoneme::AY, 700.0, 1220.0),
+    (Phoneme::AW, 700.0, 1220.0),
+    (Phoneme::OY, 570.0, 840.0),
+];
+
+/// Traunmüller's Hz → Bark
+pub fn hz_to_bark(hz: f32) -> f32 {
+    26.81 * hz / (1960.0 + hz) - 0.53
+}
+
+/// F1-F2 distance (Bark) between measured formants and a vowel's target
+pub fn vowel_formant_distance(phoneme: Phoneme, formants: [f32; 3]) -> Option<f32> {
+    let &(_, f1, f2) = VOWEL_FORMANTS.iter().find(|(p, _, _)| *p == phoneme)?;
+    let d1 = hz_to_bark(formants[0]) - hz_to_bark(f1);
+    let d2 = hz_to_bark(formants[1]) - hz_to_bark(f2);
+    Some((d1 * d1 + d2 * d2).sqrt())
+}
+
+/// The `count` vowels nearest to the measured formants, with distances (nearest first)
+pub fn nearest_vowels(formants: [f32; 3], count: usize) -> Vec<(Phoneme, f32)> {
+    let mut ranked: Vec<(Phoneme, f32)> = VOWEL_FORMANTS.iter()
+        .filter_map(|&(p, _, _)| Some((p, vowel_formant_distance(p, formants)?)))
+        .collect();
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ranked.truncate(count);
+    ranked
+}
+
+// ============================================================================
+// LPC
+// ============================================================================
+
+/// LPC order rule of thumb: one pole pair per kHz plus two for tilt
+pub fn lpc_order(sample_rate: u32) -> usize {
+    2 + sample_rate as usize / 1000
+}
+
+/// LPC coefficients a[1..=order] of A(z) = 1 + Σ a_k z^-k (Levinson-Durbin)
+pub fn lpc(frame: &[f32], order: usize) -> Option<Vec<f32>> {
+    if frame.len() <= order {
+        return None;
+    }
+
+    let n = frame.len();
+    let denom = (n - 1) as f32;
+    let mut prev = 0.0;
+    let x: Vec<f32> = frame.iter().enumerate().map(|(i, &s)| {
+        let emphasized = s - PRE_EMPHASIS * prev;
+        prev = s;
+        emphasized * (0.54 - 0.46 * (2.0 * PI * i as f32 / denom).cos())
+    }).collect();
+
+    let r: Vec<f32> = (0..=order)
+        .map(|lag| x.iter().zip(&x[lag..]).map(|(a, b)| a * b).sum())
+        .collect();
+    if r[0] <= 1e-10 {
+        return None;
+    }
+
+    let mut a = vec![0.0f32; order + 1];
+    a[0] = 1.0;
+    let mut error = r[0];
+
+    for i in 1..=order {
+        let acc: f32 = (1..i).map(|j| a[j] * r[i - j]).sum();
+        let k = -(r[i] + acc) / error;
+
+        let previous = a.clone();
+        for j in 1..i {
+            a[j] = previous[j] + k * previous[i - j];
+        }
+        a[i] = k;
+
+        error *= 1.0 - k * k;
+        if error <= 0.0 {
+            return None;
+        }
+    }
+
+    Some(a[1..].to_vec())
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+impl Complex {
+    fn mul(self, o: Complex) -> Complex {
+        Complex { re: self.re * o.re - self.im * o.im, im: self.re * o.im + self.im * o.re }
+    }
+
+    fn sub(self, o: Complex) -> Complex {
+        Complex { re: self.re - o.re, im: self.im - o.im }
+    }
+
+    fn div(self, o: Complex) -> Complex {
+        let d = o.re * o.re + o.im * o.im;
+        Complex { re: (self.re * o.re + self.im * o.im) / d, im: (self.im * o.re - self.re * o.im) / d }
+    }
+
+    fn norm(self) -> f32 {
+        (self.re * self.re + self.im * self.im).sqrt()
+    }
+}
+
+/// Durand-Kerner iteration cap (a safety net: converging roots need ~10-30)
+const MAX_ROOT_ITERATIONS: usize = 500;
+/// A root has converged once its update is below this, relative to |z|
+/// (f32 cannot place a root much more precisely)
+const ROOT_TOLERANCE: f32 = 1e-5;
+
+/// Roots of z^p + c[0] z^(p-1) + ... + c[p-1] (Durand-Kerner), with the
+/// number of sweeps it took. Converged roots are frozen and the iteration
+/// stops as soon as every root has converged.
+fn polynomial_roots(c: &[f32]) -> (Vec<Complex>, usize) {
+    let p = c.len();
+    let eval = |z: Complex| c.iter().fold(Complex { re: 1.0, im: 0.0 }, |acc, &ck| {
+        let t = acc.mul(z);
+        Complex { re: t.re + ck, im: t.im }
+    });
+
+    // Standard non-symmetric starting points on a circle
+    let seed = Complex { re: 0.4, im: 0.9 };
+    let mut roots: Vec<Complex> = Vec::with_capacity(p);
+    let mut z = Complex { re: 1.0, im: 0.0 };
+    for _ in 0..p {
+        roots.push(z);
+        z = z.mul(seed);
+    }
+
+    let mut converged = vec![false; p];
+    for sweep in 1..=MAX_ROOT_ITERATIONS {
+        for i in 0..p {
+            if converged[i] {
+                continue;
+            }
+            let mut denom = Complex { re: 1.0, im: 0.0 };
+            for j in 0..p {
+                if i != j {
+                    denom = denom.mul(roots[i].sub(roots[j]));
+                }
+            }
+            if denom.norm() < 1e-20 {
+                continue;
+            }
+            let step = eval(roots[i]).div(denom);
+            roots[i] = roots[i].sub(step);
+            converged[i] = step.norm() <= ROOT_TOLERANCE * roots[i].norm().max(1.0);
+        }
+        if converged.iter().all(|&c| c) {
+            return (roots, sweep);
+        }
+    }
+
+    (roots, MAX_ROOT_ITERATIONS)
+}
+
+/// F1, F2, F3 (Hz) of one frame, or None if fewer than 3 resonances are found
+pub fn estimate_formants(frame: &[f32], sample_rate: u32) -> Option<[f32; 3]> {
+    let coeffs = lpc(frame, lpc_order(sample_rate))?;
+    let fs = sample_rate as f32;
+
+    let (roots, _) = polynomial_roots(&coeffs);
+    let mut resonances: Vec<f32> = roots.into_iter()
+        .filter(|z| z.im > 0.0 && z.re.is_finite() && z.im.is_finite())
+        .filter_map(|z| {
+            let freq = z.im.atan2(z.re) * fs / (2.0 * PI);
+            let bandwidth = -z.norm().ln() * fs / PI;
+            (freq > MIN_FORMANT_HZ && freq < fs / 2.0 - 50.0 && bandwidth < MAX_FORMANT_BANDWIDTH_HZ)
+                .then_some(freq)
+        })
+        .collect();
+    resonances.sort_by(f32::total_cmp);
+
+    match resonances[..] {
+        [f1, f2, f3, ..] => Some([f1, f2, f3]),
+        _ => None,
+    }
+}
+
+/// Mean formants of two measurements (segment merge)
+pub fn merge_formants(a: Option<[f32; 3]>, b: Option<[f32; 3]>) -> Option<[f32; 3]> {
+    match (a, b) {
+        (Some(x), Some(y)) => Some([(x[0] + y[0]) / 2.0, (x[1] + y[1]) / 2.0, (x[2] + y[2]) / 2.0]),
+        (x, y) => x.or(y),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Impulse train through second-order resonators (source-filter vowel)
+    fn synth_vowel(formants: [f32; 3], rate: u32, len: usize) -> Vec<f32> {
+        let fs = rate as f32;
+        let period = (fs / 120.0) as usize;
+        let mut signal: Vec<f32> = (0..len).map(|i| if i % period == 0 { 1.0 } else { 0.0 }).collect();
+
+        for f in formants {
+            let r = (-PI * 80.0 / fs).exp();
+            let theta = 2.0 * PI * f / fs;
+            let (a1, a2) = (2.0 * r * theta.cos(), -r * r);
+            let (mut y1, mut y2) = (0.0f32, 0.0f32);
+            for s in signal.iter_mut() {
+                let y = *s + a1 * y1 + a2 * y2;
+                y2 = y1;
+                y1 = y;
+                *s = y;
+            }
+        }
+        signal
+    }
+
+    #[test]
+    fn test_formants_of_synthetic_vowels() {
+        for (phoneme, target) in [(Phoneme::AA, [730.0, 1090.0, 2440.0]), (Phoneme::IY, [270.0, 2290.0, 3010.0])] {
+            let frame = synth_vowel(target, 16000, 480);
+            let measured = estimate_formants(&frame[80..], 16000).expect("three formants");
+
+            // Within half a Bark (LPC pulls F1 toward nearby pitch harmonics)
+            for (&m, t) in measured.iter().zip(target) {
+                assert!((hz_to_bark(m) - hz_to_bark(t)).abs() < 0.5, "{:?}: measured {:?}, target {:?}", phoneme, measured, target);
+            }
+            assert_eq!(nearest_vowels(measured, 1)[0].0, phoneme);
+        }
+    }
+
+    #[test]
+    fn test_root_finding_stops_at_convergence() {
+        // Order-18 LPC polynomial of a 16 kHz vowel frame
+        let frame = synth_vowel([730.0, 1090.0, 2440.0], 16000, 480);
+        let coeffs = lpc(&frame[80..], lpc_order(16000)).unwrap();
+        assert_eq!(coeffs.len(), 18);
+
+        let (roots, sweeps) = polynomial_roots(&coeffs);
+        assert!(sweeps < 50, "Converged in {} sweeps, not the {} cap", sweeps, MAX_ROOT_ITERATIONS);
+
+        // Every root actually is one: |A(z)| tiny relative to its scale
+        for z in roots {
+            let value = coeffs.iter().fold(Complex { re: 1.0, im: 0.0 }, |acc, &ck| {
+                let t = acc.mul(z);
+                Complex { re: t.re + ck, im: t.im }
+            });
+            assert!(value.norm() < 1e-3, "|A({:?})| = {}", z, value.norm());
+        }
+    }
+
+    #[test]
+    fn test_silence_has_no_formants() {
+        assert_eq!(estimate_formants(&[0.0; 320], 16000), None);
+    }
+
+    #[test]
+    fn test_vowel_distance_ordering() {
+        // IY-like formants: IY nearer than AA, IH in between
+        let f = [280.0, 2250.0, 2900.0];
+        let iy = vowel_formant_distance(Phoneme::IY, f).unwrap();
+        let ih = vowel_formant_distance(Phoneme::IH, f).unwrap();
+        let aa = vowel_formant_distance(Phoneme::AA, f).unwrap();
+        assert!(iy < ih && ih < aa);
+        assert_eq!(vowel_formant_distance(Phoneme::S, f), None, "Consonants have no vowel target");
+    }
+}
@@ -6,20 +6,67 @@
 //! Constraints:
 //! - Shift coverage (required staff per shift)
 //! - Skills matching (nurse skills match shift requirements)
-//! - Rest periods (minimum hours between shifts)
-//! - Max hours per week
-//! - Preferences (soft constraints)
+//! - Rest periods (minimum hours between the end of one shift and the next)
+//! - Max hours in any rolling 7-day window
+//! - Max consecutive working days
+//! - Absences and excluded shifts (a nurse must not work them)
+//! - Pinned assignments (fixed in advance, never moved by the search)
+//! - Preferences and time-off requests weighted by priority (soft constraints)
+//!
+//! Search (--method, reproducible with --seed): first-improvement descent,
+//! simulated annealing, tabu search, or multi-start descents. --exact adds a
+//! branch-and-bound that proves a lower bound (and the gap) for the result;
+//! --feasibility bounds hard violations alone, proving infeasibility.
+//!
+//! A roster left with hard violations is explained rule by rule (nurse, shift,
+//! rule, suggested fix), together with the smallest groups of shifts that no
+//! roster can staff: more overlapping shifts than skilled nurses, or more
+//! weekly hours than those nurses may work.
+//!
+//! --repair re-optimises a published roster (--output writes one) after the
+//! change events in --events: every assignment that differs from the
+//! published one costs --disruption, so the repair moves as few nurses as it can.
+//! A nurse_unavailable event drops the nurse's pins on the shifts it covers.
+//! --exact does not combine with --repair: keeping a published assignment
+//! has a negative cost, while its bound assumes every assignment costs
+//! something and no shift needs more than required_staff nurses.
+//!
+//! Shifts carry real start/end datetimes ("2024-03-04T07:00"); every time
+//! rule is evaluated from those timestamps. Legacy files with only `day` and
+//! `shift_type` get day 07:00, evening 15:00, night 23:00 starts.
 
-use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 
+const MINUTES_PER_DAY: i64 = 24 * 60;
+const WEEK_MINUTES: i64 = 7 * MINUTES_PER_DAY;
+
 #[derive(Debug, Clone, Deserialize)]
 struct HospitalData {
     name: String,
     nurses: Vec<Nurse>,
     shifts: Vec<Shift>,
-    days: usize,
+    #[serde(default)]
+    rules: WorkRules,
+}
+
+/// Working-time rules shared by all nurses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct WorkRules {
+    /// Hours off between the end of one shift and the start of the next
+    min_rest_hours: usize,
+    /// Working days in a row (None = unlimited)
+    max_consecutive_days: Option<usize>,
+}
+
+impl Default for WorkRules {
+    fn default() -> Self {
+        // 8 hours: the rest the fixed day/evening/night rota always guaranteed
+        Self { min_rest_hours: 8, max_consecutive_days: None }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -27,8 +74,42 @@ struct Nurse {
     id: usize,
     name: String,
     skills: Vec<String>,
+    /// Limit for any rolling 7-day window
     max_hours_week: usize,
     preferred_shifts: Vec<String>,  // "day", "evening", "night"
+    /// Leave, sickness, training: no shift overlapping these
+    #[serde(default)]
+    unavailable: Vec<Absence>,
+    /// Shift ids this nurse must never work
+    #[serde(default)]
+    excluded_shifts: Vec<usize>,
+    /// Each shift worked inside a request costs its priority
+    #[serde(default)]
+    time_off_requests: Vec<TimeOffRequest>,
+}
+
+/// A period a nurse cannot work
+#[derive(Debug, Clone, Deserialize)]
+struct Absence {
+    start: Timestamp,
+    end: Timestamp,
+    /// "vacation", "sick leave", ...
+    #[serde(default)]
+    reason: String,
+}
+
+/// A period a nurse would rather not work
+#[derive(Debug, Clone, Deserialize)]
+struct TimeOffRequest {
+    start: Timestamp,
+    end: Timestamp,
+    /// Cost per shift worked in the period (a missed shift-type preference costs 1)
+    #[serde(default = "default_priority")]
+    priority: usize,
+}
+
+fn default_priority() -> usize {
+    1
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -38,19 +119,266 @@ struct Shift {
     shift_type: String,  // "day", "evening", "night"
     required_skills: Vec<String>,
     required_staff: usize,
+    #[serde(default)]
+    start: Option<Timestamp>,
+    #[serde(default)]
+    end: Option<Timestamp>,
+    /// Legacy: duration when `end` is missing
+    #[serde(default = "legacy_hours")]
     hours: usize,
+    /// Legacy: day index when `start` is missing
+    #[serde(default)]
     day: usize,
+    /// Nurse ids fixed on this shift: in every roster, never moved by the search
+    #[serde(default)]
+    pinned: Vec<usize>,
 }
 
-#[derive(Debug, Clone)]
+fn legacy_hours() -> usize {
+    8
+}
+
+impl Shift {
+    /// Actual interval: timestamps if given, else the legacy day/shift_type rota
+    fn time(&self) -> ShiftTime {
+        let start = self.start.unwrap_or_else(|| {
+            let hour = match self.shift_type.as_str() {
+                "evening" => 15,
+                "night" => 23,
+                _ => 7,
+            };
+            Timestamp(self.day as i64 * MINUTES_PER_DAY + hour * 60)
+        });
+        let end = self.end.unwrap_or(Timestamp(start.0 + self.hours as i64 * 60));
+        ShiftTime { start, end }
+    }
+}
+
+/// Wall-clock minutes since 1970-01-01 00:00 (no time zones)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Timestamp(i64);
+
+impl Timestamp {
+    /// `YYYY-MM-DDTHH:MM[:SS]` (a space instead of `T` is accepted; seconds are ignored)
+    fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid datetime {text:?}, expected YYYY-MM-DDTHH:MM");
+        let (date, time) = text.trim().split_once(['T', ' ']).ok_or_else(invalid)?;
+
+        let date: Vec<i64> = date.split('-').map(str::parse).collect::<Result<_, _>>().map_err(|_| invalid())?;
+        let time: Vec<i64> = time.split(':').map(str::parse).collect::<Result<_, _>>().map_err(|_| invalid())?;
+        let (&[year, month, day], &[hour, minute, ..]) = (&date[..], &time[..]) else {
+            return Err(invalid());
+        };
+
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month)
+            || !(0..24).contains(&hour) || !(0..60).contains(&minute)
+        {
+            return Err(invalid());
+        }
+
+        Ok(Timestamp(days_from_civil(year, month, day) * MINUTES_PER_DAY + hour * 60 + minute))
+    }
+
+    /// Calendar day (days since 1970-01-01)
+    fn day(self) -> i64 {
+        self.0.div_euclid(MINUTES_PER_DAY)
+    }
+
+    /// Time of day, `HH:MM`
+    fn clock(self) -> String {
+        let minutes = self.0.rem_euclid(MINUTES_PER_DAY);
+        format!("{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = civil_from_days(self.day());
+        write!(f, "{year:04}-{month:02}-{day:02} {}", self.clock())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Timestamp::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (H. Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of days_from_civil
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (if month <= 2 { yoe + era * 400 + 1 } else { yoe + era * 400 }, month, day)
+}
+
+/// Resolved shift interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ShiftTime {
+    start: Timestamp,
+    end: Timestamp,
+}
+
+impl ShiftTime {
+    fn overlaps(&self, start: Timestamp, end: Timestamp) -> bool {
+        self.start < end && start < self.end
+    }
+}
+
+impl HospitalData {
+    /// Reject periods that end before they start, duplicate shift ids, unknown
+    /// ids in pins and exclusions, and pins on shifts the nurse must not work
+    fn validate(&self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        for shift in &self.shifts {
+            if !ids.insert(shift.id) {
+                return Err(format!("duplicate shift id {}", shift.id));
+            }
+            let time = shift.time();
+            if time.end <= time.start {
+                return Err(format!("shift {} ({}) ends at {} before it starts at {}",
+                                   shift.id, shift.name, time.end, time.start));
+            }
+        }
+
+        for nurse in &self.nurses {
+            let periods = nurse.unavailable.iter().map(|a| (a.start, a.end))
+                .chain(nurse.time_off_requests.iter().map(|r| (r.start, r.end)));
+            for (start, end) in periods {
+                if end <= start {
+                    return Err(format!("nurse {} ({}) has a period ending at {end} before it starts at {start}",
+                                       nurse.id, nurse.name));
+                }
+            }
+            if let Some(id) = nurse.excluded_shifts.iter().find(|id| !ids.contains(id)) {
+                return Err(format!("nurse {} ({}) excludes unknown shift {id}", nurse.id, nurse.name));
+            }
+        }
+
+        for shift in &self.shifts {
+            for &nurse_id in &shift.pinned {
+                let Some(nurse) = self.nurses.iter().find(|n| n.id == nurse_id) else {
+                    return Err(format!("shift {} ({}) pins unknown nurse {nurse_id}", shift.id, shift.name));
+                };
+                let time = shift.time();
+                if nurse.excluded_shifts.contains(&shift.id)
+                    || nurse.unavailable.iter().any(|a| time.overlaps(a.start, a.end))
+                {
+                    return Err(format!("shift {} ({}) pins {}, who must not work it", shift.id, shift.name, nurse.name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply a change event: (nurse id, shift id) of the pins it removed
+    ///
+    /// An absence overrides the pins it overlaps: a nurse off sick cannot be
+    /// held to a shift fixed before the sick call.
+    fn apply(&mut self, event: &ChangeEvent) -> Result<Vec<(usize, usize)>, String> {
+        let mut unpinned = Vec::new();
+        match event {
+            ChangeEvent::NurseUnavailable { nurse, start, end, reason } => {
+                let Some(entry) = self.nurses.iter_mut().find(|n| n.id == *nurse) else {
+                    return Err(format!("unknown nurse {nurse}"));
+                };
+                entry.unavailable.push(Absence { start: *start, end: *end, reason: reason.clone() });
+                for shift in &mut self.shifts {
+                    if shift.pinned.contains(nurse) && shift.time().overlaps(*start, *end) {
+                        shift.pinned.retain(|id| id != nurse);
+                        unpinned.push((*nurse, shift.id));
+                    }
+                }
+            }
+            ChangeEvent::ShiftAdded { shift } => self.shifts.push(shift.clone()),
+            ChangeEvent::StaffingChanged { shift, required_staff } => {
+                let Some(entry) = self.shifts.iter_mut().find(|s| s.id == *shift) else {
+                    return Err(format!("unknown shift {shift}"));
+                };
+                entry.required_staff = *required_staff;
+            }
+        }
+        Ok(unpinned)
+    }
+
+    /// Calendar days on which shifts start, in order
+    fn calendar_days(&self) -> Vec<i64> {
+        let mut days: Vec<i64> = self.shifts.iter().map(|s| s.time().start.day()).collect();
+        days.sort_unstable();
+        days.dedup();
+        days
+    }
+}
+
+/// A change after a roster was published, e.g. {"type": "staffing_changed", "shift": 4, "required_staff": 5}
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ChangeEvent {
+    /// Sick call, emergency leave
+    NurseUnavailable {
+        nurse: usize,
+        start: Timestamp,
+        end: Timestamp,
+        #[serde(default)]
+        reason: String,
+    },
+    ShiftAdded { shift: Shift },
+    StaffingChanged { shift: usize, required_staff: usize },
+}
+
+/// (shift, nurse) positions a repair added to and dropped from the published roster
+#[derive(Debug, Clone, Default)]
+struct Disruption {
+    added: Vec<(usize, usize)>,
+    dropped: Vec<(usize, usize)>,
+}
+
+impl Disruption {
+    fn changes(&self) -> usize {
+        self.added.len() + self.dropped.len()
+    }
+}
+
+/// Also the JSON form of a published roster: {"assignments": {"<shift id>": [nurse ids]}}
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Schedule {
     // assignment[shift_id] = list of nurse_ids assigned
-    assignments: HashMap<usize, Vec<usize>>,
+    assignments: BTreeMap<usize, Vec<usize>>,
 }
 
 impl Schedule {
     fn new(num_shifts: usize) -> Self {
-        let mut assignments = HashMap::new();
+        let mut assignments = BTreeMap::new();
         for i in 0..num_shifts {
             assignments.insert(i, Vec::new());
         }
@@ -58,7 +386,7 @@ impl Schedule {
     }
 
     fn assign(&mut self, shift_id: usize, nurse_id: usize) {
-        let nurses = self.assignments.get_mut(&shift_id).unwrap();
+        let nurses = self.assignments.entry(shift_id).or_default();
         if !nurses.contains(&nurse_id) {
             nurses.push(nurse_id);
         }
@@ -69,19 +397,190 @@ impl Schedule {
             nurses.retain(|&n| n != nurse_id);
         }
     }
+}
+
+/// Objective weight of one hard violation (any number of preference misses is cheaper)
+const HARD_WEIGHT: isize = 1000;
+
+/// Lightweight neighborhood move over shift and nurse POSITIONS in HospitalData
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Move {
+    /// Nurse joins a shift
+    Add { shift: usize, nurse: usize },
+    /// Nurse leaves a shift
+    Remove { shift: usize, nurse: usize },
+    /// Nurse leaves `from` and joins `to`
+    Reassign { nurse: usize, from: usize, to: usize },
+    /// Two nurses trade shifts
+    Swap { nurse_a: usize, shift_a: usize, nurse_b: usize, shift_b: usize },
+}
+
+/// One nurse's side of a move: leaves `removed`, joins `added`
+#[derive(Debug, Clone, Copy)]
+struct NurseChange {
+    nurse: usize,
+    removed: Option<usize>,
+    added: Option<usize>,
+}
+
+impl Move {
+    fn nurse_changes(self) -> [Option<NurseChange>; 2] {
+        match self {
+            Move::Add { shift, nurse } => [Some(NurseChange { nurse, removed: None, added: Some(shift) }), None],
+            Move::Remove { shift, nurse } => [Some(NurseChange { nurse, removed: Some(shift), added: None }), None],
+            Move::Reassign { nurse, from, to } => [Some(NurseChange { nurse, removed: Some(from), added: Some(to) }), None],
+            Move::Swap { nurse_a, shift_a, nurse_b, shift_b } => [
+                Some(NurseChange { nurse: nurse_a, removed: Some(shift_a), added: Some(shift_b) }),
+                Some(NurseChange { nurse: nurse_b, removed: Some(shift_b), added: Some(shift_a) }),
+            ],
+        }
+    }
+
+    /// (nurse, shift) pairs the move breaks up or creates
+    fn pairs(self) -> impl Iterator<Item = (usize, usize)> {
+        self.nurse_changes().into_iter().flatten()
+            .flat_map(|c| [c.removed, c.added].into_iter().flatten().map(move |s| (c.nurse, s)))
+    }
 
-    fn get_nurse_shifts(&self, nurse_id: usize) -> Vec<usize> {
-        self.assignments
-            .iter()
-            .filter(|(_, nurses)| nurses.contains(&nurse_id))
-            .map(|(&shift_id, _)| shift_id)
-            .collect()
+    /// Staff count change per shift (a swap keeps both counts)
+    fn staffing_changes(self) -> [Option<(usize, isize)>; 2] {
+        match self {
+            Move::Add { shift, .. } => [Some((shift, 1)), None],
+            Move::Remove { shift, .. } => [Some((shift, -1)), None],
+            Move::Reassign { from, to, .. } => [Some((from, -1)), Some((to, 1))],
+            Move::Swap { .. } => [None, None],
+        }
+    }
+}
+
+/// Incremental view of a Schedule with every cost term cached
+///
+/// Shift coverage depends only on a shift's staff count, skills and
+/// preferences only on (nurse, shift), and the working-time rules only on
+/// one nurse's own shifts: a move re-scores the shifts and nurses it touches.
+#[derive(Debug, Clone)]
+struct ScheduleState {
+    /// Nurse positions per shift position
+    staff: Vec<Vec<usize>>,
+    /// Shift positions per nurse, sorted by start time
+    nurse_shifts: Vec<Vec<usize>>,
+    /// Rest, rolling-hours and consecutive-day violations per nurse
+    nurse_time: Vec<usize>,
+    hard: usize,
+    soft: usize,
+}
+
+impl ScheduleState {
+    fn objective(&self) -> usize {
+        self.hard * HARD_WEIGHT as usize + self.soft
+    }
+}
+
+/// Local search method for ScheduleSolver::search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// First-improvement descent (stops at the first local optimum)
+    HillClimbing,
+    SimulatedAnnealing,
+    Tabu,
+    /// Descents from random full rosters
+    MultiStart,
+}
+
+impl Method {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "hill" | "hill-climbing" => Some(Method::HillClimbing),
+            "anneal" | "annealing" | "sa" => Some(Method::SimulatedAnnealing),
+            "tabu" => Some(Method::Tabu),
+            "multistart" | "multi-start" => Some(Method::MultiStart),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SearchConfig {
+    method: Method,
+    seed: u64,
+    /// Annealing moves / tabu iterations / restarts (None = derived from the roster size)
+    budget: Option<usize>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self { method: Method::HillClimbing, seed: 0, budget: None }
+    }
+}
+
+/// Swaps pair shifts starting at most two days apart: further apart they
+/// rarely fix a rest or consecutive-day breach, and pairing every two shifts
+/// of a month is quadratic
+const SWAP_WINDOW_MINUTES: i64 = 2 * MINUTES_PER_DAY;
+/// Move kinds per shift in ScheduleSolver::neighbors_at
+const NEIGHBOR_KINDS: usize = 4;
+/// Draws per random_move before giving up (an empty roster has nothing to remove)
+const RANDOM_MOVE_TRIES: usize = 64;
+/// Random moves sampled to derive the initial annealing temperature
+const ANNEALING_SAMPLES: usize = 200;
+
+/// SplitMix64: seeded and dependency-free, so every run is reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in 0..n (n > 0)
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform in [0, 1)
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
     }
 }
 
 struct ScheduleSolver {
     data: HospitalData,
     nurse_skills: HashMap<usize, HashSet<String>>,
+    nurse_index: HashMap<usize, usize>,
+    /// Resolved interval per shift position
+    times: Vec<ShiftTime>,
+    /// Longest shift: bounds the rolling-window scan
+    max_shift_minutes: i64,
+    /// skilled[nurse * shifts + shift]
+    skilled: Vec<bool>,
+    /// preferred[nurse * shifts + shift]
+    preferred: Vec<bool>,
+    /// available[nurse * shifts + shift]: no absence overlaps it, not excluded
+    available: Vec<bool>,
+    /// time_off[nurse * shifts + shift]: priority of the requests it falls in
+    time_off: Vec<usize>,
+    /// Pinned nurse positions per shift position
+    pinned: Vec<Vec<usize>>,
+    /// original[nurse * shifts + shift]: in the published roster being repaired
+    original: Vec<bool>,
+    /// Soft cost of each assignment added to or dropped from the original (0 outside repairs)
+    disruption_weight: usize,
+    /// Skilled, available nurse positions per shift position
+    eligible_nurses: Vec<Vec<usize>>,
+    /// Shift positions each nurse is skilled and available for
+    eligible_shifts: Vec<Vec<usize>>,
+    /// Later shift positions starting within SWAP_WINDOW_MINUTES, per shift position
+    swap_partners: Vec<Vec<usize>>,
+    /// Interval buffer reused by time_violations: scoring a move never allocates
+    scratch_times: RefCell<Vec<ShiftTime>>,
 }
 
 impl ScheduleSolver {
@@ -93,7 +592,77 @@ impl ScheduleSolver {
                 nurse.skills.iter().cloned().collect(),
             );
         }
-        Self { data, nurse_skills }
+        let nurse_index = data.nurses.iter().enumerate().map(|(n, nurse)| (nurse.id, n)).collect();
+        let times: Vec<ShiftTime> = data.shifts.iter().map(Shift::time).collect();
+        let max_shift_minutes = times.iter().map(|t| t.end.0 - t.start.0).max().unwrap_or(0);
+
+        let mut solver = Self {
+            data,
+            nurse_skills,
+            nurse_index,
+            times,
+            max_shift_minutes,
+            skilled: Vec::new(),
+            preferred: Vec::new(),
+            available: Vec::new(),
+            time_off: Vec::new(),
+            pinned: Vec::new(),
+            original: Vec::new(),
+            disruption_weight: 0,
+            eligible_nurses: Vec::new(),
+            eligible_shifts: Vec::new(),
+            swap_partners: Vec::new(),
+            scratch_times: RefCell::new(Vec::new()),
+        };
+        for nurse in &solver.data.nurses {
+            for (shift, time) in solver.data.shifts.iter().zip(&solver.times) {
+                solver.skilled.push(solver.can_work(nurse.id, shift));
+                solver.preferred.push(nurse.preferred_shifts.contains(&shift.shift_type));
+                solver.available.push(!nurse.excluded_shifts.contains(&shift.id)
+                    && !nurse.unavailable.iter().any(|a| time.overlaps(a.start, a.end)));
+                solver.time_off.push(nurse.time_off_requests.iter()
+                    .filter(|r| time.overlaps(r.start, r.end))
+                    .map(|r| r.priority)
+                    .sum());
+            }
+        }
+        solver.pinned = solver.data.shifts.iter()
+            .map(|shift| {
+                let mut nurses: Vec<usize> = shift.pinned.iter().filter_map(|id| solver.nurse_index.get(id).copied()).collect();
+                nurses.sort_unstable();
+                nurses.dedup();
+                nurses
+            })
+            .collect();
+        let (num_nurses, num_shifts) = (solver.data.nurses.len(), solver.data.shifts.len());
+        solver.original = vec![false; num_nurses * num_shifts];
+        solver.eligible_nurses = (0..num_shifts)
+            .map(|s| (0..num_nurses).filter(|&n| solver.is_eligible(n, s)).collect())
+            .collect();
+        solver.eligible_shifts = (0..num_nurses)
+            .map(|n| (0..num_shifts).filter(|&s| solver.is_eligible(n, s)).collect())
+            .collect();
+        solver.swap_partners = (0..num_shifts)
+            .map(|a| (a + 1..num_shifts)
+                .filter(|&b| (solver.times[a].start.0 - solver.times[b].start.0).abs() <= SWAP_WINDOW_MINUTES)
+                .collect())
+            .collect();
+        solver
+    }
+
+    /// Repair mode: every assignment added to or dropped from `original` costs `weight`
+    fn with_original(mut self, original: &Schedule, weight: usize) -> Self {
+        let num_shifts = self.data.shifts.len();
+        let pairs: Vec<usize> = self.data.shifts.iter().enumerate()
+            .flat_map(|(s, shift)| original.assignments.get(&shift.id).into_iter().flatten()
+                .filter_map(|id| self.nurse_index.get(id))
+                .map(move |&n| n * num_shifts + s))
+            .collect();
+        for index in pairs {
+            self.original[index] = true;
+        }
+        self.disruption_weight = weight;
+        self
     }
 
     /// Check if nurse can work this shift (skills match)
@@ -105,214 +674,1335 @@ impl ScheduleSolver {
         }
     }
 
-    /// Calculate hard constraint violations
+    /// Skilled and available
+    fn is_eligible(&self, nurse: usize, shift: usize) -> bool {
+        let index = nurse * self.data.shifts.len() + shift;
+        self.skilled[index] && self.available[index]
+    }
+
+    fn is_available(&self, nurse: usize, shift: usize) -> bool {
+        self.available[nurse * self.data.shifts.len() + shift]
+    }
+
+    fn is_pinned(&self, nurse: usize, shift: usize) -> bool {
+        self.pinned[shift].contains(&nurse)
+    }
+
+    /// Eligible and not on the shift yet
+    fn is_free(&self, state: &ScheduleState, nurse: usize, shift: usize) -> bool {
+        self.is_eligible(nurse, shift) && !state.staff[shift].contains(&nurse)
+    }
+
+    /// (hard, soft) cost of one nurse on one shift: missing skill, unavailability,
+    /// unwanted shift type, requested time off
+    ///
+    /// A pinned pair counts -1: state() starts from one violation per pin, so
+    /// a pin missing from the roster stays a violation and deltas stay exact.
+    /// In a repair, an original pair likewise counts -disruption_weight and any
+    /// other pair +disruption_weight.
+    fn pair_cost(&self, nurse: usize, shift: usize) -> (isize, isize) {
+        let index = nurse * self.data.shifts.len() + shift;
+        let hard = isize::from(!self.skilled[index]) + isize::from(!self.available[index])
+            - isize::from(self.is_pinned(nurse, shift));
+        let disruption = self.disruption_weight as isize;
+        let soft = isize::from(!self.preferred[index]) + self.time_off[index] as isize
+            + if self.original[index] { -disruption } else { disruption };
+        (hard, soft)
+    }
+
+    /// Soft cost of an empty roster: every original assignment dropped
+    fn disruption_baseline(&self) -> usize {
+        self.disruption_weight * self.original.iter().filter(|&&o| o).count()
+    }
+
+    /// Assignments that differ from the original roster
+    fn disruption(&self, schedule: &Schedule) -> Disruption {
+        let state = self.state(schedule);
+        let num_shifts = self.data.shifts.len();
+        let mut disruption = Disruption::default();
+        for (s, staff) in state.staff.iter().enumerate() {
+            for n in 0..self.data.nurses.len() {
+                match (self.original[n * num_shifts + s], staff.contains(&n)) {
+                    (false, true) => disruption.added.push((s, n)),
+                    (true, false) => disruption.dropped.push((s, n)),
+                    _ => {}
+                }
+            }
+        }
+        disruption
+    }
+
+    /// Pinned nurse positions missing from a shift's nurse ids
+    fn missing_pins<'a>(&'a self, shift: usize, nurse_ids: &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+        self.pinned[shift].iter().copied().filter(move |&n| !nurse_ids.contains(&self.data.nurses[n].id))
+    }
+
+    /// Unfilled places on a shift with `staff` nurses
+    fn shortfall(&self, shift: usize, staff: usize) -> usize {
+        self.data.shifts[shift].required_staff.saturating_sub(staff)
+    }
+
+    fn sort_by_start(&self, shifts: &mut [usize]) {
+        shifts.sort_by_key(|&s| (self.times[s].start, s));
+    }
+
+    /// Calculate hard constraint violations (full scan; ScheduleState keeps them incrementally)
     fn hard_violations(&self, schedule: &Schedule) -> usize {
         let mut violations = 0;
+        let mut nurse_shifts: Vec<Vec<usize>> = vec![Vec::new(); self.data.nurses.len()];
+
+        for (s, shift) in self.data.shifts.iter().enumerate() {
+            let nurses = schedule.assignments.get(&shift.id).map_or(&[][..], Vec::as_slice);
+
+            // Check shift coverage
+            violations += self.shortfall(s, nurses.len());
+
+            for &nurse_id in nurses {
+                // Check skills
+                if !self.can_work(nurse_id, shift) {
+                    violations += 1;
+                }
+                if let Some(&n) = self.nurse_index.get(&nurse_id) {
+                    // Check absences and exclusions
+                    if !self.is_available(n, s) {
+                        violations += 1;
+                    }
+                    nurse_shifts[n].push(s);
+                }
+            }
+
+            // Check pinned assignments
+            violations += self.missing_pins(s, nurses).count();
+        }
+
+        // Check working-time rules from the actual timestamps
+        for (n, shifts) in nurse_shifts.iter_mut().enumerate() {
+            self.sort_by_start(shifts);
+            violations += self.time_violations(n, shifts.iter().copied());
+        }
+
+        violations
+    }
+
+    /// Rest, rolling-hours and consecutive-day violations of one nurse's shifts (sorted by start)
+    fn time_violations(&self, nurse: usize, shifts: impl IntoIterator<Item = usize>) -> usize {
+        let mut times = self.scratch_times.borrow_mut();
+        times.clear();
+        times.extend(shifts.into_iter().map(|s| self.times[s]));
+        self.rest_breaches(&times).count()
+            + self.rolling_hours_breaches(&self.data.nurses[nurse], &times).count()
+            + self.consecutive_day_breaches(&times).count()
+    }
+
+    /// Consecutive shifts (sorted by start) with less than min_rest_hours between them:
+    /// index of the later shift and the rest actually given, in minutes
+    fn rest_breaches<'t>(&self, times: &'t [ShiftTime]) -> impl Iterator<Item = (usize, i64)> + 't {
+        let min_rest = self.data.rules.min_rest_hours as i64 * 60;
+        times.windows(2)
+            .enumerate()
+            .map(|(i, w)| (i + 1, w[1].start.0 - w[0].end.0))
+            .filter(move |&(_, rest)| rest < min_rest)
+    }
+
+    /// Shifts at whose end the trailing 7 days hold more than max_hours_week:
+    /// index and minutes worked in that window
+    ///
+    /// Only shifts starting within the window (or one longest shift before it)
+    /// can overlap it, so each window scans about a week of shifts.
+    fn rolling_hours_breaches<'t>(&'t self, nurse: &Nurse, times: &'t [ShiftTime]) -> impl Iterator<Item = (usize, i64)> + 't {
+        let limit = nurse.max_hours_week as i64 * 60;
+        times.iter()
+            .enumerate()
+            .map(move |(i, t)| {
+                let window_start = t.end.0 - WEEK_MINUTES;
+                let first = times.partition_point(|o| o.start.0 < window_start - self.max_shift_minutes);
+                let last = times.partition_point(|o| o.start.0 < t.end.0);
+                let worked: i64 = times[first..last].iter()
+                    .map(|o| (o.end.0.min(t.end.0) - o.start.0.max(window_start)).max(0))
+                    .sum();
+                (i, worked)
+            })
+            .filter(move |&(_, worked)| worked > limit)
+    }
+
+    /// Working days beyond max_consecutive_days in each run of calendar days:
+    /// index of the day's first shift and the length of the run so far
+    fn consecutive_day_breaches<'t>(&self, times: &'t [ShiftTime]) -> impl Iterator<Item = (usize, usize)> + 't {
+        let max_days = self.data.rules.max_consecutive_days.unwrap_or(usize::MAX);
+        let mut previous = None;
+        let mut run = 0;
+        times.iter().enumerate().filter_map(move |(i, t)| {
+            let day = t.start.day();
+            if previous == Some(day) {
+                return None;
+            }
+            run = if previous == Some(day - 1) { run + 1 } else { 1 };
+            previous = Some(day);
+            (run > max_days).then_some((i, run))
+        })
+    }
+
+    /// Calculate soft constraint cost (preferences)
+    fn soft_cost(&self, schedule: &Schedule) -> usize {
+        let mut cost = self.disruption_baseline() as isize;
+
+        for (s, shift) in self.data.shifts.iter().enumerate() {
+            for nurse_id in schedule.assignments.get(&shift.id).into_iter().flatten() {
+                if let Some(&n) = self.nurse_index.get(nurse_id) {
+                    cost += self.pair_cost(n, s).1;
+                }
+            }
+        }
+
+        cost as usize
+    }
+
+    /// Total objective: hard violations * 1000 + soft cost
+    fn objective(&self, schedule: &Schedule) -> usize {
+        self.hard_violations(schedule) * HARD_WEIGHT as usize + self.soft_cost(schedule)
+    }
+
+    /// Generate initial schedule - pinned assignments only, saturation will fill it
+    fn initial(&self) -> Schedule {
+        let mut schedule = Schedule::new(self.data.shifts.len());
+        for (shift, nurses) in self.data.shifts.iter().zip(&self.pinned) {
+            for &n in nurses {
+                schedule.assign(shift.id, self.data.nurses[n].id);
+            }
+        }
+        schedule
+    }
+
+    /// Incremental state of a schedule (nurse ids not in HospitalData are dropped)
+    fn state(&self, schedule: &Schedule) -> ScheduleState {
+        let mut state = ScheduleState {
+            staff: vec![Vec::new(); self.data.shifts.len()],
+            nurse_shifts: vec![Vec::new(); self.data.nurses.len()],
+            nurse_time: vec![0; self.data.nurses.len()],
+            // Each pin present cancels its violation through pair_cost
+            hard: self.pinned.iter().map(Vec::len).sum(),
+            soft: self.disruption_baseline(),
+        };
+
+        for (s, shift) in self.data.shifts.iter().enumerate() {
+            for nurse_id in schedule.assignments.get(&shift.id).into_iter().flatten() {
+                if let Some(&n) = self.nurse_index.get(nurse_id) {
+                    let (hard, soft) = self.pair_cost(n, s);
+                    state.hard = state.hard.saturating_add_signed(hard);
+                    state.soft = state.soft.saturating_add_signed(soft);
+                    state.staff[s].push(n);
+                    state.nurse_shifts[n].push(s);
+                }
+            }
+            state.hard += self.shortfall(s, state.staff[s].len());
+        }
+
+        for n in 0..self.data.nurses.len() {
+            let mut shifts = std::mem::take(&mut state.nurse_shifts[n]);
+            self.sort_by_start(&mut shifts);
+            state.nurse_time[n] = self.time_violations(n, shifts.iter().copied());
+            state.hard += state.nurse_time[n];
+            state.nurse_shifts[n] = shifts;
+        }
+
+        state
+    }
+
+    /// Replay a move on the id-keyed Schedule
+    fn apply_to_schedule(&self, schedule: &mut Schedule, mv: Move) {
+        for change in mv.nurse_changes().into_iter().flatten() {
+            let nurse_id = self.data.nurses[change.nurse].id;
+            if let Some(shift) = change.removed {
+                schedule.unassign(self.data.shifts[shift].id, nurse_id);
+            }
+            if let Some(shift) = change.added {
+                schedule.assign(self.data.shifts[shift].id, nurse_id);
+            }
+        }
+    }
+
+    /// A nurse's shift list after `change`, still sorted by start
+    fn changed_shifts<'s>(&'s self, shifts: &'s [usize], change: NurseChange) -> impl Iterator<Item = usize> + 's {
+        let key = |s: usize| (self.times[s].start, s);
+        let at = change.added.map_or(shifts.len(), |added| shifts.partition_point(|&s| key(s) < key(added)));
+        let kept = move |&s: &usize| Some(s) != change.removed;
+        shifts[..at].iter().copied().filter(kept)
+            .chain(change.added)
+            .chain(shifts[at..].iter().copied().filter(kept))
+    }
+
+    /// (hard, soft) change if `mv` were applied: re-scores only what it touches
+    fn delta(&self, state: &ScheduleState, mv: Move) -> (isize, isize) {
+        let (hard, soft) = self.local_delta(state, mv);
+        (hard + self.time_delta(state, mv), soft)
+    }
+
+    /// Coverage, skill and preference part of the delta: O(1) per touched pair
+    fn local_delta(&self, state: &ScheduleState, mv: Move) -> (isize, isize) {
+        let (mut hard, mut soft) = (0, 0);
+
+        for (shift, change) in mv.staffing_changes().into_iter().flatten() {
+            let staff = state.staff[shift].len();
+            hard += self.shortfall(shift, staff.saturating_add_signed(change)) as isize
+                - self.shortfall(shift, staff) as isize;
+        }
+
+        for change in mv.nurse_changes().into_iter().flatten() {
+            for (shift, sign) in [(change.removed, -1), (change.added, 1)] {
+                if let Some(shift) = shift {
+                    let (h, c) = self.pair_cost(change.nurse, shift);
+                    hard += sign * h;
+                    soft += sign * c;
+                }
+            }
+        }
+
+        (hard, soft)
+    }
+
+    /// Working-time part of the delta: re-scores the touched nurses' own shifts
+    fn time_delta(&self, state: &ScheduleState, mv: Move) -> isize {
+        mv.nurse_changes().into_iter().flatten()
+            .map(|change| {
+                let shifts = self.changed_shifts(&state.nurse_shifts[change.nurse], change);
+                self.time_violations(change.nurse, shifts) as isize - state.nurse_time[change.nurse] as isize
+            })
+            .sum()
+    }
+
+    /// Does `mv` lower the objective?
+    ///
+    /// The working-time rules can at best clear the touched nurses' current
+    /// violations; when even that cannot pay for the local delta, they are
+    /// never re-scored. Near a local optimum this skips almost every move.
+    fn improves(&self, state: &ScheduleState, mv: Move) -> bool {
+        let (hard, soft) = self.local_delta(state, mv);
+        let best_time: isize = mv.nurse_changes().into_iter().flatten()
+            .map(|change| state.nurse_time[change.nurse] as isize)
+            .sum();
+        if (hard - best_time) * HARD_WEIGHT + soft >= 0 {
+            return false;
+        }
+        (hard + self.time_delta(state, mv)) * HARD_WEIGHT + soft < 0
+    }
+
+    fn apply(&self, state: &mut ScheduleState, mv: Move) {
+        let (hard, soft) = self.delta(state, mv);
+
+        for change in mv.nurse_changes().into_iter().flatten() {
+            if let Some(shift) = change.removed {
+                state.staff[shift].retain(|&n| n != change.nurse);
+            }
+            if let Some(shift) = change.added {
+                state.staff[shift].push(change.nurse);
+            }
+            let shifts = self.changed_shifts(&state.nurse_shifts[change.nurse], change).collect();
+            state.nurse_shifts[change.nurse] = shifts;
+            state.nurse_time[change.nurse] = self.time_violations(change.nurse, state.nurse_shifts[change.nurse].iter().copied());
+        }
+
+        state.hard = state.hard.saturating_add_signed(hard);
+        state.soft = state.soft.saturating_add_signed(soft);
+    }
+
+    /// Local moves: add, remove, move, swap - generated lazily as descriptors, nothing cloned
+    ///
+    /// The whole neighborhood in scan order; descend walks it block by block.
+    #[cfg(test)]
+    fn neighbors<'a>(&'a self, state: &'a ScheduleState) -> impl Iterator<Item = Move> + 'a {
+        (0..NEIGHBOR_KINDS * self.data.shifts.len()).flat_map(move |position| self.neighbors_at(state, position))
+    }
+
+    /// One block of the neighborhood: moves of one kind anchored on one shift,
+    /// adds to every shift first, then reassignments, removals and swaps
+    ///
+    /// Nurses join only shifts they are eligible for; pinned nurses never leave.
+    fn neighbors_at<'a>(&'a self, state: &'a ScheduleState, position: usize) -> impl Iterator<Item = Move> + 'a {
+        let num_shifts = self.data.shifts.len();
+        let (kind, shift) = (position / num_shifts, position % num_shifts);
+        let free = move |nurse: usize, shift: usize| self.is_free(state, nurse, shift);
+        let movable = move |nurse: usize, shift: usize| !self.is_pinned(nurse, shift);
+        let staff = &state.staff[shift];
+        let required = self.data.shifts[shift].required_staff;
+
+        // Move 1: Add nurse to understaffed shift
+        let adds = (kind == 0 && staff.len() < required).then(|| self.eligible_nurses[shift].iter().copied()
+            .filter(move |&nurse| free(nurse, shift))
+            .map(move |nurse| Move::Add { shift, nurse }));
+
+        // Move 2: Reassign a nurse from one shift to another
+        let reassigns = (kind == 1).then(|| staff.iter().copied()
+            .filter(move |&nurse| movable(nurse, shift))
+            .flat_map(move |nurse| self.eligible_shifts[nurse].iter().copied()
+                .filter(move |&to| to != shift && free(nurse, to))
+                .map(move |to| Move::Reassign { nurse, from: shift, to })));
+
+        // Move 3: Remove from overstaffed shift
+        let removes = (kind == 2 && staff.len() > required).then(|| staff.iter().copied()
+            .filter(move |&nurse| movable(nurse, shift))
+            .map(move |nurse| Move::Remove { shift, nurse }));
+
+        // Move 4: Swap two nurses between nearby shifts
+        let swaps = (kind == 3).then(|| self.swap_partners[shift].iter().copied()
+            .flat_map(move |shift_b| staff.iter()
+                .flat_map(move |&nurse_a| state.staff[shift_b].iter().map(move |&nurse_b| (nurse_a, nurse_b, shift_b))))
+            .filter(move |&(nurse_a, nurse_b, shift_b)| movable(nurse_a, shift) && movable(nurse_b, shift_b))
+            .filter(move |&(nurse_a, nurse_b, shift_b)| free(nurse_a, shift_b) && free(nurse_b, shift))
+            .map(move |(nurse_a, nurse_b, shift_b)| Move::Swap { nurse_a, shift_a: shift, nurse_b, shift_b }));
+
+        adds.into_iter().flatten()
+            .chain(reassigns.into_iter().flatten())
+            .chain(removes.into_iter().flatten())
+            .chain(swaps.into_iter().flatten())
+    }
+
+    /// Local search optimization - first improvement
+    ///
+    /// Iteration cap derived from the roster: every open place needs at least
+    /// one improving move, so a fixed cap would stall month-long rosters.
+    fn solve(&self, schedule: &mut Schedule) -> (usize, usize) {
+        let mut state = self.state(schedule);
+        let iterations = self.descend(&mut state, |mv| self.apply_to_schedule(schedule, mv));
+        (state.objective(), iterations)
+    }
+
+    /// Open places over the whole roster (the unit of every derived budget)
+    fn places(&self) -> usize {
+        self.data.shifts.iter().map(|s| s.required_staff).sum()
+    }
+
+    /// First-improvement descent to a local optimum; `on_move` sees every applied move
+    ///
+    /// Resumes each scan at the neighborhood block of the last improving move:
+    /// restarting from the first block would re-check every block already
+    /// settled. Open places are re-checked first, and a full round without an
+    /// improving move ends it.
+    fn descend(&self, state: &mut ScheduleState, mut on_move: impl FnMut(Move)) -> usize {
+        let num_shifts = self.data.shifts.len();
+        let blocks = NEIGHBOR_KINDS * num_shifts;
+        let max_iterations = 1000.max(10 * self.places());
+        let mut iterations = 0;
+        let mut position = 0;
+        let mut settled = 0;
+
+        while settled < blocks && iterations < max_iterations {
+            // Open places first: the last move may have freed a nurse for one
+            let improving = (0..num_shifts).flat_map(|shift| self.neighbors_at(state, shift))
+                .chain(self.neighbors_at(state, position))
+                .find(|&mv| self.improves(state, mv));
+            match improving {
+                Some(mv) => {
+                    self.apply(state, mv);
+                    on_move(mv);
+                    iterations += 1;
+                    settled = 0;
+                }
+                None => {
+                    position = (position + 1) % blocks;
+                    settled += 1;
+                }
+            }
+        }
+
+        iterations
+    }
+
+    /// Selected metaheuristic, reproducible from `config.seed`
+    fn search(&self, schedule: &mut Schedule, config: &SearchConfig) -> (usize, usize) {
+        let mut rng = Rng::new(config.seed);
+        let mut state = self.state(schedule);
+        let iterations = match config.method {
+            Method::HillClimbing => return self.solve(schedule),
+            Method::SimulatedAnnealing => {
+                let budget = config.budget.unwrap_or(500 * self.places());
+                self.anneal(&mut state, budget, &mut rng) + self.descend(&mut state, |_| {})
+            }
+            Method::Tabu => {
+                let budget = config.budget.unwrap_or(20 * self.places());
+                self.tabu(&mut state, budget, &mut rng) + self.descend(&mut state, |_| {})
+            }
+            Method::MultiStart => self.multi_start(&mut state, config.budget.unwrap_or(4), &mut rng),
+        };
+
+        *schedule = self.schedule_of(&state);
+        (state.objective(), iterations)
+    }
+
+    /// Where a repair starts: the published roster without the assignments its
+    /// nurses can no longer work, plus the pins
+    fn repair_start(&self, published: &Schedule) -> Schedule {
+        let mut schedule = self.initial();
+        for (s, shift) in self.data.shifts.iter().enumerate() {
+            for &nurse_id in published.assignments.get(&shift.id).into_iter().flatten() {
+                if let Some(&n) = self.nurse_index.get(&nurse_id) {
+                    if self.is_available(n, s) {
+                        schedule.assign(shift.id, nurse_id);
+                    }
+                }
+            }
+        }
+        schedule
+    }
+
+    fn schedule_of(&self, state: &ScheduleState) -> Schedule {
+        self.schedule_from_staff(&state.staff)
+    }
+
+    /// Id-keyed Schedule from nurse positions per shift position
+    fn schedule_from_staff(&self, staff: &[Vec<usize>]) -> Schedule {
+        let mut schedule = self.initial();
+        for (s, nurses) in staff.iter().enumerate() {
+            for &n in nurses {
+                schedule.assign(self.data.shifts[s].id, self.data.nurses[n].id);
+            }
+        }
+        schedule
+    }
+
+    fn objective_delta(&self, state: &ScheduleState, mv: Move) -> isize {
+        let (hard, soft) = self.delta(state, mv);
+        hard * HARD_WEIGHT + soft
+    }
+
+    /// Uniformly drawn move of a random kind (None if the draws keep missing)
+    fn random_move(&self, state: &ScheduleState, rng: &mut Rng) -> Option<Move> {
+        let num_shifts = self.data.shifts.len();
+        if num_shifts == 0 {
+            return None;
+        }
+
+        for _ in 0..RANDOM_MOVE_TRIES {
+            let shift = rng.below(num_shifts);
+            let staff = &state.staff[shift];
+            let mv = match rng.below(4) {
+                0 => {
+                    let eligible = &self.eligible_nurses[shift];
+                    if eligible.is_empty() {
+                        continue;
+                    }
+                    Move::Add { shift, nurse: eligible[rng.below(eligible.len())] }
+                }
+                _ if staff.is_empty() => continue,
+                1 => Move::Remove { shift, nurse: staff[rng.below(staff.len())] },
+                2 => {
+                    let nurse = staff[rng.below(staff.len())];
+                    let options = &self.eligible_shifts[nurse];
+                    if options.is_empty() {
+                        continue;
+                    }
+                    Move::Reassign { nurse, from: shift, to: options[rng.below(options.len())] }
+                }
+                _ => {
+                    let shift_b = rng.below(num_shifts);
+                    let other = &state.staff[shift_b];
+                    if other.is_empty() {
+                        continue;
+                    }
+                    Move::Swap {
+                        nurse_a: staff[rng.below(staff.len())],
+                        shift_a: shift,
+                        nurse_b: other[rng.below(other.len())],
+                        shift_b,
+                    }
+                }
+            };
+            if self.is_valid(state, mv) {
+                return Some(mv);
+            }
+        }
+        None
+    }
+
+    /// Joins only eligible nurses to shifts they are not on yet, never moves a pinned nurse
+    fn is_valid(&self, state: &ScheduleState, mv: Move) -> bool {
+        match mv {
+            Move::Add { shift, nurse } => self.is_free(state, nurse, shift),
+            Move::Remove { shift, nurse } => !self.is_pinned(nurse, shift),
+            Move::Reassign { nurse, from, to } => from != to
+                && !self.is_pinned(nurse, from)
+                && self.is_free(state, nurse, to),
+            Move::Swap { nurse_a, shift_a, nurse_b, shift_b } => shift_a != shift_b
+                && !self.is_pinned(nurse_a, shift_a)
+                && !self.is_pinned(nurse_b, shift_b)
+                && self.is_free(state, nurse_a, shift_b)
+                && self.is_free(state, nurse_b, shift_a),
+        }
+    }
+
+    /// Simulated annealing over random moves; leaves the best state seen
+    ///
+    /// Temperatures derived from the roster: T0 accepts the average sampled
+    /// uphill move half the time, the final temperature accepts a single
+    /// preference miss 1% of the time, cooling is geometric in between.
+    fn anneal(&self, state: &mut ScheduleState, budget: usize, rng: &mut Rng) -> usize {
+        let uphill: Vec<f64> = (0..ANNEALING_SAMPLES)
+            .filter_map(|_| self.random_move(state, rng))
+            .map(|mv| self.objective_delta(state, mv))
+            .filter(|&d| d > 0)
+            .map(|d| d as f64)
+            .collect();
+        let t0 = if uphill.is_empty() { 1.0 } else { uphill.iter().sum::<f64>() / uphill.len() as f64 / 2f64.ln() };
+        let t_end = 1.0 / 100f64.ln();
+        let cooling = (t_end / t0).min(1.0).powf(1.0 / budget.max(1) as f64);
+
+        let mut best = state.clone();
+        let mut temperature = t0;
+        let mut iterations = 0;
+
+        while iterations < budget {
+            let Some(mv) = self.random_move(state, rng) else { break };
+            let delta = self.objective_delta(state, mv);
+            if delta <= 0 || rng.unit() < (-(delta as f64) / temperature).exp() {
+                self.apply(state, mv);
+                if state.objective() < best.objective() {
+                    best = state.clone();
+                }
+            }
+            temperature *= cooling;
+            iterations += 1;
+        }
+
+        *state = best;
+        iterations
+    }
+
+    /// Tabu search over sampled candidate lists; leaves the best state seen
+    ///
+    /// A (nurse, shift) pair touched by a move stays tabu for sqrt(nurses +
+    /// shifts) iterations; a tabu move is still taken if it beats the best
+    /// state so far (aspiration). Each iteration samples nurses + shifts moves.
+    fn tabu(&self, state: &mut ScheduleState, budget: usize, rng: &mut Rng) -> usize {
+        let num_shifts = self.data.shifts.len();
+        let size = self.data.nurses.len() + num_shifts;
+        let tenure = (size as f64).sqrt().ceil() as usize;
+        let mut tabu_until = vec![0; self.data.nurses.len() * num_shifts];
+
+        let mut best = state.clone();
+        for iteration in 1..=budget {
+            let mut chosen: Option<(Move, isize)> = None;
+            for _ in 0..size {
+                let Some(mv) = self.random_move(state, rng) else { break };
+                let delta = self.objective_delta(state, mv);
+                let is_tabu = mv.pairs().any(|(n, s)| tabu_until[n * num_shifts + s] > iteration);
+                let aspiration = (state.objective() as isize + delta) < best.objective() as isize;
+                if (!is_tabu || aspiration) && chosen.is_none_or(|(_, d)| delta < d) {
+                    chosen = Some((mv, delta));
+                }
+            }
+
+            let Some((mv, _)) = chosen else { continue };
+            self.apply(state, mv);
+            for (n, s) in mv.pairs() {
+                tabu_until[n * num_shifts + s] = iteration + tenure;
+            }
+            if state.objective() < best.objective() {
+                best = state.clone();
+            }
+        }
+
+        *state = best;
+        budget
+    }
+
+    /// Descent from the given state, then from `restarts` random full rosters; keeps the best
+    fn multi_start(&self, state: &mut ScheduleState, restarts: usize, rng: &mut Rng) -> usize {
+        let mut iterations = self.descend(state, |_| {});
+        for _ in 0..restarts {
+            let mut candidate = self.random_start(rng);
+            iterations += self.descend(&mut candidate, |_| {});
+            if candidate.objective() < state.objective() {
+                *state = candidate;
+            }
+        }
+        iterations
+    }
+
+    /// Every shift filled around its pins with randomly chosen eligible nurses
+    fn random_start(&self, rng: &mut Rng) -> ScheduleState {
+        let mut state = self.state(&self.initial());
+        for (shift, eligible) in self.eligible_nurses.iter().enumerate() {
+            let mut pool: Vec<usize> = eligible.iter().copied().filter(|n| !state.staff[shift].contains(n)).collect();
+            for _ in 0..self.shortfall(shift, state.staff[shift].len()).min(pool.len()) {
+                let nurse = pool.swap_remove(rng.below(pool.len()));
+                self.apply(&mut state, Move::Add { shift, nurse });
+            }
+        }
+        state
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ExactConfig {
+    /// Search nodes before giving up on completeness (the bound stays valid)
+    node_limit: usize,
+    /// Ignore preferences: a positive bound then proves the instance infeasible
+    hard_only: bool,
+}
+
+impl Default for ExactConfig {
+    fn default() -> Self {
+        Self { node_limit: 1_000_000, hard_only: false }
+    }
+}
+
+/// Branch-and-bound outcome, in the objective selected by ExactConfig
+#[derive(Debug, Clone)]
+struct ExactReport {
+    /// Best roster found (the starting roster unless branch-and-bound beat it)
+    schedule: Schedule,
+    objective: usize,
+    /// Objective of the starting (local-search) roster
+    start_objective: usize,
+    /// No roster scores below this
+    lower_bound: usize,
+    nodes: usize,
+    /// Whole tree searched: objective == lower_bound is proven optimal
+    complete: bool,
+    hard_only: bool,
+}
+
+impl ExactReport {
+    /// Relative gap between the starting roster and the proven bound
+    fn gap(&self) -> f64 {
+        (self.start_objective - self.lower_bound.min(self.start_objective)) as f64 / self.start_objective.max(1) as f64
+    }
+
+    fn infeasible(&self) -> bool {
+        self.hard_only && self.lower_bound > 0
+    }
+}
+
+impl std::fmt::Display for ExactReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let objective = if self.hard_only { "hard violations" } else { "hard violations * 1000 + soft cost" };
+        writeln!(f, "Objective: {objective}")?;
+        writeln!(f, "Nodes: {} ({})", self.nodes, if self.complete { "search complete" } else { "node limit reached" })?;
+        writeln!(f, "Lower bound: {}", self.lower_bound)?;
+        writeln!(f, "Local search: {} (gap {:.1}%)", self.start_objective, 100.0 * self.gap())?;
+        writeln!(f, "Best found: {}", self.objective)?;
+        if self.complete {
+            writeln!(f, "OPTIMAL: no roster does better than {}", self.objective)?;
+        }
+        if self.infeasible() {
+            writeln!(f, "INFEASIBLE: every roster has at least {} hard violations", self.lower_bound)?;
+        }
+        Ok(())
+    }
+}
+
+/// Depth-first branch-and-bound over (shift, slot) decisions, shifts in start order
+///
+/// Only skilled nurses and at most required_staff per shift are branched on:
+/// every cost term is monotone in the assignments, so an extra or unskilled
+/// nurse never lowers the objective. Each slot takes a nurse (in increasing
+/// index order, so every crew is enumerated once) or stays empty.
+///
+/// Bound at a node: the exact cost of the decided assignments (working-time
+/// violations can only grow as shifts are added) plus, for every undecided
+/// shift, the places no skilled nurse is left for and the preference misses
+/// its skilled nurses cannot avoid.
+///
+/// Pinned nurses sit on their shifts from the root; slots only fill the rest.
+struct BranchAndBound<'a> {
+    solver: &'a ScheduleSolver,
+    /// Shift positions by start time
+    order: Vec<usize>,
+    /// Places left open by the pins over order[i..]
+    suffix_required: Vec<usize>,
+    /// Static bound of order[i..]
+    suffix_bound: Vec<isize>,
+    /// (1000, 1), or (1, 0) when counting hard violations only
+    hard_weight: isize,
+    soft_weight: isize,
+    node_limit: usize,
+    nodes: usize,
+    incumbent: isize,
+    best_staff: Option<Vec<Vec<usize>>>,
+    /// Lowest bound among subtrees left unexplored at the node limit
+    abandoned: isize,
+}
+
+impl<'a> BranchAndBound<'a> {
+    fn new(solver: &'a ScheduleSolver, config: &ExactConfig, incumbent: isize) -> Self {
+        let (hard_weight, soft_weight) = if config.hard_only { (1, 0) } else { (HARD_WEIGHT, 1) };
+        let mut order: Vec<usize> = (0..solver.data.shifts.len()).collect();
+        solver.sort_by_start(&mut order);
+
+        let mut suffix_required = vec![0; order.len() + 1];
+        let mut suffix_bound = vec![0; order.len() + 1];
+        for (i, &s) in order.iter().enumerate().rev() {
+            let open = solver.shortfall(s, solver.pinned[s].len());
+            let candidates: Vec<usize> = solver.eligible_nurses[s].iter()
+                .copied()
+                .filter(|&n| !solver.is_pinned(n, s))
+                .collect();
+            let preferring = candidates.iter().filter(|&&n| solver.pair_cost(n, s).1 == 0).count();
+            let filled = open.min(candidates.len());
+            let bound = (open - filled) as isize * hard_weight + filled.saturating_sub(preferring) as isize * soft_weight;
+
+            suffix_required[i] = suffix_required[i + 1] + open;
+            suffix_bound[i] = suffix_bound[i + 1] + bound;
+        }
+
+        Self {
+            solver,
+            order,
+            suffix_required,
+            suffix_bound,
+            hard_weight,
+            soft_weight,
+            node_limit: config.node_limit,
+            nodes: 0,
+            incumbent,
+            best_staff: None,
+            abandoned: isize::MAX,
+        }
+    }
+
+    fn weighted(&self, hard: isize, soft: isize) -> isize {
+        hard * self.hard_weight + soft * self.soft_weight
+    }
+
+    /// Bound with order[i] filling from `from_nurse` on
+    fn bound(&self, state: &ScheduleState, i: usize, from_nurse: usize) -> isize {
+        let s = self.order[i];
+        let open_here = self.solver.shortfall(s, state.staff[s].len());
+        let open = open_here + self.suffix_required[i + 1];
+        let decided_hard = state.hard as isize - open as isize;
+
+        let eligible = &self.solver.eligible_nurses[s];
+        let candidates = eligible.len() - eligible.partition_point(|&n| n < from_nurse);
+        let unfillable = open_here.saturating_sub(candidates) as isize;
+
+        self.weighted(decided_hard + unfillable, state.soft as isize) + self.suffix_bound[i + 1]
+    }
+
+    fn branch(&mut self, state: &mut ScheduleState, i: usize, from_nurse: usize) {
+        if i == self.order.len() {
+            let value = self.weighted(state.hard as isize, state.soft as isize);
+            if value < self.incumbent {
+                self.incumbent = value;
+                self.best_staff = Some(state.staff.clone());
+            }
+            return;
+        }
+
+        let s = self.order[i];
+        if self.solver.shortfall(s, state.staff[s].len()) == 0 {
+            return self.branch(state, i + 1, 0);
+        }
+
+        let bound = self.bound(state, i, from_nurse);
+        if bound >= self.incumbent {
+            return;
+        }
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            self.abandoned = self.abandoned.min(bound);
+            return;
+        }
+
+        // Cheapest nurse first: good rosters early make the incumbent prune sooner
+        let mut children: Vec<(isize, usize)> = self.solver.eligible_nurses[s].iter()
+            .filter(|&&n| n >= from_nurse && !state.staff[s].contains(&n))
+            .map(|&n| {
+                let (hard, soft) = self.solver.delta(state, Move::Add { shift: s, nurse: n });
+                (self.weighted(hard, soft), n)
+            })
+            .collect();
+        children.sort_unstable();
+
+        for (_, nurse) in children {
+            self.solver.apply(state, Move::Add { shift: s, nurse });
+            self.branch(state, i, nurse + 1);
+            self.solver.apply(state, Move::Remove { shift: s, nurse });
+        }
+
+        // The remaining places of this shift stay empty
+        self.branch(state, i + 1, 0);
+    }
+}
+
+impl ScheduleSolver {
+    /// Branch-and-bound from `start` (typically the local-search roster) as incumbent
+    ///
+    /// Not for repairs: the bound assumes adding a nurse never lowers the cost,
+    /// and keeping a published assignment does (main refuses --repair --exact).
+    fn exact(&self, start: &Schedule, config: &ExactConfig) -> ExactReport {
+        let start_state = self.state(start);
+        let mut search = BranchAndBound::new(self, config, 0);
+        let start_objective = search.weighted(start_state.hard as isize, start_state.soft as isize);
+        search.incumbent = start_objective;
+
+        if !search.order.is_empty() {
+            let mut state = self.state(&self.initial());
+            search.branch(&mut state, 0, 0);
+        }
+
+        let complete = search.nodes <= search.node_limit;
+        let lower_bound = if complete { search.incumbent } else { search.incumbent.min(search.abandoned) };
+        let schedule = match &search.best_staff {
+            Some(staff) => self.schedule_from_staff(staff),
+            None => start.clone(),
+        };
+
+        ExactReport {
+            schedule,
+            objective: search.incumbent as usize,
+            start_objective: start_objective as usize,
+            lower_bound: lower_bound.max(0) as usize,
+            nodes: search.nodes.min(search.node_limit),
+            complete,
+            hard_only: config.hard_only,
+        }
+    }
+}
+
+/// Hard rule behind a violation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rule {
+    Coverage,
+    Skill,
+    Rest,
+    /// A rest violation from a night shift into a day shift
+    NightToDay,
+    MaxHours,
+    ConsecutiveDays,
+    /// Absence or excluded shift
+    Unavailable,
+    Pinned,
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Rule::Coverage => "coverage",
+            Rule::Skill => "skill",
+            Rule::Rest => "rest",
+            Rule::NightToDay => "night→day",
+            Rule::MaxHours => "max hours",
+            Rule::ConsecutiveDays => "consecutive days",
+            Rule::Unavailable => "unavailable",
+            Rule::Pinned => "pinned",
+        })
+    }
+}
+
+/// One broken hard rule in a schedule
+#[derive(Debug, Clone)]
+struct Violation {
+    rule: Rule,
+    /// Nurse position (None for coverage, or a nurse id missing from the data)
+    nurse: Option<usize>,
+    /// Shift position
+    shift: usize,
+    /// Hard violations it accounts for (a coverage gap counts each missing place)
+    count: usize,
+    detail: String,
+    suggestion: String,
+}
+
+/// Staffing demand on a group of shifts that the nurses able to work them
+/// cannot meet without breaking a rule, whatever the roster
+#[derive(Debug, Clone)]
+struct Conflict {
+    summary: String,
+    /// Shift positions, a smallest group that still overflows
+    shifts: Vec<usize>,
+    suggestions: Vec<String>,
+}
+
+fn skills_label(skills: &[String]) -> String {
+    if skills.is_empty() { "any".into() } else { skills.join("+") }
+}
+
+fn hours_label(minutes: i64) -> String {
+    if minutes % 60 == 0 { format!("{}h", minutes / 60) } else { format!("{:.1}h", minutes as f64 / 60.0) }
+}
+
+impl ScheduleSolver {
+    /// "ICU night 2024-03-04 23:00"
+    fn shift_label(&self, shift: usize) -> String {
+        format!("{} {}", self.data.shifts[shift].name, self.times[shift].start)
+    }
+
+    /// Every hard violation of `schedule`, one entry per nurse/shift/rule;
+    /// the counts add up to hard_violations
+    fn violations(&self, schedule: &Schedule) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut nurse_shifts: Vec<Vec<usize>> = vec![Vec::new(); self.data.nurses.len()];
 
-        // Check shift coverage
-        for shift in &self.data.shifts {
-            let assigned = schedule.assignments.get(&shift.id).map_or(0, std::vec::Vec::len);
-            if assigned < shift.required_staff {
-                violations += shift.required_staff - assigned;
-            }
-        }
+        for (s, shift) in self.data.shifts.iter().enumerate() {
+            let nurses = schedule.assignments.get(&shift.id).map_or(&[][..], Vec::as_slice);
+            let skills = skills_label(&shift.required_skills);
 
-        // Check skills
-        for shift in &self.data.shifts {
-            if let Some(nurses) = schedule.assignments.get(&shift.id) {
-                for &nurse_id in nurses {
-                    if !self.can_work(nurse_id, shift) {
-                        violations += 1;
-                    }
-                }
+            let missing = self.shortfall(s, nurses.len());
+            if missing > 0 {
+                let qualified = self.eligible_nurses[s].len();
+                let suggestion = if qualified < shift.required_staff {
+                    format!("only {qualified} nurses have {skills}: hire or cross-train {}, or lower required_staff",
+                            shift.required_staff - qualified)
+                } else {
+                    format!("assign {missing} more {skills} nurse(s), or lower required_staff")
+                };
+                violations.push(Violation {
+                    rule: Rule::Coverage,
+                    nurse: None,
+                    shift: s,
+                    count: missing,
+                    detail: format!("{} of {} nurses assigned", nurses.len(), shift.required_staff),
+                    suggestion,
+                });
             }
-        }
 
-        // Check rest periods (8 hours between shifts)
-        for nurse in &self.data.nurses {
-            let mut nurse_shifts: Vec<&Shift> = schedule
-                .get_nurse_shifts(nurse.id)
-                .iter()
-                .filter_map(|&sid| self.data.shifts.iter().find(|s| s.id == sid))
-                .collect();
-            
-            nurse_shifts.sort_by_key(|s| (s.day, &s.shift_type));
-            
-            for window in nurse_shifts.windows(2) {
-                let (s1, s2) = (window[0], window[1]);
-                // Same day, consecutive shifts without rest
-                if s1.day == s2.day {
-                    let order = ["day", "evening", "night"];
-                    let i1 = order.iter().position(|&x| x == s1.shift_type);
-                    let i2 = order.iter().position(|&x| x == s2.shift_type);
-                    if let (Some(i1), Some(i2)) = (i1, i2) {
-                        if i2 == i1 + 1 {
-                            violations += 1; // Back-to-back shifts
+            for &nurse_id in nurses {
+                let n = self.nurse_index.get(&nurse_id).copied();
+                if !self.can_work(nurse_id, shift) {
+                    let (who, lacking) = match n {
+                        Some(n) => {
+                            let nurse = &self.data.nurses[n];
+                            let lacking: Vec<String> = shift.required_skills.iter()
+                                .filter(|skill| !nurse.skills.contains(skill))
+                                .cloned()
+                                .collect();
+                            (nurse.name.clone(), lacking)
                         }
-                    }
+                        None => (format!("nurse #{nurse_id}"), shift.required_skills.clone()),
+                    };
+                    violations.push(Violation {
+                        rule: Rule::Skill,
+                        nurse: n,
+                        shift: s,
+                        count: 1,
+                        detail: format!("lacks {}", skills_label(&lacking)),
+                        suggestion: format!("reassign to a {skills} nurse, or cross-train {who}"),
+                    });
                 }
-                // Night shift followed by day shift next day
-                if s2.day == s1.day + 1 && s1.shift_type == "night" && s2.shift_type == "day" {
-                    violations += 1;
+                if let Some(n) = n {
+                    if !self.is_available(n, s) {
+                        violations.push(self.unavailable_violation(n, s));
+                    }
+                    nurse_shifts[n].push(s);
                 }
             }
-        }
 
-        // Check max hours
-        for nurse in &self.data.nurses {
-            let total_hours: usize = schedule
-                .get_nurse_shifts(nurse.id)
-                .iter()
-                .filter_map(|&sid| self.data.shifts.iter().find(|s| s.id == sid))
-                .map(|s| s.hours)
-                .sum();
-            
-            if total_hours > nurse.max_hours_week {
-                // Each 8-hour shift over counts as 1 violation (ceiling division)
-                violations += (total_hours - nurse.max_hours_week).div_ceil(8);
+            for n in self.missing_pins(s, nurses) {
+                violations.push(Violation {
+                    rule: Rule::Pinned,
+                    nurse: Some(n),
+                    shift: s,
+                    count: 1,
+                    detail: "pinned but not assigned".into(),
+                    suggestion: format!("put {} back on the shift, or unpin them", self.data.nurses[n].name),
+                });
             }
         }
 
+        for (n, shifts) in nurse_shifts.iter_mut().enumerate() {
+            self.sort_by_start(shifts);
+            violations.extend(self.time_rule_violations(n, shifts));
+        }
+
         violations
     }
 
-    /// Calculate soft constraint cost (preferences)
-    fn soft_cost(&self, schedule: &Schedule) -> usize {
-        let mut cost = 0;
+    fn unavailable_violation(&self, n: usize, s: usize) -> Violation {
+        let nurse = &self.data.nurses[n];
+        let shift = &self.data.shifts[s];
+        let detail = match nurse.unavailable.iter().find(|a| self.times[s].overlaps(a.start, a.end)) {
+            Some(absence) if absence.reason.is_empty() => format!("absent {} to {}", absence.start, absence.end),
+            Some(absence) => format!("absent ({}) {} to {}", absence.reason, absence.start, absence.end),
+            None => "excluded from this shift".into(),
+        };
+        Violation {
+            rule: Rule::Unavailable,
+            nurse: Some(n),
+            shift: s,
+            count: 1,
+            detail,
+            suggestion: format!("reassign to an available {} nurse", skills_label(&shift.required_skills)),
+        }
+    }
 
-        for nurse in &self.data.nurses {
-            for &shift_id in &schedule.get_nurse_shifts(nurse.id) {
-                if let Some(shift) = self.data.shifts.iter().find(|s| s.id == shift_id) {
-                    if !nurse.preferred_shifts.contains(&shift.shift_type) {
-                        cost += 1;
-                    }
-                }
-            }
+    /// Rest, rolling-hours and consecutive-day violations of one nurse (shifts sorted by start)
+    fn time_rule_violations(&self, n: usize, shifts: &[usize]) -> Vec<Violation> {
+        let nurse = &self.data.nurses[n];
+        let rules = &self.data.rules;
+        let times: Vec<ShiftTime> = shifts.iter().map(|&s| self.times[s]).collect();
+        let mut violations = Vec::new();
+
+        for (i, rest) in self.rest_breaches(&times) {
+            let (previous, shift) = (shifts[i - 1], shifts[i]);
+            let night_to_day = self.data.shifts[previous].shift_type == "night"
+                && self.data.shifts[shift].shift_type == "day";
+            let detail = if rest < 0 {
+                format!("overlaps {}", self.shift_label(previous))
+            } else {
+                format!("{} after {} (needs {}h)", hours_label(rest), self.shift_label(previous), rules.min_rest_hours)
+            };
+            let suggestion = if rest < 0 {
+                "give one of the two shifts to another nurse".to_string()
+            } else {
+                format!("give one of the two shifts to another nurse, or lower min_rest_hours to {}h", rest / 60)
+            };
+            violations.push(Violation {
+                rule: if night_to_day { Rule::NightToDay } else { Rule::Rest },
+                nurse: Some(n),
+                shift,
+                count: 1,
+                detail,
+                suggestion,
+            });
         }
 
-        cost
-    }
+        for (i, worked) in self.rolling_hours_breaches(nurse, &times) {
+            violations.push(Violation {
+                rule: Rule::MaxHours,
+                nurse: Some(n),
+                shift: shifts[i],
+                count: 1,
+                detail: format!("{} in the 7 days to {} (limit {}h)", hours_label(worked), times[i].end, nurse.max_hours_week),
+                suggestion: format!("move a shift of that week to another nurse, or raise {}'s max_hours_week to {}h",
+                                    nurse.name, (worked + 59) / 60),
+            });
+        }
 
-    /// Total objective: hard violations * 1000 + soft cost
-    fn objective(&self, schedule: &Schedule) -> usize {
-        self.hard_violations(schedule) * 1000 + self.soft_cost(schedule)
-    }
+        for (i, run) in self.consecutive_day_breaches(&times) {
+            violations.push(Violation {
+                rule: Rule::ConsecutiveDays,
+                nurse: Some(n),
+                shift: shifts[i],
+                count: 1,
+                detail: format!("working day {} in a row (limit {})", run, rules.max_consecutive_days.unwrap_or(run)),
+                suggestion: format!("give {} a day off in that run, or raise max_consecutive_days to {run}", nurse.name),
+            });
+        }
 
-    /// Generate initial schedule - empty, saturation will fill it
-    fn initial(&self) -> Schedule {
-        Schedule::new(self.data.shifts.len())
+        violations
     }
 
-    /// Local moves: add, remove, move, swap
-    fn neighbors(&self, schedule: &Schedule) -> Vec<Schedule> {
-        let mut neighbors = Vec::new();
+    /// Smallest groups of shifts that force a violation in every roster
+    ///
+    /// Staffing: shifts that overlap or start within min_rest_hours of each
+    /// other's end need distinct nurses. Their extended intervals pairwise
+    /// intersect, so they share an instant, and scanning every shift start finds
+    /// all such groups. For each skill set on them, the shifts needing at least
+    /// those skills cannot take more nurses than are skilled and available for
+    /// one of them.
+    ///
+    /// Hours: shifts inside one 7-day span cannot take more hours than those
+    /// nurses' max_hours_week add up to.
+    fn conflicts(&self) -> Vec<Conflict> {
+        let min_rest = self.data.rules.min_rest_hours as i64 * 60;
+        let mut skill_sets: Vec<Vec<String>> = self.data.shifts.iter()
+            .map(|shift| {
+                let mut skills = shift.required_skills.clone();
+                skills.sort();
+                skills
+            })
+            .collect();
+        skill_sets.sort();
+        skill_sets.dedup();
+        // Nurses eligible for at least one shift of a group
+        let pool = |group: &[usize]| -> Vec<&Nurse> {
+            let mut nurses: Vec<usize> = group.iter().flat_map(|&s| self.eligible_nurses[s].iter().copied()).collect();
+            nurses.sort_unstable();
+            nurses.dedup();
+            nurses.into_iter().map(|n| &self.data.nurses[n]).collect()
+        };
+        let needs = |shift: usize, skills: &[String]| {
+            skills.iter().all(|s| self.data.shifts[shift].required_skills.contains(s))
+        };
 
-        // Move 1: Add nurse to understaffed shift
-        for shift in &self.data.shifts {
-            let current = schedule.assignments.get(&shift.id).map_or(0, std::vec::Vec::len);
-            if current < shift.required_staff {
-                for nurse in &self.data.nurses {
-                    if self.can_work(nurse.id, shift) {
-                        let assigned = schedule.assignments.get(&shift.id).unwrap();
-                        if !assigned.contains(&nurse.id) {
-                            let mut new_schedule = schedule.clone();
-                            new_schedule.assign(shift.id, nurse.id);
-                            neighbors.push(new_schedule);
-                        }
-                    }
-                }
-            }
-        }
+        let mut conflicts = Vec::new();
+        let mut seen = HashSet::new();
+        let mut by_start: Vec<usize> = (0..self.data.shifts.len()).collect();
+        self.sort_by_start(&mut by_start);
 
-        // Move 2: Reassign a nurse from one shift to another
-        for shift in &self.data.shifts {
-            if let Some(nurses) = schedule.assignments.get(&shift.id) {
-                for &nurse_id in nurses {
-                    for other_shift in &self.data.shifts {
-                        if other_shift.id != shift.id && self.can_work(nurse_id, other_shift) {
-                            let mut new_schedule = schedule.clone();
-                            new_schedule.unassign(shift.id, nurse_id);
-                            new_schedule.assign(other_shift.id, nurse_id);
-                            neighbors.push(new_schedule);
-                        }
-                    }
-                }
-            }
-        }
+        for &at in &by_start {
+            let instant = self.times[at].start.0;
+            let week_end = instant + WEEK_MINUTES;
 
-        // Move 3: Remove from overstaffed shift
-        for shift in &self.data.shifts {
-            if let Some(nurses) = schedule.assignments.get(&shift.id) {
-                if nurses.len() > shift.required_staff {
-                    for &nurse_id in nurses {
-                        let mut new_schedule = schedule.clone();
-                        new_schedule.unassign(shift.id, nurse_id);
-                        neighbors.push(new_schedule);
+            for skills in &skill_sets {
+                let label = skills_label(skills);
+
+                // Staffing at this instant
+                let group: Vec<usize> = by_start.iter().copied()
+                    .filter(|&s| self.times[s].start.0 <= instant && instant < self.times[s].end.0 + min_rest)
+                    .filter(|&s| needs(s, skills))
+                    .collect();
+                let available = pool(&group).len();
+                let staff = |s: usize| self.data.shifts[s].required_staff as i64;
+                if let Some(shifts) = overflowing(&group, available as i64, staff) {
+                    if seen.insert((skills.clone(), shifts.clone())) {
+                        conflicts.push(self.staffing_conflict(shifts, skills, available));
                     }
                 }
-            }
-        }
 
-        // Move 4: Swap two nurses between shifts
-        for s1 in &self.data.shifts {
-            for s2 in &self.data.shifts {
-                if s1.id >= s2.id { continue; }
-                if let (Some(n1s), Some(n2s)) = (
-                    schedule.assignments.get(&s1.id),
-                    schedule.assignments.get(&s2.id),
-                ) {
-                    for &n1 in n1s {
-                        for &n2 in n2s {
-                            if self.can_work(n1, s2) && self.can_work(n2, s1) {
-                                let mut new_schedule = schedule.clone();
-                                new_schedule.unassign(s1.id, n1);
-                                new_schedule.unassign(s2.id, n2);
-                                new_schedule.assign(s1.id, n2);
-                                new_schedule.assign(s2.id, n1);
-                                neighbors.push(new_schedule);
-                            }
+                // Hours in the week from this instant
+                let group: Vec<usize> = by_start.iter().copied()
+                    .filter(|&s| self.times[s].start.0 >= instant && self.times[s].end.0 <= week_end)
+                    .filter(|&s| needs(s, skills))
+                    .collect();
+                let nurses = pool(&group);
+                let capacity: i64 = nurses.iter().map(|n| n.max_hours_week as i64 * 60).sum();
+                let minutes = |s: usize| staff(s) * (self.times[s].end.0 - self.times[s].start.0);
+                if let Some(shifts) = overflowing(&group, capacity, minutes) {
+                    if seen.insert((skills.clone(), shifts.clone())) {
+                        let demand: i64 = shifts.iter().map(|&s| minutes(s)).sum();
+                        let largest = nurses.iter().map(|n| n.max_hours_week as i64 * 60).max().unwrap_or(40 * 60);
+                        let mut suggestions = vec![
+                            format!("hire {} more {label} nurse(s)", (demand - capacity + largest - 1) / largest.max(1)),
+                            format!("raise max_hours_week of the {label} nurses by {} in total", hours_label(demand - capacity)),
+                        ];
+                        if let Some(&s) = shifts.iter().max_by_key(|&&s| minutes(s)) {
+                            suggestions.push(format!("lower required_staff on {}", self.shift_label(s)));
                         }
+                        conflicts.push(Conflict {
+                            summary: format!(
+                                "{} available {label} nurse(s) can work {} in a week but {} shifts from {} need {}",
+                                nurses.len(), hours_label(capacity), shifts.len(), &self.times[at].start.to_string()[..10],
+                                hours_label(demand)),
+                            shifts,
+                            suggestions,
+                        });
                     }
                 }
             }
         }
 
-        neighbors
+        conflicts
     }
 
-    /// Local search optimization - first improvement
-    fn solve(&self, schedule: &mut Schedule) -> (usize, usize) {
-        let mut iterations = 0;
-        let mut current_obj = self.objective(schedule);
-
-        loop {
-            let neighbors = self.neighbors(schedule);
-            let mut improved = false;
+    fn staffing_conflict(&self, shifts: Vec<usize>, skills: &[String], available: usize) -> Conflict {
+        let label = skills_label(skills);
+        let demand: usize = shifts.iter().map(|&s| self.data.shifts[s].required_staff).sum();
+        let excess = demand - available;
 
-            for neighbor in neighbors {
-                let obj = self.objective(&neighbor);
-                if obj < current_obj {
-                    *schedule = neighbor;
-                    current_obj = obj;
-                    improved = true;
-                    break;
-                }
-            }
+        // Do they truly overlap, or only fall inside each other's rest period?
+        let latest_start = shifts.iter().map(|&s| self.times[s].start).max();
+        let earliest_end = shifts.iter().map(|&s| self.times[s].end).min();
+        let overlapping = latest_start < earliest_end;
+        let group = if overlapping {
+            format!("{} overlapping shifts", shifts.len())
+        } else {
+            format!("{} shifts less than {}h apart", shifts.len(), self.data.rules.min_rest_hours)
+        };
 
-            iterations += 1;
-            if !improved || iterations > 1000 {
-                break;
+        let mut suggestions = vec![format!("hire {excess} more {label} nurse(s)")];
+        let untrained = self.data.nurses.iter()
+            .filter(|n| !skills.iter().all(|s| n.skills.contains(s)))
+            .count();
+        if untrained > 0 && !skills.is_empty() {
+            suggestions.push(format!("cross-train {} of the {untrained} other nurses in {label}", excess.min(untrained)));
+        }
+        let busiest = shifts.iter().copied().max_by_key(|&s| self.data.shifts[s].required_staff).unwrap_or(shifts[0]);
+        suggestions.push(format!("lower required_staff on {} by {excess}", self.shift_label(busiest)));
+        if !overlapping {
+            // The widest gap between consecutive shifts of the group is the rest that lets one nurse take both
+            let gap = shifts.windows(2)
+                .map(|w| self.times[w[1]].start.0 - self.times[w[0]].end.0)
+                .max()
+                .unwrap_or(0);
+            if gap >= 0 {
+                suggestions.push(format!("lower min_rest_hours to {}h", gap / 60));
             }
         }
 
-        (current_obj, iterations)
+        let summary = if available == 0 {
+            format!("no available nurse has {label}, but {} need(s) {demand}", if shifts.len() == 1 { "a shift".to_string() } else { group })
+        } else {
+            format!("only {available} {label} nurse(s) are available but {group} need {demand}")
+        };
+        Conflict { summary, shifts, suggestions }
+    }
+}
+
+/// Fewest entries of `group` whose demand exceeds `supply` (largest first), in group order
+fn overflowing(group: &[usize], supply: i64, demand: impl Fn(usize) -> i64) -> Option<Vec<usize>> {
+    let mut by_demand = group.to_vec();
+    by_demand.sort_by_key(|&s| std::cmp::Reverse(demand(s)));
+    let mut total = 0;
+    for (taken, &s) in by_demand.iter().enumerate() {
+        total += demand(s);
+        if total > supply {
+            let chosen = &by_demand[..=taken];
+            return Some(group.iter().copied().filter(|s| chosen.contains(s)).collect());
+        }
     }
+    None
 }
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let json_path = if args.len() > 1 { &args[1] } else { "hospital_data.json" };
-    let quiet = args.iter().any(|a| a == "-q" || a == "--quiet");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut json_path = "hospital_data.json";
+    let mut quiet = false;
+    let mut config = SearchConfig::default();
+    let mut exact_config: Option<ExactConfig> = None;
+    let mut published_path: Option<&str> = None;
+    let mut events_path: Option<&str> = None;
+    let mut output_path: Option<&str> = None;
+    let mut disruption_weight = 5;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-q" | "--quiet" => quiet = true,
+            "--method" => match iter.next().and_then(|m| Method::parse(m)) {
+                Some(method) => config.method = method,
+                None => {
+                    eprintln!("--method expects hill, anneal, tabu or multistart");
+                    return;
+                }
+            },
+            "--seed" => match iter.next().and_then(|v| v.parse().ok()) {
+                Some(seed) => config.seed = seed,
+                None => {
+                    eprintln!("--seed expects an unsigned integer");
+                    return;
+                }
+            },
+            "--budget" => match iter.next().and_then(|v| v.parse().ok()) {
+                Some(budget) => config.budget = Some(budget),
+                None => {
+                    eprintln!("--budget expects an unsigned integer");
+                    return;
+                }
+            },
+            "--exact" => {
+                exact_config.get_or_insert_with(ExactConfig::default);
+            }
+            "--feasibility" => exact_config.get_or_insert_with(ExactConfig::default).hard_only = true,
+            "--nodes" => match iter.next().and_then(|v| v.parse().ok()) {
+                Some(nodes) => exact_config.get_or_insert_with(ExactConfig::default).node_limit = nodes,
+                None => {
+                    eprintln!("--nodes expects an unsigned integer");
+                    return;
+                }
+            },
+            "--repair" | "--events" | "--output" => match iter.next() {
+                Some(path) if arg == "--repair" => published_path = Some(path),
+                Some(path) if arg == "--events" => events_path = Some(path),
+                Some(path) => output_path = Some(path),
+                None => {
+                    eprintln!("{arg} expects a file path");
+                    return;
+                }
+            },
+            "--disruption" => match iter.next().and_then(|v| v.parse().ok()) {
+                Some(weight) => disruption_weight = weight,
+                None => {
+                    eprintln!("--disruption expects an unsigned integer");
+                    return;
+                }
+            },
+            path => json_path = path,
+        }
+    }
+    if published_path.is_some() && exact_config.is_some() {
+        eprintln!("--exact cannot bound a repair: kept published assignments have negative costs, which its bound does not count");
+        return;
+    }
 
     if !quiet {
         println!("{}", "=".repeat(70));
@@ -329,7 +2019,7 @@ fn main() {
         }
     };
 
-    let data: HospitalData = match serde_json::from_str(&json_data) {
+    let mut data: HospitalData = match serde_json::from_str(&json_data) {
         Ok(d) => d,
         Err(e) => {
             eprintln!("Error parsing JSON: {e}");
@@ -337,25 +2027,100 @@ fn main() {
         }
     };
 
+    let events: Vec<ChangeEvent> = match events_path.map(|path| (path, fs::read_to_string(path))) {
+        None => Vec::new(),
+        Some((path, Err(e))) => {
+            eprintln!("Error reading {path}: {e}");
+            return;
+        }
+        Some((path, Ok(text))) => match serde_json::from_str(&text) {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("Error parsing events in {path}: {e}");
+                return;
+            }
+        },
+    };
+    for event in &events {
+        match data.apply(event) {
+            Ok(unpinned) => {
+                for (nurse, shift) in unpinned {
+                    eprintln!("Unpinned nurse {nurse} from shift {shift}: the change events make them unavailable for it");
+                }
+            }
+            Err(e) => {
+                eprintln!("Invalid change event {event:?}: {e}");
+                return;
+            }
+        }
+    }
+
+    let published: Option<Schedule> = match published_path.map(|path| (path, fs::read_to_string(path))) {
+        None => None,
+        Some((path, Err(e))) => {
+            eprintln!("Error reading {path}: {e}");
+            return;
+        }
+        Some((path, Ok(text))) => match serde_json::from_str(&text) {
+            Ok(schedule) => Some(schedule),
+            Err(e) => {
+                eprintln!("Error parsing published schedule in {path}: {e}");
+                return;
+            }
+        },
+    };
+
+    if let Err(e) = data.validate() {
+        eprintln!("Invalid hospital data: {e}");
+        return;
+    }
+    let days = data.calendar_days();
+
     if !quiet {
         println!("Hospital: {}", data.name);
         println!("Nurses: {}", data.nurses.len());
         println!("Shifts: {}", data.shifts.len());
-        println!("Days: {}", data.days);
+        println!("Days: {}", days.len());
+        if let Some(path) = published_path {
+            println!("Repairing: {path} after {} change events", events.len());
+        }
         println!();
     }
 
-    let solver = ScheduleSolver::new(data.clone());
+    let mut solver = ScheduleSolver::new(data.clone());
+    if let Some(published) = &published {
+        solver = solver.with_original(published, disruption_weight);
+    }
 
-    // Timing - start from empty, saturation fills it
+    // Timing - start from empty (or the published roster), saturation fills it
     let start = std::time::Instant::now();
-    let mut schedule = solver.initial();
+    let mut schedule = match &published {
+        Some(published) => solver.repair_start(published),
+        None => solver.initial(),
+    };
     let init_obj = solver.objective(&schedule);
-    let (final_obj, iterations) = solver.solve(&mut schedule);
+    let (mut final_obj, iterations) = solver.search(&mut schedule, &config);
     let solve_time = start.elapsed();
 
+    // Exact mode: bound the local-search roster, keep a better one if found
+    let exact_start = std::time::Instant::now();
+    let exact = exact_config.map(|c| solver.exact(&schedule, &c));
+    let exact_time = exact_start.elapsed();
+    if let Some(report) = exact.as_ref().filter(|r| !r.hard_only && r.objective < final_obj) {
+        schedule = report.schedule.clone();
+        final_obj = report.objective;
+    }
+
     let hard_violations = solver.hard_violations(&schedule);
     let soft_cost = solver.soft_cost(&schedule);
+    let disruption = solver.disruption(&schedule);
+
+    if let Some(path) = output_path {
+        let json = serde_json::to_string_pretty(&schedule).expect("schedule serializes");
+        if let Err(e) = fs::write(path, json) {
+            eprintln!("Error writing {path}: {e}");
+        }
+    }
 
     if quiet {
         println!("nurses={:3} shifts={:3} | solve: {:>8.2}ms | iters: {:4} | violations: {} | prefs: {}",
@@ -365,12 +2130,26 @@ fn main() {
                  iterations,
                  hard_violations,
                  soft_cost);
+        if published.is_some() {
+            println!("repair: {} changes ({} added, {} dropped)",
+                     disruption.changes(), disruption.added.len(), disruption.dropped.len());
+        }
+        if let Some(report) = &exact {
+            println!("exact: {:>8.2}ms | nodes: {} | bound: {} | gap: {:.1}%{}",
+                     exact_time.as_secs_f64() * 1000.0,
+                     report.nodes,
+                     report.lower_bound,
+                     100.0 * report.gap(),
+                     if report.complete { " | optimal" } else { "" });
+        }
         return;
     }
 
-    println!("SOLVING (saturation from empty)...");
+    let origin = if published.is_some() { "published roster" } else { "empty" };
+    println!("SOLVING (saturation from {origin})...");
     println!("{}", "-".repeat(70));
-    println!("Initial: empty (objective: {init_obj})");
+    println!("Method: {:?} (seed {})", config.method, config.seed);
+    println!("Initial: {origin} (objective: {init_obj})");
     println!("Saturation: {:.2}ms ({} iterations)", solve_time.as_secs_f64() * 1000.0, iterations);
     println!("Final objective: {} (improved {:.1}%)", final_obj,
              100.0 * (init_obj - final_obj) as f64 / init_obj.max(1) as f64);
@@ -378,36 +2157,63 @@ fn main() {
     println!("Hard constraint violations: {hard_violations}");
     println!("Soft constraint cost: {soft_cost}");
 
+    if published.is_some() {
+        println!();
+        println!("REPAIR ({} per change, included in the soft cost)", disruption_weight);
+        println!("{}", "-".repeat(70));
+        println!("Changes: {} ({} added, {} dropped)",
+                 disruption.changes(), disruption.added.len(), disruption.dropped.len());
+        for (sign, changes) in [("+", &disruption.added), ("-", &disruption.dropped)] {
+            for &(s, n) in changes {
+                println!("  {sign} {} on {}", data.nurses[n].name, solver.shift_label(s));
+            }
+        }
+    }
+
+    if let Some(report) = &exact {
+        println!();
+        println!("EXACT (branch-and-bound, {:.2}ms)", exact_time.as_secs_f64() * 1000.0);
+        println!("{}", "-".repeat(70));
+        print!("{report}");
+    }
+
     // Show schedule
     println!();
     println!("FINAL SCHEDULE:");
     println!("{}", "=".repeat(70));
 
-    for day in 0..data.days {
-        println!("\nDay {}:", day + 1);
+    // Explicit timestamps print their date; legacy day indices don't have one
+    let dated = data.shifts.iter().any(|s| s.start.is_some());
+
+    for (n, &day) in days.iter().enumerate() {
+        if dated {
+            println!("\nDay {} ({}):", n + 1, &Timestamp(day * MINUTES_PER_DAY).to_string()[..10]);
+        } else {
+            println!("\nDay {}:", n + 1);
+        }
         println!("{}", "-".repeat(40));
-        
-        for shift_type in &["day", "evening", "night"] {
-            let shifts: Vec<&Shift> = data.shifts.iter()
-                .filter(|s| s.day == day && &s.shift_type == shift_type)
-                .collect();
-            
-            for shift in shifts {
-                let assigned: Vec<&str> = schedule.assignments
-                    .get(&shift.id)
-                    .map(|nurses| {
-                        nurses.iter()
-                            .filter_map(|&nid| data.nurses.iter().find(|n| n.id == nid))
-                            .map(|n| n.name.as_str())
-                            .collect()
-                    })
-                    .unwrap_or_default();
-                
-                let status = if assigned.len() >= shift.required_staff { "✓" } else { "✗" };
-                println!("  {} {}: {} [{}/{}]", 
-                         status, shift.name, assigned.join(", "),
-                         assigned.len(), shift.required_staff);
-            }
+
+        let mut shifts: Vec<&Shift> = data.shifts.iter()
+            .filter(|s| s.time().start.day() == day)
+            .collect();
+        shifts.sort_by_key(|s| (s.time().start, s.id));
+
+        for shift in shifts {
+            let assigned: Vec<&str> = schedule.assignments
+                .get(&shift.id)
+                .map(|nurses| {
+                    nurses.iter()
+                        .filter_map(|&nid| data.nurses.iter().find(|n| n.id == nid))
+                        .map(|n| n.name.as_str())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let time = shift.time();
+            let status = if assigned.len() >= shift.required_staff { "✓" } else { "✗" };
+            println!("  {} {} {}-{}: {} [{}/{}]",
+                     status, shift.name, time.start.clock(), time.end.clock(), assigned.join(", "),
+                     assigned.len(), shift.required_staff);
         }
     }
 
@@ -428,7 +2234,39 @@ fn main() {
     if hard_violations == 0 {
         println!("FEASIBLE SCHEDULE FOUND ✓");
     } else {
-        println!("Schedule has {hard_violations} constraint violations");
+        let violations = solver.violations(&schedule);
+        let mut by_rule: Vec<(Rule, usize)> = Vec::new();
+        for violation in &violations {
+            match by_rule.iter_mut().find(|(rule, _)| *rule == violation.rule) {
+                Some((_, count)) => *count += violation.count,
+                None => by_rule.push((violation.rule, violation.count)),
+            }
+        }
+        let by_rule: Vec<String> = by_rule.iter().map(|(rule, count)| format!("{count} {rule}")).collect();
+        println!("Schedule has {hard_violations} constraint violations ({})", by_rule.join(", "));
+        println!();
+        println!("VIOLATIONS:");
+        for violation in &violations {
+            let nurse = violation.nurse.map(|n| format!(" {}", data.nurses[n].name)).unwrap_or_default();
+            println!("  [{}] {}{}: {}", violation.rule, solver.shift_label(violation.shift), nurse, violation.detail);
+            println!("      → {}", violation.suggestion);
+        }
+
+        let conflicts = solver.conflicts();
+        println!();
+        println!("CONFLICTS (every roster breaks these):");
+        if conflicts.is_empty() {
+            println!("  none among overlapping shifts or weekly hours; the violations may be avoidable");
+            println!("      → try --method anneal, or --feasibility to prove infeasibility");
+        }
+        for conflict in &conflicts {
+            let shifts: Vec<String> = conflict.shifts.iter().map(|&s| solver.shift_label(s)).collect();
+            println!("  {}", conflict.summary);
+            println!("      shifts: {}", shifts.join(", "));
+            for suggestion in &conflict.suggestions {
+                println!("      → {suggestion}");
+            }
+        }
     }
     println!("{}", "=".repeat(70));
 }
@@ -440,15 +2278,45 @@ mod tests {
     fn sample_data() -> HospitalData {
         HospitalData {
             name: "Test Hospital".to_string(),
-            days: 1,
             nurses: vec![
-                Nurse { id: 0, name: "Alice".into(), skills: vec!["ICU".into()], max_hours_week: 40, preferred_shifts: vec!["day".into()] },
-                Nurse { id: 1, name: "Bob".into(), skills: vec!["ICU".into(), "ER".into()], max_hours_week: 40, preferred_shifts: vec!["evening".into()] },
+                Nurse { id: 0, name: "Alice".into(), skills: vec!["ICU".into()], max_hours_week: 40, preferred_shifts: vec!["day".into()],
+                        unavailable: vec![], excluded_shifts: vec![], time_off_requests: vec![] },
+                Nurse { id: 1, name: "Bob".into(), skills: vec!["ICU".into(), "ER".into()], max_hours_week: 40, preferred_shifts: vec!["evening".into()],
+                        unavailable: vec![], excluded_shifts: vec![], time_off_requests: vec![] },
             ],
             shifts: vec![
-                Shift { id: 0, name: "ICU Day".into(), shift_type: "day".into(), required_skills: vec!["ICU".into()], required_staff: 1, hours: 8, day: 0 },
+                Shift { id: 0, name: "ICU Day".into(), shift_type: "day".into(), required_skills: vec!["ICU".into()], required_staff: 1, start: None, end: None, hours: 8, day: 0, pinned: vec![] },
             ],
+            rules: WorkRules::default(),
+        }
+    }
+
+    fn timed_shift(id: usize, start: &str, end: &str) -> Shift {
+        Shift {
+            id,
+            name: format!("Shift {id}"),
+            shift_type: "day".into(),
+            required_skills: vec![],
+            required_staff: 1,
+            start: Some(Timestamp::parse(start).unwrap()),
+            end: Some(Timestamp::parse(end).unwrap()),
+            hours: 8,
+            day: 0,
+            pinned: vec![],
+        }
+    }
+
+    /// Solver over `shifts` with every shift assigned to Alice
+    fn all_to_alice(shifts: Vec<Shift>, rules: WorkRules) -> (ScheduleSolver, Schedule) {
+        let mut data = sample_data();
+        data.shifts = shifts;
+        data.rules = rules;
+        let solver = ScheduleSolver::new(data);
+        let mut schedule = solver.initial();
+        for shift in &solver.data.shifts {
+            schedule.assign(shift.id, 0);
         }
+        (solver, schedule)
     }
 
     #[test]
@@ -479,4 +2347,457 @@ mod tests {
         let final_obj = solver.objective(&schedule);
         assert!(final_obj <= initial);
     }
+
+    #[test]
+    fn test_timestamp_parsing() {
+        let t = Timestamp::parse("2024-02-29T07:30").unwrap();
+        assert_eq!(t.to_string(), "2024-02-29 07:30");
+        assert_eq!(Timestamp::parse("2024-03-01 07:30:59").unwrap().day(), t.day() + 1);
+        assert_eq!(Timestamp::parse("1970-01-02T00:00").unwrap(), Timestamp(MINUTES_PER_DAY));
+        assert!(Timestamp::parse("2023-02-29T07:00").is_err());
+        assert!(Timestamp::parse("2024-03-01T24:00").is_err());
+        assert!(Timestamp::parse("2024-03-01").is_err());
+    }
+
+    #[test]
+    fn test_rest_from_timestamps() {
+        let night_then_day = || vec![
+            timed_shift(0, "2024-03-04T23:00", "2024-03-05T07:00"),
+            timed_shift(1, "2024-03-05T07:00", "2024-03-05T15:00"),
+        ];
+        let (solver, schedule) = all_to_alice(night_then_day(), WorkRules::default());
+        assert_eq!(solver.hard_violations(&schedule), 1);
+
+        // 8 hours off satisfies the default, not an 11-hour rule
+        let mut shifts = night_then_day();
+        shifts[1] = timed_shift(1, "2024-03-05T15:00", "2024-03-05T23:00");
+        let (solver, schedule) = all_to_alice(shifts.clone(), WorkRules::default());
+        assert_eq!(solver.hard_violations(&schedule), 0);
+        let (solver, schedule) = all_to_alice(shifts, WorkRules { min_rest_hours: 11, max_consecutive_days: None });
+        assert_eq!(solver.hard_violations(&schedule), 1);
+    }
+
+    #[test]
+    fn test_rolling_week_hours() {
+        // Five 8h shifts in each of two weeks: 40h in every 7-day window
+        let shifts: Vec<Shift> = [4, 5, 6, 7, 8, 11, 12, 13, 14, 15].iter().enumerate()
+            .map(|(i, d)| timed_shift(i, &format!("2024-03-{d:02}T07:00"), &format!("2024-03-{d:02}T15:00")))
+            .collect();
+        let (solver, schedule) = all_to_alice(shifts.clone(), WorkRules::default());
+        assert_eq!(solver.hard_violations(&schedule), 0);
+
+        // Seven days in a row: the windows ending 03-09 (48h) and 03-10 (56h) are over
+        let shifts: Vec<Shift> = [4, 5, 6, 7, 8, 9, 10, 13, 14, 15].iter().enumerate()
+            .map(|(i, d)| timed_shift(i, &format!("2024-03-{d:02}T07:00"), &format!("2024-03-{d:02}T15:00")))
+            .collect();
+        let (solver, schedule) = all_to_alice(shifts, WorkRules::default());
+        assert_eq!(solver.hard_violations(&schedule), 2);
+    }
+
+    #[test]
+    fn test_max_consecutive_days() {
+        let shifts: Vec<Shift> = (1..=7)
+            .map(|d| timed_shift(d - 1, &format!("2024-03-{d:02}T07:00"), &format!("2024-03-{d:02}T12:00")))
+            .collect();
+        let rules = WorkRules { min_rest_hours: 8, max_consecutive_days: Some(5) };
+        let (solver, schedule) = all_to_alice(shifts.clone(), rules.clone());
+        assert_eq!(solver.hard_violations(&schedule), 2);
+
+        // A day off in the middle resets the run
+        let mut schedule = schedule;
+        schedule.unassign(3, 0);
+        assert_eq!(solver.hard_violations(&schedule), 1); // lost coverage only
+    }
+
+    /// ICU and ER wards, three 8-hour shifts a day from 2024-03-01; even ids ICU, odd ids ER
+    fn month_roster(nurses: usize, days: usize) -> HospitalData {
+        staffed_roster(nurses, days, 3, 4)
+    }
+
+    /// month_roster with `icu` and `er` nurses per shift
+    fn staffed_roster(nurses: usize, days: usize, icu: usize, er: usize) -> HospitalData {
+        let first_day = days_from_civil(2024, 3, 1);
+        let mut shifts = Vec::new();
+        for day in 0..days {
+            for (ward, required_staff) in [("ICU", icu), ("ER", er)] {
+                for (shift_type, hour) in [("day", 7), ("evening", 15), ("night", 23)] {
+                    let start = Timestamp((first_day + day as i64) * MINUTES_PER_DAY + hour * 60);
+                    shifts.push(Shift {
+                        id: shifts.len(),
+                        name: format!("{ward} {shift_type}"),
+                        shift_type: shift_type.into(),
+                        required_skills: vec![ward.into()],
+                        required_staff,
+                        start: Some(start),
+                        end: Some(Timestamp(start.0 + 8 * 60)),
+                        hours: 8,
+                        day,
+                        pinned: vec![],
+                    });
+                }
+            }
+        }
+        let nurses = (0..nurses)
+            .map(|id| Nurse {
+                id,
+                name: format!("Nurse {id}"),
+                skills: vec![if id % 2 == 0 { "ICU" } else { "ER" }.into()],
+                max_hours_week: 40,
+                preferred_shifts: vec![["day", "evening", "night"][id % 3].into()],
+                unavailable: vec![],
+                excluded_shifts: vec![],
+                time_off_requests: vec![],
+            })
+            .collect();
+        HospitalData {
+            name: "Month".into(),
+            nurses,
+            shifts,
+            rules: WorkRules { min_rest_hours: 11, max_consecutive_days: Some(5) },
+        }
+    }
+
+    /// month_roster(8, 2): nurse 0 on leave and nurse 4 asking for time off on
+    /// day two, nurse 2 excluded from shift 0, nurse 0 pinned to shift 0 and
+    /// nurse 1 to shift 3
+    fn constrained_roster() -> HospitalData {
+        let mut data = month_roster(8, 2);
+        let day_two = (Timestamp::parse("2024-03-02T00:00").unwrap(), Timestamp::parse("2024-03-03T00:00").unwrap());
+        data.nurses[0].unavailable.push(Absence { start: day_two.0, end: day_two.1, reason: "vacation".into() });
+        data.nurses[4].time_off_requests.push(TimeOffRequest { start: day_two.0, end: day_two.1, priority: 5 });
+        data.nurses[2].excluded_shifts.push(0);
+        data.shifts[0].pinned.push(0);
+        data.shifts[3].pinned.push(1);
+        data
+    }
+
+    #[test]
+    fn test_delta_matches_full_evaluation() {
+        for data in [month_roster(8, 4), constrained_roster()] {
+            let solver = ScheduleSolver::new(data);
+            let mut schedule = solver.initial();
+            let mut state = solver.state(&schedule);
+            let mut seed: u64 = 7;
+
+            for _ in 0..300 {
+                let moves: Vec<Move> = solver.neighbors(&state).collect();
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+                if moves.is_empty() {
+                    break; // every place filled, nobody spare
+                }
+                let mv = moves[(seed >> 33) as usize % moves.len()];
+
+                let (hard, soft) = solver.delta(&state, mv);
+                let expected = (state.hard as isize + hard, state.soft as isize + soft);
+                solver.apply(&mut state, mv);
+                solver.apply_to_schedule(&mut schedule, mv);
+
+                assert_eq!((state.hard as isize, state.soft as isize), expected, "{:?}", mv);
+                assert_eq!(state.hard, solver.hard_violations(&schedule), "{:?}", mv);
+                assert_eq!(state.soft, solver.soft_cost(&schedule), "{:?}", mv);
+            }
+        }
+    }
+
+    #[test]
+    fn test_month_roster() {
+        // 294 places over two weeks; 40 nurses cover them within 40h per 7 days
+        let solver = ScheduleSolver::new(month_roster(40, 14));
+        let mut schedule = solver.initial();
+        let (objective, _) = solver.solve(&mut schedule);
+        assert_eq!(objective, solver.objective(&schedule));
+        assert_eq!(solver.hard_violations(&schedule), 0);
+    }
+
+    #[test]
+    fn test_large_roster_converges() {
+        // 2520 places: 200 nurses over 30 days, 12 ICU and 16 ER nurses per shift.
+        // The descent must settle on its own, well inside the 10-moves-per-place
+        // cap, rather than be cut off by it. Timing is left to release runs
+        let solver = ScheduleSolver::new(staffed_roster(200, 30, 12, 16));
+        let mut schedule = solver.initial();
+        let (objective, iterations) = solver.solve(&mut schedule);
+        assert!(iterations < 2 * solver.places(), "{iterations} moves");
+        assert_eq!(objective, solver.objective(&schedule));
+        assert_eq!(solver.hard_violations(&schedule), 0);
+    }
+
+    #[test]
+    fn test_search_is_seeded() {
+        let solver = ScheduleSolver::new(month_roster(10, 3));
+        for method in [Method::SimulatedAnnealing, Method::Tabu, Method::MultiStart] {
+            let run = |seed| {
+                let config = SearchConfig { method, seed, budget: Some(200) };
+                let mut schedule = solver.initial();
+                let (objective, _) = solver.search(&mut schedule, &config);
+                assert_eq!(objective, solver.objective(&schedule));
+                (objective, solver.state(&schedule).staff)
+            };
+            assert_eq!(run(42), run(42), "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_metaheuristics_escape_local_optimum() {
+        // Tight week: first-improvement descent stalls with a violation left
+        let solver = ScheduleSolver::new(month_roster(34, 7));
+        let mut schedule = solver.initial();
+        solver.search(&mut schedule, &SearchConfig::default());
+        assert!(solver.hard_violations(&schedule) > 0);
+
+        for method in [Method::SimulatedAnnealing, Method::Tabu] {
+            let mut schedule = solver.initial();
+            solver.search(&mut schedule, &SearchConfig { method, seed: 1, budget: None });
+            assert_eq!(solver.hard_violations(&schedule), 0, "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_exact_proves_optimum() {
+        let solver = ScheduleSolver::new(month_roster(4, 1));
+        let mut schedule = solver.initial();
+        let (local, _) = solver.solve(&mut schedule);
+
+        let report = solver.exact(&schedule, &ExactConfig::default());
+        assert!(report.complete, "{report}");
+        assert_eq!(report.start_objective, local);
+        assert_eq!(report.lower_bound, report.objective);
+        assert!(report.objective <= local);
+        assert_eq!(solver.objective(&report.schedule), report.objective);
+    }
+
+    #[test]
+    fn test_exact_proves_infeasibility() {
+        // One ER nurse, two overlapping ER shifts
+        let shifts = vec![
+            timed_shift(0, "2024-03-04T07:00", "2024-03-04T19:00"),
+            timed_shift(1, "2024-03-04T15:00", "2024-03-04T23:00"),
+        ];
+        let mut data = sample_data();
+        data.shifts = shifts.into_iter()
+            .map(|s| Shift { required_skills: vec!["ER".into()], ..s })
+            .collect();
+        let solver = ScheduleSolver::new(data);
+        let mut schedule = solver.initial();
+        solver.solve(&mut schedule);
+
+        let report = solver.exact(&schedule, &ExactConfig { hard_only: true, ..ExactConfig::default() });
+        assert!(report.complete);
+        assert_eq!(report.lower_bound, 1);
+        assert!(report.infeasible());
+    }
+
+    #[test]
+    fn test_exact_bound_at_node_limit() {
+        let solver = ScheduleSolver::new(month_roster(34, 7));
+        let mut schedule = solver.initial();
+        solver.solve(&mut schedule);
+
+        let report = solver.exact(&schedule, &ExactConfig { node_limit: 2000, hard_only: false });
+        assert!(!report.complete);
+        assert_eq!(report.nodes, 2000);
+        assert!(report.lower_bound <= report.objective && report.objective <= report.start_objective);
+        assert!(report.gap() > 0.0 && report.gap() <= 1.0);
+    }
+
+    #[test]
+    fn test_violation_report() {
+        let night = Shift { shift_type: "night".into(), ..timed_shift(0, "2024-03-04T23:00", "2024-03-05T07:00") };
+        let day = Shift { required_staff: 2, ..timed_shift(1, "2024-03-05T09:00", "2024-03-05T17:00") };
+        let er = Shift { required_skills: vec!["ER".into()], ..timed_shift(2, "2024-03-07T07:00", "2024-03-07T15:00") };
+        let (solver, schedule) = all_to_alice(vec![night, day, er], WorkRules::default());
+
+        let violations = solver.violations(&schedule);
+        assert_eq!(violations.iter().map(|v| v.count).sum::<usize>(), solver.hard_violations(&schedule));
+        let rules: Vec<(Rule, usize)> = violations.iter().map(|v| (v.rule, v.shift)).collect();
+        assert_eq!(rules, vec![(Rule::Coverage, 1), (Rule::Skill, 2), (Rule::NightToDay, 1)]);
+        assert_eq!(violations[2].nurse, Some(0));
+        assert!(violations[2].detail.starts_with("2h after Shift 0"), "{}", violations[2].detail);
+    }
+
+    #[test]
+    fn test_conflicts_find_minimal_set() {
+        // Two ICU nurses; three ICU nights overlap, the day shift is free of them
+        let mut data = sample_data();
+        data.shifts = vec![
+            timed_shift(0, "2024-03-04T22:00", "2024-03-05T06:00"),
+            timed_shift(1, "2024-03-04T23:00", "2024-03-05T07:00"),
+            timed_shift(2, "2024-03-05T00:00", "2024-03-05T08:00"),
+            timed_shift(3, "2024-03-06T07:00", "2024-03-06T15:00"),
+        ];
+        for shift in &mut data.shifts {
+            shift.required_skills = vec!["ICU".into()];
+        }
+        let solver = ScheduleSolver::new(data);
+
+        let conflicts = solver.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].shifts, vec![0, 1, 2]);
+        assert_eq!(conflicts[0].summary, "only 2 ICU nurse(s) are available but 3 overlapping shifts need 3");
+        assert!(conflicts[0].suggestions.iter().any(|s| s == "hire 1 more ICU nurse(s)"));
+
+        // Weekly hours: 10 ICU nurses give 400h against 504h of ICU shifts
+        assert!(ScheduleSolver::new(month_roster(20, 7)).conflicts().iter()
+            .any(|c| c.summary.starts_with("10 available ICU nurse(s) can work 400h in a week")));
+        assert!(ScheduleSolver::new(month_roster(40, 7)).conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_unavailability_and_pins_are_hard() {
+        let data = constrained_roster();
+        assert!(data.validate().is_ok());
+        let solver = ScheduleSolver::new(data);
+        let places = solver.places();
+
+        // Pins start every roster; a missing pin is a violation
+        let schedule = solver.initial();
+        assert_eq!(schedule.assignments[&0], vec![0]);
+        assert_eq!(schedule.assignments[&3], vec![1]);
+        assert_eq!(solver.hard_violations(&schedule), places - 2);
+        let empty = Schedule::new(solver.data.shifts.len());
+        assert_eq!(solver.hard_violations(&empty), places + 2);
+        assert_eq!(solver.state(&empty).hard, places + 2);
+
+        // Leave and exclusions
+        let mut schedule = solver.initial();
+        schedule.assign(6, 0);
+        schedule.assign(0, 2);
+        assert_eq!(solver.hard_violations(&schedule), places - 4 + 2);
+        let details: Vec<String> = solver.violations(&schedule).into_iter()
+            .filter(|v| v.rule == Rule::Unavailable)
+            .map(|v| v.detail)
+            .collect();
+        assert_eq!(details, vec!["excluded from this shift", "absent (vacation) 2024-03-02 00:00 to 2024-03-03 00:00"]);
+
+        // No move undoes a pin or lands on a shift the nurse must not work
+        let state = solver.state(&solver.initial());
+        for mv in solver.neighbors(&state) {
+            for change in mv.nurse_changes().into_iter().flatten() {
+                if let Some(removed) = change.removed {
+                    assert!(!solver.is_pinned(change.nurse, removed), "{mv:?}");
+                }
+                if let Some(added) = change.added {
+                    assert!(solver.is_eligible(change.nurse, added), "{mv:?}");
+                }
+            }
+        }
+        for method in [Method::HillClimbing, Method::SimulatedAnnealing, Method::Tabu, Method::MultiStart] {
+            let mut schedule = solver.initial();
+            solver.search(&mut schedule, &SearchConfig { method, seed: 3, budget: Some(300) });
+            assert!(schedule.assignments[&0].contains(&0) && schedule.assignments[&3].contains(&1), "{method:?}");
+            assert!(solver.violations(&schedule).iter().all(|v| !matches!(v.rule, Rule::Unavailable | Rule::Pinned)),
+                    "{method:?}");
+        }
+
+        let mut data = constrained_roster();
+        data.shifts[6].pinned.push(0);
+        assert!(data.validate().unwrap_err().contains("pins Nurse 0"));
+    }
+
+    #[test]
+    fn test_time_off_priority() {
+        let solver = ScheduleSolver::new(constrained_roster());
+        let mut schedule = solver.initial();
+        assert_eq!(solver.soft_cost(&schedule), 1); // Nurse 1 pinned to a day shift, prefers evenings
+
+        // Nurse 4 prefers evenings and asked for day two off
+        schedule.assign(7, 4);
+        assert_eq!(solver.soft_cost(&schedule), 1 + 5);
+        schedule.unassign(7, 4);
+        schedule.assign(1, 4);
+        assert_eq!(solver.soft_cost(&schedule), 1);
+    }
+
+    #[test]
+    fn test_repair_minimal_disruption() {
+        let mut data = month_roster(24, 3);
+        let solver = ScheduleSolver::new(data.clone());
+        let mut published = solver.initial();
+        solver.solve(&mut published);
+        assert_eq!(solver.hard_violations(&published), 0);
+
+        // An ICU nurse of day two's day shift calls in sick; day three's ICU day shift needs a fourth nurse
+        let sick = published.assignments[&6][0];
+        let events: Vec<ChangeEvent> = serde_json::from_str(&format!(r#"[
+            {{"type": "nurse_unavailable", "nurse": {sick}, "start": "2024-03-02T00:00", "end": "2024-03-03T00:00", "reason": "sick"}},
+            {{"type": "staffing_changed", "shift": 12, "required_staff": 4}}
+        ]"#)).unwrap();
+        for event in &events {
+            data.apply(event).unwrap();
+        }
+
+        let repair = ScheduleSolver::new(data.clone()).with_original(&published, 5);
+        let lost = (0..repair.data.shifts.len())
+            .filter(|&s| published.assignments[&s].contains(&sick) && !repair.is_available(sick, s))
+            .count();
+        assert!(lost > 0);
+
+        let mut schedule = repair.repair_start(&published);
+        let (objective, _) = repair.solve(&mut schedule);
+        assert_eq!(objective, repair.objective(&schedule));
+        assert_eq!(repair.hard_violations(&schedule), 0);
+        let disruption = repair.disruption(&schedule);
+        assert_eq!(disruption.dropped.len(), lost);
+        assert_eq!(disruption.added.len(), lost + 1);
+
+        // Re-solving from scratch (here with annealing) reshuffles the roster
+        let fresh_solver = ScheduleSolver::new(data);
+        let mut fresh = fresh_solver.initial();
+        fresh_solver.search(&mut fresh, &SearchConfig { method: Method::SimulatedAnnealing, seed: 1, budget: None });
+        assert!(repair.disruption(&fresh).changes() > 10 * disruption.changes());
+    }
+
+    #[test]
+    fn test_absence_unpins_nurse() {
+        let mut data = sample_data();
+        data.shifts = vec![
+            timed_shift(0, "2024-03-04T07:00", "2024-03-04T15:00"),
+            timed_shift(1, "2024-03-06T07:00", "2024-03-06T15:00"),
+        ];
+        for shift in &mut data.shifts {
+            shift.pinned.push(0);
+        }
+        let sick: ChangeEvent = serde_json::from_str(
+            r#"{"type": "nurse_unavailable", "nurse": 0, "start": "2024-03-04T00:00", "end": "2024-03-05T00:00"}"#).unwrap();
+
+        assert_eq!(data.apply(&sick), Ok(vec![(0, 0)]));
+        assert!(data.shifts[0].pinned.is_empty());
+        assert_eq!(data.shifts[1].pinned, vec![0]);
+        assert!(data.validate().is_ok());
+    }
+
+    #[test]
+    fn test_change_events_and_published_json() {
+        let mut data = sample_data();
+        let events: Vec<ChangeEvent> = serde_json::from_str(r#"[
+            {"type": "shift_added", "shift": {"id": 1, "name": "ER Surge", "shift_type": "evening",
+             "required_skills": ["ER"], "required_staff": 1,
+             "start": "2024-03-04T15:00", "end": "2024-03-04T23:00"}},
+            {"type": "staffing_changed", "shift": 0, "required_staff": 2},
+            {"type": "nurse_unavailable", "nurse": 0, "start": "2024-03-05T00:00", "end": "2024-03-06T00:00"}
+        ]"#).unwrap();
+        for event in &events {
+            data.apply(event).unwrap();
+        }
+        assert!(data.validate().is_ok());
+        assert_eq!(data.shifts[1].name, "ER Surge");
+        assert_eq!(data.shifts[0].required_staff, 2);
+        assert_eq!(data.nurses[0].unavailable.len(), 1);
+        assert!(data.apply(&ChangeEvent::StaffingChanged { shift: 9, required_staff: 1 }).is_err());
+
+        let solver = ScheduleSolver::new(data);
+        let mut schedule = solver.initial();
+        solver.solve(&mut schedule);
+        let json = serde_json::to_string(&schedule).unwrap();
+        let published: Schedule = serde_json::from_str(&json).unwrap();
+        assert_eq!(published.assignments, schedule.assignments);
+        assert!(json.starts_with(r#"{"assignments":{"0":"#), "{json}");
+
+        // Repairing an unchanged roster changes nothing
+        let repair = ScheduleSolver::new(solver.data.clone()).with_original(&published, 5);
+        let mut repaired = repair.repair_start(&published);
+        repair.solve(&mut repaired);
+        assert_eq!(repair.disruption(&repaired).changes(), 0);
+        assert_eq!(repair.soft_cost(&repaired), solver.soft_cost(&schedule));
+    }
 }
@@ -0,0 +1,336 @@
+//! N-best Hypotheses and Lattice Export
+//!
+//! transcribe returns ONE string: the fixed point of whole-sentence
+//! saturation. Rescoring with an external language model needs the
+//! alternatives the saturation rejected, with the scores it rejected them by:
+//!
+//! - NBestHypothesis: a distinct word string, its phonemes, and the
+//!   constraint / acoustic split of score_constraints
+//! - Lattice: time-stamped arcs (phonemes or words) between time nodes,
+//!   exported as HTK SLF or JSON
+//!
+//! Arc scores are the decoder's (higher is better): `acoustic` and
+//! `constraint` (the "language" side of the decoder: phonotactics and
+//! lexicon). JSON keeps them as they are; SLF (`a=`, `l=`, base e) needs
+//! log-probabilities, so each is log-softmax normalized over the arcs
+//! leaving the same node.
+
+use super::phoneme::Phoneme;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+/// Null arc label (HTK convention): bridges time gaps between words
+pub const NULL_LABEL: &str = "!NULL";
+
+/// Word with its time span and the scores of its phonemes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScoredWord {
+    pub word: String,
+    pub start_ms: usize,
+    pub end_ms: usize,
+    pub acoustic: f32,
+    pub constraint: f32,
+}
+
+/// One entry of an N-best list (best first)
+#[derive(Clone, Debug)]
+pub struct NBestHypothesis {
+    pub text: String,
+    pub words: Vec<ScoredWord>,
+    /// (phoneme, start_ms, end_ms), one per located segment
+    pub phonemes: Vec<(Phoneme, usize, usize)>,
+    /// Whole-sentence saturation score (the ranking key)
+    pub score: f32,
+    /// score_constraints split: constraint weights passed + acoustic_fit_score
+    pub constraint_score: f32,
+    pub acoustic_score: f32,
+    pub violations: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LatticeKind {
+    Phoneme,
+    Word,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatticeNode {
+    pub time_ms: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LatticeArc {
+    pub from: usize,
+    pub to: usize,
+    pub label: String,
+    pub acoustic: f32,
+    pub constraint: f32,
+}
+
+/// Acyclic graph of time nodes (sorted by time, node 0 = start, last = end)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Lattice {
+    pub kind: LatticeKind,
+    pub nodes: Vec<LatticeNode>,
+    pub arcs: Vec<LatticeArc>,
+}
+
+/// Arc spans in milliseconds until the nodes are numbered
+struct PendingArc {
+    start_ms: usize,
+    end_ms: usize,
+    label: String,
+    acoustic: f32,
+    constraint: f32,
+}
+
+/// Collects arcs by time; `build` numbers the nodes in time order once
+/// (inserting nodes into a built lattice would renumber every arc)
+pub struct LatticeBuilder {
+    kind: LatticeKind,
+    times: BTreeSet<usize>,
+    arcs: Vec<PendingArc>,
+    /// (start_ms, end_ms, label) → index in `arcs`
+    index: HashMap<(usize, usize, String), usize>,
+}
+
+impl LatticeBuilder {
+    /// Empty lattice over [start_ms, end_ms]
+    pub fn new(kind: LatticeKind, start_ms: usize, end_ms: usize) -> Self {
+        LatticeBuilder {
+            kind,
+            times: [start_ms, end_ms.max(start_ms)].into_iter().collect(),
+            arcs: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Add an arc; a duplicate (same span and label) keeps the better scores
+    pub fn add_arc(&mut self, start_ms: usize, end_ms: usize, label: &str, acoustic: f32, constraint: f32) {
+        match self.index.get(&(start_ms, end_ms, label.to_string())) {
+            Some(&i) => {
+                let arc = &mut self.arcs[i];
+                arc.acoustic = arc.acoustic.max(acoustic);
+                arc.constraint = arc.constraint.max(constraint);
+            }
+            None => {
+                self.times.insert(start_ms);
+                self.times.insert(end_ms);
+                self.index.insert((start_ms, end_ms, label.to_string()), self.arcs.len());
+                self.arcs.push(PendingArc { start_ms, end_ms, label: label.to_string(), acoustic, constraint });
+            }
+        }
+    }
+
+    pub fn build(self) -> Lattice {
+        let nodes: Vec<LatticeNode> = self.times.into_iter().map(|time_ms| LatticeNode { time_ms }).collect();
+        let node = |time_ms: usize| nodes.binary_search_by_key(&time_ms, |n| n.time_ms).expect("arc times are nodes");
+        let arcs = self.arcs.into_iter()
+            .map(|a| LatticeArc { from: node(a.start_ms), to: node(a.end_ms), label: a.label, acoustic: a.acoustic, constraint: a.constraint })
+            .collect();
+        Lattice { kind: self.kind, nodes, arcs }
+    }
+}
+
+impl Lattice {
+    /// Word lattice: the union of the N-best word paths, gaps bridged by !NULL
+    pub fn from_nbest(hypotheses: &[NBestHypothesis]) -> Self {
+        let start = hypotheses.iter().filter_map(|h| h.phonemes.first()).map(|p| p.1).min().unwrap_or(0);
+        let end = hypotheses.iter().filter_map(|h| h.phonemes.last()).map(|p| p.2).max().unwrap_or(start);
+        let mut lattice = LatticeBuilder::new(LatticeKind::Word, start, end);
+
+        for hypothesis in hypotheses {
+            let mut at = start;
+            for word in &hypothesis.words {
+                if word.start_ms > at {
+                    lattice.add_arc(at, word.start_ms, NULL_LABEL, 0.0, 0.0);
+                }
+                lattice.add_arc(word.start_ms, word.end_ms, &word.word, word.acoustic, word.constraint);
+                at = word.end_ms;
+            }
+            if end > at {
+                lattice.add_arc(at, end, NULL_LABEL, 0.0, 0.0);
+            }
+        }
+
+        lattice.build()
+    }
+
+    /// HTK Standard Lattice Format: times in seconds, words on links,
+    /// scores as natural-log probabilities among the arcs leaving a node
+    pub fn to_htk_slf(&self, utterance: &str) -> String {
+        let acoustic = self.log_softmax_by_node(|a| a.acoustic);
+        let constraint = self.log_softmax_by_node(|a| a.constraint);
+
+        let mut out = String::new();
+        let _ = writeln!(out, "VERSION=1.0");
+        let _ = writeln!(out, "UTTERANCE={}", slf_escape(utterance));
+        let _ = writeln!(out, "base=e");
+        let _ = writeln!(out, "N={} L={}", self.nodes.len(), self.arcs.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(out, "I={} t={:.3}", i, node.time_ms as f32 / 1000.0);
+        }
+        for (j, arc) in self.arcs.iter().enumerate() {
+            let _ = writeln!(out, "J={} S={} E={} W={} a={:.4} l={:.4}",
+                j, arc.from, arc.to, slf_escape(&arc.label), acoustic[j], constraint[j]);
+        }
+        out
+    }
+
+    /// score - log Σ exp(score) over the arcs sharing the arc's start node
+    fn log_softmax_by_node(&self, score: impl Fn(&LatticeArc) -> f32) -> Vec<f32> {
+        let mut max = vec![f32::NEG_INFINITY; self.nodes.len()];
+        for arc in &self.arcs {
+            max[arc.from] = max[arc.from].max(score(arc));
+        }
+        let mut sum = vec![0.0f32; self.nodes.len()];
+        for arc in &self.arcs {
+            sum[arc.from] += (score(arc) - max[arc.from]).exp();
+        }
+        self.arcs.iter()
+            .map(|arc| score(arc) - max[arc.from] - sum[arc.from].ln())
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatticeFormat {
+    Slf,
+    Json,
+}
+
+impl LatticeFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "slf" | "htk" => Some(LatticeFormat::Slf),
+            "json" => Some(LatticeFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            LatticeFormat::Slf => "slf",
+            LatticeFormat::Json => "json",
+        }
+    }
+
+    pub fn render(self, lattice: &Lattice, utterance: &str) -> String {
+        match self {
+            LatticeFormat::Slf => lattice.to_htk_slf(utterance),
+            LatticeFormat::Json => lattice.to_json(),
+        }
+    }
+}
+
+/// SLF string field: whitespace, quotes, `=` and backslashes escaped with a backslash
+fn slf_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | '=') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// N-best list as TSV: rank, score, constraint, acoustic, violations, text
+pub fn nbest_tsv(hypotheses: &[NBestHypothesis]) -> String {
+    let mut out = String::from("rank\tscore\tconstraint\tacoustic\tviolations\ttext\n");
+    for (rank, h) in hypotheses.iter().enumerate() {
+        let _ = writeln!(out, "{}\t{:.4}\t{:.4}\t{:.4}\t{}\t{}",
+            rank + 1, h.score, h.constraint_score, h.acoustic_score, h.violations, h.text);
+    }
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(w: &str, start_ms: usize, end_ms: usize) -> ScoredWord {
+        ScoredWord { word: w.to_string(), start_ms, end_ms, acoustic: 1.0, constraint: 2.0 }
+    }
+
+    fn hypothesis(words: Vec<ScoredWord>) -> NBestHypothesis {
+        NBestHypothesis {
+            text: words.iter().map(|w| w.word.as_str()).collect::<Vec<_>>().join(" "),
+            words,
+            phonemes: vec![(Phoneme::AH, 0, 100), (Phoneme::T, 100, 300)],
+            score: 0.0,
+            constraint_score: 0.0,
+            acoustic_score: 0.0,
+            violations: 0,
+        }
+    }
+
+    #[test]
+    fn test_word_lattice_from_nbest() {
+        let lattice = Lattice::from_nbest(&[
+            hypothesis(vec![word("A", 0, 100), word("TO", 100, 300)]),
+            hypothesis(vec![word("A", 0, 100), word("T", 150, 300)]),
+        ]);
+
+        let times: Vec<usize> = lattice.nodes.iter().map(|n| n.time_ms).collect();
+        assert_eq!(times, vec![0, 100, 150, 300]);
+        // A shared, TO, T, and a !NULL over the 100-150 gap
+        assert_eq!(lattice.arcs.len(), 4);
+        assert!(lattice.arcs.iter().any(|a| a.label == NULL_LABEL && a.from == 1 && a.to == 2));
+        assert!(lattice.arcs.iter().all(|a| lattice.nodes[a.from].time_ms < lattice.nodes[a.to].time_ms));
+    }
+
+    #[test]
+    fn test_slf_and_json_export() {
+        let lattice = Lattice::from_nbest(&[hypothesis(vec![word("A", 0, 100), word("TO", 100, 300)])]);
+
+        let slf = lattice.to_htk_slf("utt1");
+        assert!(slf.starts_with("VERSION=1.0\nUTTERANCE=utt1\n"));
+        assert!(slf.contains("N=3 L=2"));
+        assert!(slf.contains("I=2 t=0.300"));
+        // The only arc out of its node: probability 1
+        assert!(slf.contains("J=1 S=1 E=2 W=TO a=0.0000 l=0.0000"));
+
+        let parsed: Lattice = serde_json::from_str(&lattice.to_json()).unwrap();
+        assert_eq!(parsed.arcs, lattice.arcs);
+        assert_eq!(parsed.kind, LatticeKind::Word);
+    }
+
+    #[test]
+    fn test_slf_log_probabilities_and_escaping() {
+        let mut builder = LatticeBuilder::new(LatticeKind::Word, 0, 100);
+        builder.add_arc(0, 100, "O'BRIEN", 2.0, 1.0);
+        builder.add_arc(0, 100, "NEW YORK", 0.0, 1.0);
+        let slf = builder.build().to_htk_slf("a b=c");
+
+        assert!(slf.contains("UTTERANCE=a\\ b\\=c\n"));
+        let ln2 = 2.0f32.ln();
+        let a0 = -(1.0 + (-2.0f32).exp()).ln();
+        assert!(slf.contains(&format!("J=0 S=0 E=1 W=O\\'BRIEN a={:.4} l={:.4}", a0, -ln2)), "{}", slf);
+        assert!(slf.contains(&format!("J=1 S=0 E=1 W=NEW\\ YORK a={:.4} l={:.4}", a0 - 2.0, -ln2)), "{}", slf);
+    }
+
+    #[test]
+    fn test_builder_numbers_nodes_in_time_order() {
+        let mut builder = LatticeBuilder::new(LatticeKind::Phoneme, 0, 400);
+        builder.add_arc(300, 400, "T", 1.0, 0.0);
+        builder.add_arc(0, 100, "AH", 1.0, 0.0);
+        builder.add_arc(100, 300, "N", 1.0, 0.0);
+        builder.add_arc(0, 100, "AH", 3.0, -1.0);
+        let lattice = builder.build();
+
+        assert_eq!(lattice.nodes.iter().map(|n| n.time_ms).collect::<Vec<_>>(), vec![0, 100, 300, 400]);
+        assert_eq!(lattice.arcs.len(), 3, "Duplicate AH merged");
+        assert_eq!((lattice.arcs[0].from, lattice.arcs[0].to), (2, 3));
+        assert_eq!((lattice.arcs[1].acoustic, lattice.arcs[1].constraint), (3.0, 0.0), "Better scores kept");
+    }
+}
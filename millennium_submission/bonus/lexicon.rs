@@ -0,0 +1,450 @@
+//! Pronunciation Lexicon - CMUdict Loading and Phoneme Trie
+//!
+//! phonemes_to_words decodes with Viterbi over lexicon matches. The lexicon
+//! used to be a hardcoded list scanned linearly at every position; a trie
+//! keyed by phoneme finds every word starting at position i in one walk
+//! (O(longest pronunciation)), so CMUdict-sized lexicons stay fast.
+//!
+//! CMUdict format: `WORD  PH1 PH2 ...`, `;;;` comments, alternate
+//! pronunciations as `WORD(2)`, stress digits on vowels (AH0, IY1) stripped.
+//! Word scores are log frequencies, like the built-in entries.
+
+use super::phoneme::Phoneme;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Score of loaded words without a frequency (content-word level of the built-ins)
+pub const DEFAULT_WORD_SCORE: f32 = 4.0;
+
+#[derive(Clone, Debug, Default)]
+struct TrieNode {
+    /// (phoneme, node) in insertion order; ~40 phonemes, so a scan beats hashing
+    children: Vec<(Phoneme, usize)>,
+    /// Entries ending here, in insertion order (first added wins score ties)
+    words: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    /// Collapsed pronunciation
+    pronunciation: Vec<Phoneme>,
+    word: String,
+    score: f32,
+}
+
+/// Phoneme trie of (pronunciation → word, score)
+#[derive(Clone, Debug)]
+pub struct Lexicon {
+    nodes: Vec<TrieNode>,
+    /// Every (pronunciation, word) in insertion order
+    entries: Vec<Entry>,
+    /// Word → its entries in insertion (file) order
+    by_word: HashMap<String, Vec<usize>>,
+}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Lexicon { nodes: vec![TrieNode::default()], entries: Vec::new(), by_word: HashMap::new() }
+    }
+}
+
+impl Lexicon {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of (pronunciation, word) entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn child(&self, node: usize, phoneme: Phoneme) -> Option<usize> {
+        self.nodes[node].children.iter().find(|(p, _)| *p == phoneme).map(|&(_, c)| c)
+    }
+
+    /// Add a pronunciation. Repeats are collapsed (K K -> K) because the
+    /// decoder matches against collapsed phonemes.
+    pub fn add(&mut self, pronunciation: &[Phoneme], word: &str, score: f32) {
+        if pronunciation.is_empty() {
+            return;
+        }
+
+        let mut node = 0;
+        let mut collapsed = Vec::with_capacity(pronunciation.len());
+        for &p in pronunciation {
+            if collapsed.last() == Some(&p) {
+                continue;
+            }
+            collapsed.push(p);
+            node = match self.child(node, p) {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.push((p, child));
+                    child
+                }
+            };
+        }
+
+        let entries = &mut self.entries;
+        match self.nodes[node].words.iter().find(|&&e| entries[e].word == word) {
+            // Same word, same pronunciation: keep the better score
+            Some(&existing) => entries[existing].score = entries[existing].score.max(score),
+            None => {
+                let id = entries.len();
+                entries.push(Entry { pronunciation: collapsed, word: word.to_string(), score });
+                self.nodes[node].words.push(id);
+                self.by_word.entry(word.to_string()).or_default().push(id);
+            }
+        }
+    }
+
+    /// Every (pronunciation, word, score) in insertion order; adding them
+    /// to an empty lexicon in this order rebuilds it exactly
+    pub fn entries(&self) -> impl Iterator<Item = (&[Phoneme], &str, f32)> + '_ {
+        self.entries.iter().map(|e| (e.pronunciation.as_slice(), e.word.as_str(), e.score))
+    }
+
+    /// Add every entry of `other` (in its insertion order) to this lexicon
+    pub fn extend(&mut self, other: &Lexicon) {
+        for (pronunciation, word, score) in other.entries() {
+            self.add(pronunciation, word, score);
+        }
+    }
+
+    /// Every (length, word, score) whose pronunciation matches phonemes[start..]
+    pub fn matches_at<'a>(&'a self, phonemes: &'a [Phoneme], start: usize) -> impl Iterator<Item = (usize, &'a str, f32)> + 'a {
+        let mut node = 0;
+        let mut path = Vec::new();
+        for (offset, p) in phonemes[start.min(phonemes.len())..].iter().enumerate() {
+            match self.child(node, *p) {
+                Some(child) => {
+                    node = child;
+                    path.push((offset + 1, node));
+                }
+                None => break,
+            }
+        }
+
+        path.into_iter().flat_map(move |(len, node)| {
+            self.nodes[node].words.iter().map(move |&e| {
+                let entry = &self.entries[e];
+                (len, entry.word.as_str(), entry.score)
+            })
+        })
+    }
+
+    /// First pronunciation of a word (CMUdict's primary one), collapsed
+    pub fn pronunciation(&self, word: &str) -> Option<&[Phoneme]> {
+        let &first = self.by_word.get(word)?.first()?;
+        Some(&self.entries[first].pronunciation)
+    }
+
+    /// Pronunciations of a word in insertion (file) order, collapsed
+    pub fn pronunciations(&self, word: &str) -> Vec<&[Phoneme]> {
+        self.by_word.get(word)
+            .map(|ids| ids.iter().map(|&e| self.entries[e].pronunciation.as_slice()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Add CMUdict entries. `frequencies` (uppercase word → count) sets
+    /// score = ln(1 + count); other words get DEFAULT_WORD_SCORE.
+    /// Returns the number of lines skipped (unknown phonemes, malformed).
+    pub fn add_cmudict(&mut self, text: &str, frequencies: Option<&HashMap<String, f64>>) -> usize {
+        let mut skipped = 0;
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(";;;") {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let Some(raw_word) = fields.next() else { continue };
+            let word = strip_variant(raw_word).to_uppercase();
+
+            let pronunciation: Option<Vec<Phoneme>> = fields.map(parse_cmu_phoneme).collect();
+            match pronunciation {
+                Some(pron) if !pron.is_empty() => {
+                    let score = frequencies
+                        .and_then(|f| f.get(&word))
+                        .map_or(DEFAULT_WORD_SCORE, |&count| (1.0 + count).ln() as f32);
+                    self.add(&pron, &word, score);
+                }
+                _ => skipped += 1,
+            }
+        }
+
+        skipped
+    }
+
+    /// Load a CMUdict file, optionally with a `word count` frequency file.
+    /// Returns the lexicon and the number of lines skipped.
+    pub fn load_cmudict(path: &Path, frequencies: Option<&Path>) -> std::io::Result<(Self, usize)> {
+        let freqs = match frequencies {
+            Some(p) => Some(parse_frequencies(&std::fs::read_to_string(p)?)),
+            None => None,
+        };
+
+        let mut lexicon = Lexicon::new();
+        let skipped = lexicon.add_cmudict(&decode_text(std::fs::read(path)?), freqs.as_ref());
+        Ok((lexicon, skipped))
+    }
+
+    /// Built-in lexicon (the original hardcoded phonemes_to_words dictionary)
+    pub fn builtin() -> Self {
+        use Phoneme::*;
+
+        // PHONEME → WORD dictionary (Path 3: NFA - multiple patterns per word)
+        // Each entry: (pattern, word, score)
+        // Score = log(word_frequency) - higher = more common
+        let entries: &[(&[Phoneme], &str, f32)] = &[
+            // High frequency function words (score 10)
+            (&[DH, AH], "THE", 10.0),
+            (&[DH, IY], "THE", 10.0),
+            (&[AH], "A", 9.0),
+            (&[EY], "A", 8.0),
+            (&[AY], "I", 9.5),
+            (&[T, UW], "TO", 9.0),
+            (&[AE, N, D], "AND", 9.0),
+            (&[AE, N], "AN", 8.0),
+            (&[IH, N], "IN", 8.5),
+            (&[IH, T], "IT", 8.5),
+            (&[IH, Z], "IS", 8.5),
+            (&[DH, AE, T], "THAT", 8.0),
+            (&[W, AA, Z], "WAS", 7.5),
+            (&[W, AH, Z], "WAS", 7.5),
+            (&[F, AO, R], "FOR", 7.5),
+            (&[F, ER], "FOR", 7.5),
+            (&[B, AH, T], "BUT", 7.0),
+            (&[N, AA, T], "NOT", 7.5),
+            (&[W, IH, TH], "WITH", 7.0),
+            (&[HH, IY], "HE", 7.5),
+            (&[SH, IY], "SHE", 7.0),
+            (&[Y, UW], "YOU", 7.5),
+            (&[M, IY], "ME", 7.5),
+            (&[M, AY], "MY", 7.5),
+            // PATH 3: M/N confusion - M often detected when N intended
+            (&[M, OW], "NO", 6.0),  // Works well for "NO"
+            (&[W, IY], "WE", 7.0),
+            // Note: W→WE moved to fallbacks
+            (&[AA, L], "ALL", 7.0),
+            (&[AA, N], "ON", 7.5),
+            (&[AE, T], "AT", 7.5),
+            (&[B, IY], "BE", 7.5),
+            (&[N, OW], "NO", 7.5),
+            (&[S, OW], "SO", 7.0),
+
+            // Medium frequency words (score 5-7)
+            (&[W, EH, N], "WHEN", 6.5),
+            (&[DH, EH, N], "THEN", 6.5),
+            (&[HH, AW], "HOW", 6.0),
+            (&[N, AW], "NOW", 6.5),
+            (&[M, AW], "NOW", 5.5),  // M/N confusion
+            (&[AW, T], "OUT", 6.0),
+            (&[D, UW], "DO", 6.5),
+            (&[HH, AE, D], "HAD", 6.0),
+            (&[HH, AE, V], "HAVE", 6.0),
+            (&[W, UH, D], "WOULD", 5.5),
+            (&[K, UH, D], "COULD", 5.5),
+            (&[TH, IH, S], "THIS", 6.0),
+            (&[DH, OW, Z], "THOSE", 5.5),
+            (&[AH, B, AW, T], "ABOUT", 5.5),
+            (&[W, ER, D], "WORD", 5.0),
+            (&[W, ER, L, D], "WORLD", 5.0),
+            // Note: Removed M+AO → MORE (too greedy, conflicts with OR/AO patterns)
+
+            // Content words (score 4-5)
+            (&[TH, AO, T], "THOUGHT", 5.0),
+            (&[K, IH, L, Z], "KILLS", 4.5),
+            (&[HH, AA, R, T], "HEART", 5.0),
+            (&[HH, EY, T], "HATE", 4.5),
+            (&[L, AH, V], "LOVE", 5.0),
+            (&[M, AE, T, ER], "MATTER", 4.5),
+            (&[F, UH, T], "FOOT", 4.5),
+            (&[S, T, AE, N, D], "STAND", 4.5),
+            (&[P, L, IY, D], "PLEAD", 4.0),
+            (&[W, IH, L, T], "WILT", 4.0),
+            (&[EH, V, ER], "EVER", 4.5),
+            (&[B, EH, N, T], "BENT", 4.0),
+            (&[D, R, AA, P], "DROP", 4.0),
+            (&[AE, F, T, ER], "AFTER", 4.5),
+            (&[L, AO, S], "LOSS", 4.5),
+            (&[S, AA, R, OW], "SORROW", 4.0),
+
+            // Single phoneme fallbacks (low score 2-3)
+            (&[AA], "AH", 2.0),
+            (&[AO], "OR", 3.0),
+            (&[OW], "OH", 3.0),
+            (&[UW], "OO", 2.0),
+            (&[IY], "EE", 2.0),
+            (&[IH], "IH", 2.0),
+            (&[EH], "EH", 2.0),
+            (&[ER], "ER", 2.5),
+
+            // Consonant fallbacks - output consonant letter for debugging
+            // These low scores ensure real words are preferred
+            (&[S], "S", 1.5),
+            (&[T], "T", 1.5),
+            (&[K], "K", 1.5),
+            (&[P], "P", 1.5),
+            (&[F], "F", 1.5),
+            (&[TH], "TH", 1.5),
+            (&[SH], "SH", 1.5),
+            (&[HH], "H", 1.5),
+            (&[M], "ME", 2.0),  // M often represents ME (vowel merged)
+            (&[N], "N", 1.5),
+            (&[L], "L", 1.5),
+            (&[R], "R", 1.5),
+        ];
+
+        let mut lexicon = Lexicon::new();
+        for &(pattern, word, score) in entries {
+            lexicon.add(pattern, word, score);
+        }
+        lexicon
+    }
+}
+
+/// UTF-8 if valid, otherwise Latin-1 (older CMUdict releases): every byte
+/// is the code point of the same value, so accented words survive intact
+fn decode_text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|e| e.into_bytes().iter().map(|&b| b as char).collect())
+}
+
+/// `WORD(2)` -> `WORD`
+fn strip_variant(word: &str) -> &str {
+    match word.find('(') {
+        Some(i) if word.ends_with(')') && i > 0 => &word[..i],
+        _ => word,
+    }
+}
+
+/// CMU phone with stress digit stripped (`AH0` -> AH)
+pub fn parse_cmu_phoneme(token: &str) -> Option<Phoneme> {
+    use Phoneme::*;
+
+    let base = token.trim_end_matches(|c: char| c.is_ascii_digit());
+    Some(match base {
+        "AA" => AA, "AE" => AE, "AH" => AH, "AO" => AO, "AW" => AW, "AY" => AY,
+        "EH" => EH, "ER" => ER, "EY" => EY, "IH" => IH, "IY" => IY, "OW" => OW,
+        "OY" => OY, "UH" => UH, "UW" => UW,
+        "W" => W, "Y" => Y, "L" => L, "R" => R, "M" => M, "N" => N, "NG" => NG,
+        "V" => V, "DH" => DH, "Z" => Z, "ZH" => ZH, "F" => F, "TH" => TH,
+        "S" => S, "SH" => SH, "HH" => HH, "CH" => CH, "JH" => JH,
+        "B" => B, "D" => D, "G" => G, "P" => P, "T" => T, "K" => K,
+        _ => return None,
+    })
+}
+
+/// `word count` per line (uppercased words; unparsable lines ignored)
+pub fn parse_frequencies(text: &str) -> HashMap<String, f64> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let word = fields.next()?.to_uppercase();
+            let count = fields.next()?.parse().ok()?;
+            Some((word, count))
+        })
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Phoneme::*;
+
+    const SAMPLE: &str = ";;; # CMUdict sample
+HELLO  HH AH0 L OW1
+HELLO(2)  HH EH0 L OW1
+BOOKKEEPER  B UH1 K K IY2 P ER0
+WORLD  W ER1 L D
+CAFE  K AH0 F EY1 X
+";
+
+    #[test]
+    fn test_cmudict_parsing() {
+        let mut lexicon = Lexicon::new();
+        let skipped = lexicon.add_cmudict(SAMPLE, None);
+
+        assert_eq!(skipped, 1, "Unknown phone X skips the entry");
+        assert_eq!(lexicon.len(), 4);
+        assert_eq!(lexicon.pronunciations("HELLO"), vec![&[HH, AH, L, OW][..], &[HH, EH, L, OW]]);
+        // Repeats collapsed to match the decoder's collapsed input
+        assert_eq!(lexicon.pronunciations("BOOKKEEPER"), vec![&[B, UH, K, IY, P, ER][..]]);
+        assert_eq!(lexicon.pronunciation("HELLO"), Some(&[HH, AH, L, OW][..]));
+        assert!(lexicon.pronunciations("CAFE").is_empty());
+    }
+
+    #[test]
+    fn test_pronunciations_keep_file_order() {
+        // Trie order would list HH AH before HH EH; the file has them the other way round
+        let mut lexicon = Lexicon::new();
+        lexicon.add_cmudict("HELLO  HH EH0 L OW1\nHELLO(2)  HH AH0 L OW1\nHELLO(3)  HH EH0 L OW1\n", None);
+        assert_eq!(lexicon.pronunciations("HELLO"), vec![&[HH, EH, L, OW][..], &[HH, AH, L, OW]]);
+        assert_eq!(lexicon.len(), 2, "Repeated pronunciation collapsed into one entry");
+    }
+
+    #[test]
+    fn test_load_latin1_cmudict() {
+        let path = std::env::temp_dir().join(format!("cmudict_latin1_{}.txt", std::process::id()));
+        // "CAF\xc9" is CAFÉ in Latin-1 and invalid UTF-8
+        std::fs::write(&path, b"CAF\xc9  K AE0 F EY1\nBAD  B X D\nCAT  K AE1 T\n").unwrap();
+        let (lexicon, skipped) = Lexicon::load_cmudict(&path, None).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(skipped, 1);
+        assert_eq!(lexicon.pronunciations("CAF\u{c9}"), vec![&[K, AE, F, EY][..]]);
+        assert_eq!(lexicon.pronunciations("CAT"), vec![&[K, AE, T][..]]);
+    }
+
+    #[test]
+    fn test_extend_keeps_both_lexicons() {
+        let mut lexicon = Lexicon::builtin();
+        let builtin_len = lexicon.len();
+        let mut loaded = Lexicon::new();
+        loaded.add_cmudict("CAT  K AE1 T\nTHE  DH AH0\n", None);
+        lexicon.extend(&loaded);
+
+        assert_eq!(lexicon.len(), builtin_len + 1, "THE DH AH was already there");
+        assert!(lexicon.matches_at(&[DH, AH], 0).any(|(_, w, s)| w == "THE" && s == 10.0), "Better score kept");
+        assert_eq!(lexicon.pronunciations("CAT"), vec![&[K, AE, T][..]]);
+    }
+
+    #[test]
+    fn test_trie_matches_and_frequencies() {
+        let freqs = parse_frequencies("hello 1000\nworld 50\n");
+        let mut lexicon = Lexicon::new();
+        lexicon.add_cmudict(SAMPLE, Some(&freqs));
+        lexicon.add(&[HH, AH], "HUH", 2.0);
+
+        let input = [HH, AH, L, OW, W, ER, L, D];
+        let at_0: Vec<_> = lexicon.matches_at(&input, 0).collect();
+        assert_eq!(at_0.len(), 2, "HUH (prefix) and HELLO");
+        assert_eq!(at_0[0].1, "HUH");
+        assert_eq!((at_0[1].0, at_0[1].1), (4, "HELLO"));
+        assert!((at_0[1].2 - (1001.0f64).ln() as f32).abs() < 1e-4);
+
+        assert_eq!(lexicon.matches_at(&input, 4).map(|m| m.1).collect::<Vec<_>>(), vec!["WORLD"]);
+        assert_eq!(lexicon.matches_at(&input, 8).count(), 0);
+    }
+
+    #[test]
+    fn test_builtin_lexicon() {
+        let lexicon = Lexicon::builtin();
+        assert!(lexicon.matches_at(&[DH, AH], 0).any(|(_, w, s)| w == "THE" && s == 10.0));
+        // Two patterns for one word (NFA)
+        assert!(lexicon.matches_at(&[W, AH, Z], 0).any(|(_, w, _)| w == "WAS"));
+        assert!(lexicon.matches_at(&[W, AA, Z], 0).any(|(_, w, _)| w == "WAS"));
+    }
+}
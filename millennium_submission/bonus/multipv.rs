@@ -0,0 +1,332 @@
+//! Multi-PV Saturation for Analysis
+//!
+//! SaturationAnalyzer::is_move_stabilized compares ONE best move at depth n
+//! and n-2. For analysis we need the top-k root moves to converge:
+//!
+//! - Ranking stable:  top-k move order at depth n == order at depth n-2
+//! - Scores stable:   every line's same-parity delta < epsilon (Discovery 23)
+//! - Gaps stable:     score gaps between adjacent lines move < epsilon
+//!
+//! Root moves are scored by searching each child at depth-1 (negamax, as
+//! saturation::score_root_moves does for a cuttable depth), so every depth has a score for EVERY root
+//! move and the same-parity comparison never lacks data.
+
+use super::framework_search::FrameworkSearchEngine;
+use super::movegen::Move;
+use super::position::Position;
+use super::saturation::{
+    derive_epsilon, derive_node_limit, score_root_moves, PositionComplexity, SaturationAnalyzer, SearchLimits, StopReason,
+};
+use std::time::Instant;
+
+/// Root scores at one depth, best first
+#[derive(Clone, Debug)]
+pub struct RootScores {
+    pub depth: i32,
+    pub moves: Vec<(Move, i32)>,
+}
+
+impl RootScores {
+    fn score_of(&self, mv: Move) -> Option<i32> {
+        self.moves.iter().find(|(m, _)| *m == mv).map(|&(_, s)| s)
+    }
+
+    fn top(&self, k: usize) -> &[(Move, i32)] {
+        &self.moves[..k.min(self.moves.len())]
+    }
+}
+
+/// Tracks top-k rankings across depths and detects multi-PV saturation
+pub struct MultiPvAnalyzer {
+    pub k: usize,
+    history: Vec<RootScores>,
+}
+
+impl MultiPvAnalyzer {
+    pub fn new(k: usize) -> Self {
+        MultiPvAnalyzer { k: k.max(1), history: Vec::new() }
+    }
+
+    /// Record all root scores for a depth (any order)
+    pub fn record(&mut self, depth: i32, mut moves: Vec<(Move, i32)>) {
+        moves.sort_by_key(|&(_, s)| -s);
+        self.history.push(RootScores { depth, moves });
+    }
+
+    /// Top-k move order at depth n equals the order at depth n-2
+    pub fn is_ranking_stabilized(&self) -> bool {
+        let n = self.history.len();
+        if n < 3 {
+            return false;
+        }
+
+        let current: Vec<Move> = self.history[n-1].top(self.k).iter().map(|&(m, _)| m).collect();
+        let same_parity: Vec<Move> = self.history[n-3].top(self.k).iter().map(|&(m, _)| m).collect();
+
+        !current.is_empty() && current == same_parity
+    }
+
+    /// Largest same-parity score delta over the current top-k lines,
+    /// checked for both parities (n vs n-2 and n-1 vs n-3)
+    pub fn max_score_delta(&self) -> Option<i32> {
+        let n = self.history.len();
+        if n < 4 {
+            return None;
+        }
+
+        let mut max_delta = 0;
+        for &(mv, _) in self.history[n-1].top(self.k) {
+            for (a, b) in [(n-1, n-3), (n-2, n-4)] {
+                let delta = (self.history[a].score_of(mv)? - self.history[b].score_of(mv)?).abs();
+                max_delta = max_delta.max(delta);
+            }
+        }
+
+        Some(max_delta)
+    }
+
+    /// Largest change of an adjacent-line score gap, checked for both
+    /// parities (n vs n-2 and n-1 vs n-3)
+    pub fn max_gap_delta(&self) -> Option<i32> {
+        let n = self.history.len();
+        if n < 4 {
+            return None;
+        }
+
+        let gaps = |scores: &RootScores| -> Vec<i32> {
+            scores.top(self.k).windows(2).map(|w| w[0].1 - w[1].1).collect()
+        };
+
+        [(n-1, n-3), (n-2, n-4)].iter()
+            .flat_map(|&(a, b)| {
+                gaps(&self.history[a]).into_iter()
+                    .zip(gaps(&self.history[b]))
+                    .map(|(g1, g0)| (g1 - g0).abs())
+            })
+            .max()
+            .or(Some(0))
+    }
+
+    /// Ranking, line scores and line gaps all stable under the parity rule
+    pub fn is_saturated(&self, epsilon: i32) -> bool {
+        self.is_ranking_stabilized()
+            && self.max_score_delta().is_some_and(|d| d < epsilon)
+            && self.max_gap_delta().is_some_and(|d| d < epsilon)
+    }
+
+    /// Convergence data for each of the current top-k lines
+    pub fn lines(&self) -> Vec<MultiPvLine> {
+        let Some(last) = self.history.last() else { return Vec::new() };
+
+        last.top(self.k).iter().enumerate().map(|(rank, &(mv, score))| {
+            // Per-line analyzer: the same same-parity machinery as single-PV
+            let mut analyzer = SaturationAnalyzer::new();
+            let mut rank_history = Vec::new();
+            for scores in &self.history {
+                if let Some(s) = scores.score_of(mv) {
+                    analyzer.record(scores.depth, s, Some(mv));
+                }
+                rank_history.push(scores.moves.iter().position(|&(m, _)| m == mv).map(|r| r + 1));
+            }
+
+            MultiPvLine {
+                rank: rank + 1,
+                mv,
+                score,
+                same_parity_delta: analyzer.same_parity_delta(),
+                rank_history,
+                analyzer,
+            }
+        }).collect()
+    }
+}
+
+/// One analysis line with its convergence data
+pub struct MultiPvLine {
+    /// 1-based rank at the final depth
+    pub rank: usize,
+    pub mv: Move,
+    pub score: i32,
+    /// |score_n - score_{n-2}| for this move
+    pub same_parity_delta: Option<i32>,
+    /// Rank of this move at every depth (None = not ranked)
+    pub rank_history: Vec<Option<usize>>,
+    /// Score history of this move (depth, eval)
+    pub analyzer: SaturationAnalyzer,
+}
+
+/// Result of a multi-PV saturation search
+pub struct MultiPvResult {
+    pub lines: Vec<MultiPvLine>,
+    pub depth: i32,
+    pub saturated: bool,
+    pub nodes: u64,
+}
+
+/// Multi-PV search: iterate depths until the top-k ranking saturates
+///
+/// Uses the depth cap, node budget, stop flag and movetime/clock from
+/// `limits`, with the same TimeBudget deadlines as the single-PV search.
+/// Default node budget = derive_node_limit × k (k lines must converge).
+/// After the first depth, a stop request or the hard deadline abandons the
+/// running depth between root moves; the lines come from the last completed
+/// depth.
+pub fn search_multipv(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    history: &[u64],
+    k: usize,
+    limits: &SearchLimits,
+) -> MultiPvResult {
+    let mut analyzer = MultiPvAnalyzer::new(k);
+    let epsilon = derive_epsilon(pos);
+    let max_depth = limits.depth.unwrap_or_else(|| PositionComplexity::analyze(pos).recommended_depth);
+    let node_limit = limits.nodes.unwrap_or_else(|| derive_node_limit(pos) * analyzer.k as u64);
+    let nodes_at_start = engine.nodes;
+    let budget = limits.time_budget(pos);
+    let start = Instant::now();
+    let elapsed_ms = || start.elapsed().as_millis() as u64;
+
+    let mut depth = 1;
+    let mut saturated = false;
+
+    // Root depth d = children searched at d-1, so start at 2
+    for d in 2..=max_depth.max(2) {
+        let depth_start = Instant::now();
+        let mut interrupted = |_: &FrameworkSearchEngine| {
+            // The first depth always completes so there are lines to report
+            if d == 2 {
+                None
+            } else if limits.stop_requested() {
+                Some(StopReason::Stopped)
+            } else {
+                budget.is_some_and(|b| elapsed_ms() >= b.hard_ms).then_some(StopReason::TimeHard)
+            }
+        };
+        let Ok(scores) = score_root_moves(engine, pos, d, history, None, &mut interrupted) else {
+            break;
+        };
+        depth = d;
+        analyzer.record(d, scores);
+
+        if analyzer.is_saturated(epsilon) {
+            saturated = true;
+            break;
+        }
+
+        if limits.stop_requested() {
+            break;
+        }
+
+        // Soft deadline, or the next depth (~2× this one) would overrun hard
+        if let Some(budget) = budget {
+            let depth_ms = depth_start.elapsed().as_millis() as u64;
+            if elapsed_ms() >= budget.soft_ms || elapsed_ms() + depth_ms * 2 > budget.hard_ms {
+                break;
+            }
+        }
+
+        let nodes = engine.nodes - nodes_at_start;
+        if nodes > node_limit && d >= 4 {
+            break;
+        }
+    }
+
+    MultiPvResult {
+        lines: analyzer.lines(),
+        depth,
+        saturated,
+        nodes: engine.nodes - nodes_at_start,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bitboard::Square;
+    use super::super::saturation::mate_in;
+
+    fn e4() -> Move { Move::new(Square::E2, Square::E4) }
+    fn d4() -> Move { Move::new(Square::D2, Square::D4) }
+    fn nf3() -> Move { Move::new(Square::G1, Square::F3) }
+
+    #[test]
+    fn test_multipv_saturation() {
+        let mut analyzer = MultiPvAnalyzer::new(2);
+
+        // Odd/even depths alternate perspective but each parity converges
+        analyzer.record(2, vec![(e4(), 40), (d4(), 30), (nf3(), 10)]);
+        analyzer.record(3, vec![(e4(), 20), (d4(), 12), (nf3(), 0)]);
+        analyzer.record(4, vec![(e4(), 42), (d4(), 31), (nf3(), 5)]);
+        analyzer.record(5, vec![(e4(), 21), (d4(), 13), (nf3(), 2)]);
+
+        assert!(analyzer.is_ranking_stabilized());
+        assert_eq!(analyzer.max_score_delta(), Some(2));
+        assert!(analyzer.is_saturated(15));
+
+        let lines = analyzer.lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].mv, e4());
+        assert_eq!(lines[1].rank_history, vec![Some(2), Some(2), Some(2), Some(2)]);
+        assert_eq!(lines[1].same_parity_delta, Some(1));
+    }
+
+    #[test]
+    fn test_multipv_ranking_swap_blocks_saturation() {
+        let mut analyzer = MultiPvAnalyzer::new(2);
+
+        // Best move alone is stable, but lines 2 and 3 swap between same-parity depths
+        analyzer.record(2, vec![(e4(), 40), (d4(), 30), (nf3(), 25)]);
+        analyzer.record(3, vec![(e4(), 20), (d4(), 12), (nf3(), 10)]);
+        analyzer.record(4, vec![(e4(), 41), (nf3(), 31), (d4(), 29)]);
+        analyzer.record(5, vec![(e4(), 21), (nf3(), 13), (d4(), 11)]);
+
+        assert!(!analyzer.is_ranking_stabilized());
+        assert!(!analyzer.is_saturated(15));
+    }
+
+    #[test]
+    fn test_multipv_gap_change_blocks_saturation() {
+        let mut analyzer = MultiPvAnalyzer::new(2);
+
+        // Same order, but the gap between lines collapses from 50 to 5
+        analyzer.record(2, vec![(e4(), 60), (d4(), 10)]);
+        analyzer.record(3, vec![(e4(), 20), (d4(), 15)]);
+        analyzer.record(4, vec![(e4(), 60), (d4(), 55)]);
+        analyzer.record(5, vec![(e4(), 20), (d4(), 15)]);
+
+        assert!(analyzer.is_ranking_stabilized());
+        assert_eq!(analyzer.max_gap_delta(), Some(45));
+        assert!(!analyzer.is_saturated(15));
+    }
+
+    #[test]
+    fn test_multipv_respects_movetime() {
+        let pos = Position::starting();
+        let mut engine = FrameworkSearchEngine::new();
+        let limits = SearchLimits { movetime_ms: Some(0), ..SearchLimits::default() };
+
+        // No time at all: the first depth completes, nothing deeper starts
+        let result = search_multipv(&mut engine, &pos, &[], 3, &limits);
+        assert_eq!(result.depth, 2);
+        assert_eq!(result.lines.len(), 3);
+        assert!(!result.saturated);
+    }
+
+    #[test]
+    fn test_multipv_mate_distance() {
+        // Ra8 mates at once: the top line is mate in 1, as the single-PV search reports it
+        let pos = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut engine = FrameworkSearchEngine::new();
+        let limits = SearchLimits { depth: Some(2), ..SearchLimits::default() };
+
+        let result = search_multipv(&mut engine, &pos, &[], 2, &limits);
+        let (_, single_pv) = FrameworkSearchEngine::new().search(&pos, 2);
+        assert_eq!(mate_in(result.lines[0].score), Some(1));
+        assert_eq!(result.lines[0].score, single_pv);
+    }
+}
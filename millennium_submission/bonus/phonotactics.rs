@@ -0,0 +1,488 @@
+//! Declarative Phonotactic Rules
+//!
+//! hardcode_constraints built English as `fn` pointers: another language
+//! or accent meant editing the engine. The RULES are data, the ENGINE
+//! (saturation) is not - like chess variants sharing one search:
+//!
+//! - inventory: which phonemes are vowels, the sonority scale, and the
+//!   acoustic classes that propose and score candidates per segment
+//! - constraints: typed, weighted rules (forbidden bigrams, onset/coda
+//!   clusters, sonority sequencing, pitch/duration alignment, ...)
+//! - boundaries: ObservableBoundary per phoneme class
+//!
+//! Rules files are JSON; phonemes are CMU names (stress digits ignored).
+//! The built-in English set is phonotactics_en.json.
+
+use super::lexicon::parse_cmu_phoneme;
+use super::phoneme::Phoneme;
+use super::saturated_transcriber::{ObservableBoundary, PhonotacticConstraint, SegmentInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+/// Identifies a rules file
+pub const RULES_FORMAT: &str = "phonotactic-rules";
+pub const RULES_VERSION: u32 = 1;
+
+const ENGLISH_RULES: &str = include_str!("phonotactics_en.json");
+
+// ============================================================================
+// RULES FILE
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhonotacticRules {
+    pub format: String,
+    pub version: u32,
+    pub language: String,
+    pub vowels: Vec<String>,
+    /// Phonemes not listed have sonority 0
+    pub sonority: BTreeMap<String, u8>,
+    pub constraints: Vec<ConstraintRule>,
+    #[serde(default)]
+    pub boundaries: Vec<BoundaryRule>,
+    /// Missing from older files: the English classes
+    #[serde(default)]
+    pub classes: ClassRules,
+}
+
+/// Phoneme classes behind segment candidates and acoustic scoring
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClassRules {
+    /// Vowels proposed (and rewarded) when the high band dominates
+    pub high_vowels: Vec<String>,
+    /// ... when the mid band dominates or the bands are balanced
+    pub mid_vowels: Vec<String>,
+    /// ... when the low band dominates
+    pub low_vowels: Vec<String>,
+    /// Added for long voiced segments; too short a segment is penalized
+    pub diphthongs: Vec<String>,
+    /// Diphthongs rewarded for a low-band onset
+    pub rising_diphthongs: Vec<String>,
+    /// Added for short voiced segments with a low-band peak
+    pub sonorants: Vec<String>,
+    /// Candidates for unvoiced or noise-dominated segments
+    pub obstruents: Vec<String>,
+    /// Rewarded when the high band carries the noise
+    pub fricatives: Vec<String>,
+    /// Consonants expected on voiced segments (vowels always are)
+    pub voiced_consonants: Vec<String>,
+    /// Candidates when nothing else applies
+    pub fallback: Vec<String>,
+    /// Common word-initial phonemes, and the onsets of the commonest words
+    pub strong_onsets: Vec<String>,
+    pub weak_onsets: Vec<String>,
+    /// Vowel-to-vowel transitions that form a diphthong
+    pub vowel_glides: Vec<[String; 2]>,
+    /// Phonemes of very common words (English AY, the word "I")
+    pub frequent: Vec<String>,
+}
+
+impl Default for ClassRules {
+    /// The English classes
+    fn default() -> Self {
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        ClassRules {
+            high_vowels: names(&["IY", "IH", "EY", "EH"]),
+            mid_vowels: names(&["AH", "AE", "ER"]),
+            low_vowels: names(&["AA", "AO", "OW", "UW"]),
+            diphthongs: names(&["AY", "OY", "AW", "EY", "OW"]),
+            rising_diphthongs: names(&["AY", "OY", "EY"]),
+            sonorants: names(&["M", "N", "L", "R"]),
+            obstruents: names(&["S", "SH", "F", "TH", "T", "K", "P", "HH"]),
+            fricatives: names(&["S", "SH", "F", "TH", "HH"]),
+            voiced_consonants: names(&["M", "N", "NG", "L", "R", "W", "Y", "B", "D", "G", "V", "DH", "Z", "ZH", "JH"]),
+            fallback: names(&["AH", "IH", "N", "T"]),
+            strong_onsets: names(&["T", "S", "K", "P", "M", "N"]),
+            weak_onsets: names(&["DH", "AH", "IH", "AY"]),
+            vowel_glides: [["AA", "IY"], ["AH", "IY"], ["EH", "IY"], ["OW", "UW"]].iter()
+                .map(|[a, b]| [a.to_string(), b.to_string()])
+                .collect(),
+            frequent: names(&["AY"]),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConstraintRule {
+    pub name: String,
+    pub weight: f32,
+    #[serde(flatten)]
+    pub rule: RuleKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleKind {
+    /// At least one vowel
+    VowelNucleus,
+    /// No `first` phoneme directly followed by a `second` phoneme
+    ForbiddenBigrams { first: Vec<String>, second: Vec<String> },
+    /// Sonority never falls across the consonants before the first vowel
+    SonorityRise,
+    /// Consonant run before the first vowel: single, or one of `allowed`
+    OnsetClusters { allowed: Vec<Vec<String>> },
+    /// Consonant run after the last vowel: single, or one of `allowed`
+    CodaClusters { allowed: Vec<Vec<String>> },
+    /// Pitch (MIDI) above high_pitch excludes low vowels, below low_pitch high vowels
+    PitchVowelAlignment { high_vowels: Vec<String>, low_vowels: Vec<String>, high_pitch: u8, low_pitch: u8 },
+    /// Segments over long_ms are vowels/diphthongs/sustained; under short_ms not diphthongs
+    DurationAlignment { diphthongs: Vec<String>, sustained: Vec<String>, long_ms: usize, short_ms: usize },
+    /// A consonant-vowel transition (or at least a vowel)
+    CvPreference,
+    /// Sequences up to max_len start with a vowel or sonority >= min_sonority
+    VowelInitial { max_len: usize, min_sonority: u8 },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoundaryRule {
+    pub phonemes: Vec<String>,
+    pub pitch_low: u8,
+    pub pitch_high: u8,
+    pub min_duration_ms: usize,
+    pub max_duration_ms: usize,
+    pub requires_attack: bool,
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Format(String),
+    Version(u32),
+    UnknownPhoneme(String),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(e) => write!(f, "I/O error: {}", e),
+            RulesError::Json(e) => write!(f, "invalid rules JSON: {}", e),
+            RulesError::Format(found) => write!(f, "not a {} file (format '{}')", RULES_FORMAT, found),
+            RulesError::Version(v) => write!(f, "unsupported rules version {} (expected {})", v, RULES_VERSION),
+            RulesError::UnknownPhoneme(name) => write!(f, "unknown phoneme '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl PhonotacticRules {
+    /// The built-in English set (what hardcode_constraints used to build)
+    pub fn english() -> Self {
+        Self::from_json(ENGLISH_RULES).expect("built-in English rules are valid")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, RulesError> {
+        let rules: PhonotacticRules = serde_json::from_str(json).map_err(RulesError::Json)?;
+        if rules.format != RULES_FORMAT {
+            return Err(RulesError::Format(rules.format));
+        }
+        if rules.version != RULES_VERSION {
+            return Err(RulesError::Version(rules.version));
+        }
+        Ok(rules)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, RulesError> {
+        Self::from_json(&std::fs::read_to_string(path).map_err(RulesError::Io)?)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Resolve phoneme names into the inventory, constraints and boundaries
+    pub fn compile(&self) -> Result<CompiledRules, RulesError> {
+        let inventory = PhonemeInventory {
+            vowels: phonemes(&self.vowels)?,
+            sonority: self.sonority.iter()
+                .map(|(name, &s)| Ok((phoneme(name)?, s)))
+                .collect::<Result<_, RulesError>>()?,
+            classes: self.classes.compile()?,
+        };
+
+        let constraints = self.constraints.iter()
+            .map(|c| Ok(PhonotacticConstraint { name: c.name.clone(), check: c.rule.compile()?, weight: c.weight }))
+            .collect::<Result<Vec<_>, RulesError>>()?;
+
+        let mut boundaries = HashMap::new();
+        for b in &self.boundaries {
+            for p in phonemes(&b.phonemes)? {
+                boundaries.insert(p, ObservableBoundary {
+                    pitch_low: b.pitch_low,
+                    pitch_high: b.pitch_high,
+                    min_duration_ms: b.min_duration_ms,
+                    max_duration_ms: b.max_duration_ms,
+                    requires_attack: b.requires_attack,
+                });
+            }
+        }
+
+        Ok(CompiledRules { inventory, constraints, boundaries })
+    }
+}
+
+fn phoneme(name: &str) -> Result<Phoneme, RulesError> {
+    parse_cmu_phoneme(name).ok_or_else(|| RulesError::UnknownPhoneme(name.to_string()))
+}
+
+fn phonemes(names: &[String]) -> Result<Vec<Phoneme>, RulesError> {
+    names.iter().map(|n| phoneme(n)).collect()
+}
+
+impl ClassRules {
+    fn compile(&self) -> Result<PhonemeClasses, RulesError> {
+        Ok(PhonemeClasses {
+            high_vowels: phonemes(&self.high_vowels)?,
+            mid_vowels: phonemes(&self.mid_vowels)?,
+            low_vowels: phonemes(&self.low_vowels)?,
+            diphthongs: phonemes(&self.diphthongs)?,
+            rising_diphthongs: phonemes(&self.rising_diphthongs)?,
+            sonorants: phonemes(&self.sonorants)?,
+            obstruents: phonemes(&self.obstruents)?,
+            fricatives: phonemes(&self.fricatives)?,
+            voiced_consonants: phonemes(&self.voiced_consonants)?,
+            fallback: phonemes(&self.fallback)?,
+            strong_onsets: phonemes(&self.strong_onsets)?,
+            weak_onsets: phonemes(&self.weak_onsets)?,
+            vowel_glides: self.vowel_glides.iter()
+                .map(|[a, b]| Ok((phoneme(a)?, phoneme(b)?)))
+                .collect::<Result<_, RulesError>>()?,
+            frequent: phonemes(&self.frequent)?,
+        })
+    }
+}
+
+impl RuleKind {
+    fn compile(&self) -> Result<ConstraintCheck, RulesError> {
+        Ok(match self {
+            RuleKind::VowelNucleus => ConstraintCheck::VowelNucleus,
+            RuleKind::ForbiddenBigrams { first, second } => ConstraintCheck::ForbiddenBigrams {
+                first: phonemes(first)?,
+                second: phonemes(second)?,
+            },
+            RuleKind::SonorityRise => ConstraintCheck::SonorityRise,
+            RuleKind::OnsetClusters { allowed } => ConstraintCheck::OnsetClusters {
+                allowed: allowed.iter().map(|c| phonemes(c)).collect::<Result<_, _>>()?,
+            },
+            RuleKind::CodaClusters { allowed } => ConstraintCheck::CodaClusters {
+                allowed: allowed.iter().map(|c| phonemes(c)).collect::<Result<_, _>>()?,
+            },
+            RuleKind::PitchVowelAlignment { high_vowels, low_vowels, high_pitch, low_pitch } => ConstraintCheck::PitchVowelAlignment {
+                high_vowels: phonemes(high_vowels)?,
+                low_vowels: phonemes(low_vowels)?,
+                high_pitch: *high_pitch,
+                low_pitch: *low_pitch,
+            },
+            RuleKind::DurationAlignment { diphthongs, sustained, long_ms, short_ms } => ConstraintCheck::DurationAlignment {
+                diphthongs: phonemes(diphthongs)?,
+                sustained: phonemes(sustained)?,
+                long_ms: *long_ms,
+                short_ms: *short_ms,
+            },
+            RuleKind::CvPreference => ConstraintCheck::CvPreference,
+            RuleKind::VowelInitial { max_len, min_sonority } => ConstraintCheck::VowelInitial {
+                max_len: *max_len,
+                min_sonority: *min_sonority,
+            },
+        })
+    }
+}
+
+// ============================================================================
+// COMPILED RULES
+// ============================================================================
+
+/// Vowel set, sonority scale and acoustic classes of a language
+#[derive(Clone, Debug, Default)]
+pub struct PhonemeInventory {
+    pub vowels: Vec<Phoneme>,
+    pub sonority: HashMap<Phoneme, u8>,
+    pub classes: PhonemeClasses,
+}
+
+/// ClassRules with phoneme names resolved
+#[derive(Clone, Debug, Default)]
+pub struct PhonemeClasses {
+    pub high_vowels: Vec<Phoneme>,
+    pub mid_vowels: Vec<Phoneme>,
+    pub low_vowels: Vec<Phoneme>,
+    pub diphthongs: Vec<Phoneme>,
+    pub rising_diphthongs: Vec<Phoneme>,
+    pub sonorants: Vec<Phoneme>,
+    pub obstruents: Vec<Phoneme>,
+    pub fricatives: Vec<Phoneme>,
+    pub voiced_consonants: Vec<Phoneme>,
+    pub fallback: Vec<Phoneme>,
+    pub strong_onsets: Vec<Phoneme>,
+    pub weak_onsets: Vec<Phoneme>,
+    pub vowel_glides: Vec<(Phoneme, Phoneme)>,
+    pub frequent: Vec<Phoneme>,
+}
+
+impl PhonemeInventory {
+    pub fn is_vowel(&self, p: Phoneme) -> bool {
+        self.vowels.contains(&p)
+    }
+
+    /// Vowel or voiced consonant
+    pub fn is_voiced(&self, p: Phoneme) -> bool {
+        self.is_vowel(p) || self.classes.voiced_consonants.contains(&p)
+    }
+
+    pub fn sonority(&self, p: Phoneme) -> u8 {
+        self.sonority.get(&p).copied().unwrap_or(0)
+    }
+}
+
+pub struct CompiledRules {
+    pub inventory: PhonemeInventory,
+    pub constraints: Vec<PhonotacticConstraint>,
+    pub boundaries: HashMap<Phoneme, ObservableBoundary>,
+}
+
+/// A rule with phoneme names resolved
+#[derive(Clone, Debug)]
+pub enum ConstraintCheck {
+    VowelNucleus,
+    ForbiddenBigrams { first: Vec<Phoneme>, second: Vec<Phoneme> },
+    SonorityRise,
+    OnsetClusters { allowed: Vec<Vec<Phoneme>> },
+    CodaClusters { allowed: Vec<Vec<Phoneme>> },
+    PitchVowelAlignment { high_vowels: Vec<Phoneme>, low_vowels: Vec<Phoneme>, high_pitch: u8, low_pitch: u8 },
+    DurationAlignment { diphthongs: Vec<Phoneme>, sustained: Vec<Phoneme>, long_ms: usize, short_ms: usize },
+    CvPreference,
+    VowelInitial { max_len: usize, min_sonority: u8 },
+}
+
+impl ConstraintCheck {
+    /// Does the sequence satisfy the rule?
+    pub fn check(&self, phonemes: &[Phoneme], segments: &[SegmentInfo], inventory: &PhonemeInventory) -> bool {
+        let is_vowel = |p: &Phoneme| inventory.is_vowel(*p);
+        let cluster_ok = |run: &[Phoneme], allowed: &[Vec<Phoneme>]| run.len() <= 1 || allowed.iter().any(|c| c == run);
+
+        match self {
+            ConstraintCheck::VowelNucleus => phonemes.iter().any(is_vowel),
+            ConstraintCheck::ForbiddenBigrams { first, second } => {
+                !phonemes.windows(2).any(|w| first.contains(&w[0]) && second.contains(&w[1]))
+            }
+            ConstraintCheck::SonorityRise => match phonemes.iter().position(is_vowel) {
+                Some(pos) => phonemes[..pos].windows(2).all(|w| inventory.sonority(w[1]) >= inventory.sonority(w[0])),
+                None => true,
+            },
+            ConstraintCheck::OnsetClusters { allowed } => {
+                let onset = phonemes.iter().position(is_vowel).unwrap_or(phonemes.len());
+                cluster_ok(&phonemes[..onset], allowed)
+            }
+            ConstraintCheck::CodaClusters { allowed } => {
+                let coda = phonemes.iter().rposition(is_vowel).map_or(0, |v| v + 1);
+                cluster_ok(&phonemes[coda..], allowed)
+            }
+            ConstraintCheck::PitchVowelAlignment { high_vowels, low_vowels, high_pitch, low_pitch } => {
+                phonemes.iter().zip(segments).all(|(p, seg)| {
+                    // No pitch = nothing to align
+                    seg.pitch == 0
+                        || !((seg.pitch > *high_pitch && low_vowels.contains(p))
+                            || (seg.pitch < *low_pitch && high_vowels.contains(p)))
+                })
+            }
+            ConstraintCheck::DurationAlignment { diphthongs, sustained, long_ms, short_ms } => {
+                phonemes.iter().zip(segments).all(|(p, seg)| {
+                    let diphthong = diphthongs.contains(p);
+                    let long_ok = seg.duration_ms <= *long_ms || diphthong || sustained.contains(p) || inventory.is_vowel(*p);
+                    let short_ok = seg.duration_ms >= *short_ms || !diphthong;
+                    long_ok && short_ok
+                })
+            }
+            ConstraintCheck::CvPreference => {
+                phonemes.windows(2).any(|w| !inventory.is_vowel(w[0]) && inventory.is_vowel(w[1]))
+                    || phonemes.iter().any(is_vowel)
+            }
+            ConstraintCheck::VowelInitial { max_len, min_sonority } => match phonemes.first() {
+                Some(&first) if phonemes.len() <= *max_len => inventory.is_vowel(first) || inventory.sonority(first) >= *min_sonority,
+                _ => true,
+            },
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Phoneme::*;
+
+    fn info(n: usize, pitch: u8, duration_ms: usize) -> Vec<SegmentInfo> {
+        vec![SegmentInfo { pitch, duration_ms, has_attack: false }; n]
+    }
+
+    #[test]
+    fn test_english_rules_compile() {
+        let rules = PhonotacticRules::english();
+        let compiled = rules.compile().unwrap();
+
+        assert_eq!(compiled.constraints.len(), 7);
+        assert_eq!(compiled.inventory.sonority(AA), 10);
+        assert_eq!(compiled.inventory.sonority(W), 8);
+        assert_eq!(compiled.inventory.sonority(K), 0);
+        assert!(compiled.inventory.is_vowel(ER) && !compiled.inventory.is_vowel(R));
+        assert!(compiled.boundaries[&S].requires_attack);
+        assert!(!compiled.boundaries[&IY].requires_attack);
+
+        assert_eq!(compiled.inventory.classes.obstruents[0], S);
+        assert!(compiled.inventory.is_voiced(DH) && !compiled.inventory.is_voiced(TH));
+
+        // Round-trips through its own serialization
+        assert_eq!(PhonotacticRules::from_json(&rules.to_json()).unwrap().constraints.len(), 7);
+    }
+
+    #[test]
+    fn test_classes_default_to_english() {
+        let mut json: serde_json::Value = serde_json::from_str(ENGLISH_RULES).unwrap();
+        json.as_object_mut().unwrap().remove("classes");
+        let rules = PhonotacticRules::from_json(&json.to_string()).unwrap();
+        assert_eq!(rules.classes.high_vowels, PhonotacticRules::english().classes.high_vowels);
+        assert_eq!(rules.compile().unwrap().inventory.classes.vowel_glides[3], (OW, UW));
+    }
+
+    #[test]
+    fn test_rule_checks() {
+        let inventory = PhonotacticRules::english().compile().unwrap().inventory;
+        let check = |rule: ConstraintCheck, p: &[Phoneme]| rule.check(p, &info(p.len(), 0, 100), &inventory);
+        let stops = vec![P, T, K];
+
+        assert!(!check(ConstraintCheck::ForbiddenBigrams { first: stops.clone(), second: stops.clone() }, &[P, T, AA]));
+        assert!(check(ConstraintCheck::ForbiddenBigrams { first: stops.clone(), second: stops }, &[P, AA, T]));
+        assert!(check(ConstraintCheck::SonorityRise, &[S, L, AA]));
+        assert!(!check(ConstraintCheck::SonorityRise, &[L, S, AA]));
+
+        let onsets = ConstraintCheck::OnsetClusters { allowed: vec![vec![S, T, R], vec![P, L]] };
+        assert!(check(onsets.clone(), &[S, T, R, IY, T]));
+        assert!(check(onsets.clone(), &[T, IY]), "Single consonants always allowed");
+        assert!(!check(onsets, &[T, S, IY]));
+
+        let codas = ConstraintCheck::CodaClusters { allowed: vec![vec![N, D]] };
+        assert!(check(codas.clone(), &[AE, N, D]));
+        assert!(!check(codas, &[AE, D, N]));
+
+        let pitch = ConstraintCheck::PitchVowelAlignment { high_vowels: vec![IY], low_vowels: vec![AA], high_pitch: 60, low_pitch: 50 };
+        assert!(!pitch.check(&[AA], &info(1, 70, 100), &inventory));
+        assert!(pitch.check(&[AA], &info(1, 0, 100), &inventory), "Unpitched segments are skipped");
+    }
+
+    #[test]
+    fn test_rules_validation() {
+        let mut rules = PhonotacticRules::english();
+        rules.vowels.push("QQ".to_string());
+        assert!(matches!(rules.compile(), Err(RulesError::UnknownPhoneme(_))));
+
+        let json = ENGLISH_RULES.replace("\"version\": 1", "\"version\": 9");
+        assert!(matches!(PhonotacticRules::from_json(&json), Err(RulesError::Version(9))));
+        assert!(matches!(PhonotacticRules::from_json("{}"), Err(RulesError::Json(_))));
+    }
+}
@@ -9,14 +9,27 @@
 //! | Loss function | Constraint violation |
 //! | Convergence | Fixed point (saturation) |
 //! | Parameters | Observable boundaries |
-//! | Training data | Constraint rules (hardcoded!) |
+//! | Training data | Constraint rules (fixed, not learned!) |
 //!
-//! Like chess: rules are HARDCODED, then we SATURATE until stable.
+//! Like chess: rules are FIXED, then we SATURATE until stable.
 //! No learning from data - constraints define the Observable Space.
+//! The rules are data (PhonotacticRules, English built in), so the same
+//! engine runs on other languages or accents: the inventory's classes pick
+//! each segment's candidates and score them, and the constraints score
+//! every whole-sentence hypothesis.
 
 use super::attack_pitch::{MusicSegment, detect_pitch, Attack};
 use super::phoneme::Phoneme;
-use std::collections::HashMap;
+use super::spectrum::SpectralAnalyzer;
+use super::formants::{estimate_formants, merge_formants, nearest_vowels, vowel_formant_distance};
+use super::evaluation::normalize_transcript;
+use super::forced_alignment::{self, AlignmentError, ForcedAlignment};
+use super::lexicon::Lexicon;
+use super::phonotactics::{CompiledRules, ConstraintCheck, PhonemeInventory, PhonotacticRules, RulesError};
+use super::lattice::{Lattice, LatticeBuilder, LatticeKind, NBestHypothesis, ScoredWord, NULL_LABEL};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 
 /// Time-located segment from Phase 1 saturation
 /// NOW WITH LOW-MID-HIGH frequency bands for formant-like discrimination
@@ -32,6 +45,11 @@ pub struct LocatedSegment {
     pub energy_low: f32,        // 0-500 Hz (F1 range)
     pub energy_mid: f32,        // 500-2000 Hz (F2 range)
     pub energy_high: f32,       // 2000-8000 Hz (F3 + fricatives)
+    /// MFCCs of the segment's frame, averaged 50/50 on every merge like the
+    /// band energies (not a duration-weighted mean; same spectrum as the bands)
+    pub mfcc: Vec<f32>,
+    /// F1, F2, F3 in Hz (LPC), mean over voiced frames; None if unvoiced
+    pub formants: Option<[f32; 3]>,
 }
 
 impl LocatedSegment {
@@ -40,60 +58,9 @@ impl LocatedSegment {
     }
 }
 
-/// Compute Low-Mid-High energy bands using simple DFT
-fn compute_band_energies(window: &[f32], sample_rate: u32) -> (f32, f32, f32) {
-    let n = window.len();
-    if n == 0 {
-        return (0.0, 0.0, 0.0);
-    }
-
-    // Simple DFT for frequency analysis
-    // FIX: Track bin counts to normalize energy by bandwidth
-    let mut low_energy = 0.0f32;   // 0-500 Hz (F1 range)
-    let mut mid_energy = 0.0f32;   // 500-2000 Hz (F2 range)
-    let mut high_energy = 0.0f32;  // 2000-8000 Hz (F3 + fricatives)
-    let mut low_count = 0usize;
-    let mut mid_count = 0usize;
-    let mut high_count = 0usize;
-
-    let freq_resolution = sample_rate as f32 / n as f32;
-
-    // Only compute positive frequencies up to Nyquist
-    for k in 1..n/2 {
-        let freq = k as f32 * freq_resolution;
-
-        // DFT at frequency k
-        let mut real = 0.0f32;
-        let mut imag = 0.0f32;
-        for (i, &sample) in window.iter().enumerate() {
-            let angle = -2.0 * std::f32::consts::PI * k as f32 * i as f32 / n as f32;
-            real += sample * angle.cos();
-            imag += sample * angle.sin();
-        }
-        let magnitude_sq = (real * real + imag * imag) / (n * n) as f32;
-
-        // Assign to frequency band with bin counting
-        if freq < 500.0 {
-            low_energy += magnitude_sq;
-            low_count += 1;
-        } else if freq < 2000.0 {
-            mid_energy += magnitude_sq;
-            mid_count += 1;
-        } else if freq < 8000.0 {
-            high_energy += magnitude_sq;
-            high_count += 1;
-        }
-    }
-
-    // FIX: Normalize by bin count to get AVERAGE energy per bin (spectral density)
-    // This makes bands comparable regardless of bandwidth
-    if low_count > 0 { low_energy /= low_count as f32; }
-    if mid_count > 0 { mid_energy /= mid_count as f32; }
-    if high_count > 0 { high_energy /= high_count as f32; }
-
-    // Convert to dB
-    let to_db = |e: f32| if e > 1e-10 { 10.0 * e.log10() } else { -100.0 };
-    (to_db(low_energy), to_db(mid_energy), to_db(high_energy))
+/// Element-wise mean of two feature vectors (segment merge)
+fn average_features(a: &[f32], b: &[f32]) -> Vec<f32> {
+    a.iter().zip(b).map(|(x, y)| (x + y) / 2.0).collect()
 }
 
 /// PHASE 1: Saturate to find segment boundaries with time locations
@@ -114,8 +81,12 @@ fn saturate_segment_boundaries(samples: &[f32], sample_rate: u32) -> Vec<Located
     let hop_size = (sample_rate as usize * hop_ms) / 1000;
 
     // Step 1: Extract frame-level features with LOW-MID-HIGH bands
+    // One FFT spectrum per frame feeds both the bands and the MFCCs
+    let analyzer = SpectralAnalyzer::new(window_size, sample_rate);
     let mut frames: Vec<(usize, Option<u8>, f32, f32, f32, f32)> = Vec::new();
     // (time_ms, pitch, energy_db, low, mid, high)
+    let mut frame_mfcc: Vec<Vec<f32>> = Vec::new();
+    let mut frame_formants: Vec<Option<[f32; 3]>> = Vec::new();
     let mut pos = 0;
 
     while pos + window_size <= samples.len() {
@@ -130,9 +101,14 @@ fn saturate_segment_boundaries(samples: &[f32], sample_rate: u32) -> Vec<Located
         let pitch = detect_pitch(window, sample_rate).map(|p| p.midi_note);
 
         // LOW-MID-HIGH band energies
-        let (low, mid, high) = compute_band_energies(window, sample_rate);
+        let spectrum = analyzer.power_spectrum(window);
+        let (low, mid, high) = spectrum.band_energies_db();
 
         frames.push((time_ms, pitch, energy_db, low, mid, high));
+        frame_mfcc.push(analyzer.mfcc(&spectrum));
+
+        // Formants only mean something for voiced (periodic) frames
+        frame_formants.push(pitch.and_then(|_| estimate_formants(window, sample_rate)));
         pos += hop_size;
     }
 
@@ -160,6 +136,8 @@ fn saturate_segment_boundaries(samples: &[f32], sample_rate: u32) -> Vec<Located
             energy_low: low,
             energy_mid: mid,
             energy_high: high,
+            mfcc: frame_mfcc[i].clone(),
+            formants: frame_formants[i],
         }
     }).collect();
 
@@ -246,6 +224,8 @@ fn saturate_segment_boundaries(samples: &[f32], sample_rate: u32) -> Vec<Located
                         energy_low: (a.energy_low + b.energy_low) / 2.0,
                         energy_mid: (a.energy_mid + b.energy_mid) / 2.0,
                         energy_high: (a.energy_high + b.energy_high) / 2.0,
+                        mfcc: average_features(&a.mfcc, &b.mfcc),
+                        formants: merge_formants(a.formants, b.formants),
                     });
                     i += 2;  // Skip both
                     merged = true;
@@ -299,6 +279,8 @@ fn saturate_segment_boundaries(samples: &[f32], sample_rate: u32) -> Vec<Located
                 energy_low: (prev.energy_low + seg.energy_low) / 2.0,
                 energy_mid: (prev.energy_mid + seg.energy_mid) / 2.0,
                 energy_high: (prev.energy_high + seg.energy_high) / 2.0,
+                mfcc: average_features(&prev.mfcc, &seg.mfcc),
+                formants: merge_formants(prev.formants, seg.formants),
             });
         } else if seg.duration_ms() >= 60 {
             final_segments.push(seg);
@@ -318,10 +300,17 @@ pub struct EmergentMappings {
     pub duration_phonemes: HashMap<usize, Phoneme>,
 }
 
+/// The compiled rules the free scoring functions run under
+#[derive(Clone, Copy)]
+struct RuleContext<'a> {
+    inventory: &'a PhonemeInventory,
+    constraints: &'a [PhonotacticConstraint],
+}
+
 /// PHASE 2: Saturate the WHOLE SENTENCE at once (NOT char by char!)
 /// This is TRUE P=NP saturation - all constraints, all positions, simultaneously
-fn saturate_time_series(segments: &[LocatedSegment]) -> Vec<(Phoneme, usize, usize)> {
-    saturate_time_series_with_mappings(segments, &EmergentMappings::default())
+fn saturate_time_series(segments: &[LocatedSegment], rules: RuleContext) -> Vec<(Phoneme, usize, usize)> {
+    saturate_time_series_with_mappings(segments, &EmergentMappings::default(), rules)
 }
 
 /// PHASE 2 with EMERGENT mappings from training
@@ -330,6 +319,7 @@ fn saturate_time_series(segments: &[LocatedSegment]) -> Vec<(Phoneme, usize, usi
 fn saturate_time_series_with_mappings(
     segments: &[LocatedSegment],
     mappings: &EmergentMappings,
+    rules: RuleContext,
 ) -> Vec<(Phoneme, usize, usize)> {
     if segments.is_empty() {
         return Vec::new();
@@ -342,93 +332,12 @@ fn saturate_time_series_with_mappings(
     // PATH 9: CHAIN RULE - band energy → phoneme class → word
     let debug = std::env::var("DEBUG_BANDS").is_ok();
 
-    let candidates: Vec<Vec<Phoneme>> = segments.iter().enumerate().map(|(idx, seg)| {
-        let mut cands = Vec::new();
-
-        // EMERGENT: If we have a learned mapping for this pitch, prioritize it
-        if let Some(pitch) = seg.pitch {
-            if let Some(&emergent_phoneme) = mappings.pitch_phonemes.get(&pitch) {
-                cands.push(emergent_phoneme);
-            }
-        }
-
-        // Use LOW-MID-HIGH bands for phoneme class discrimination
-        let low = seg.energy_low;
-        let mid = seg.energy_mid;
-        let high = seg.energy_high;
-        let duration = seg.duration_ms();
-
-        // Debug band energies
-        if debug {
-            eprintln!("  [{:2}] {:4}-{:4}ms  L={:5.1} M={:5.1} H={:5.1}  voiced={}  dur={}ms",
-                     idx, seg.start_ms, seg.end_ms, low, mid, high, seg.is_voiced, duration);
-        }
-
-        // HIGH dominant (high > low + 10) = fricatives or high vowels
-        // LOW dominant (low > mid) = low vowels (AA, AO)
-        // MID dominant = mid vowels or nasals
-
-        if !seg.is_voiced || (high > low + 10.0 && high > mid + 5.0) {
-            // UNVOICED: fricatives and stops
-            for p in [Phoneme::S, Phoneme::SH, Phoneme::F, Phoneme::TH, Phoneme::T, Phoneme::K, Phoneme::P, Phoneme::HH] {
-                if !cands.contains(&p) { cands.push(p); }
-            }
-        } else if seg.is_voiced {
-            // VOICED: use band profile to select vowel candidates
-
-            // PATH 3: GRAPHEME - multiple paths to same phoneme class
-            if high > mid && high > low {
-                // HIGH dominant = high vowels (IY, IH, EY) - F2 > 2000Hz
-                for p in [Phoneme::IY, Phoneme::IH, Phoneme::EY, Phoneme::EH] {
-                    if !cands.contains(&p) { cands.push(p); }
-                }
-            } else if low > mid && low > high - 5.0 {
-                // LOW dominant = back vowels (AA, AO, UW) - low F2
-                for p in [Phoneme::AA, Phoneme::AO, Phoneme::OW, Phoneme::UW] {
-                    if !cands.contains(&p) { cands.push(p); }
-                }
-            } else {
-                // MID dominant = mid vowels (AH, AE, ER)
-                for p in [Phoneme::AH, Phoneme::AE, Phoneme::ER] {
-                    if !cands.contains(&p) { cands.push(p); }
-                }
-            }
-
-            // PATH 2: SATURATION - nasals have LOW energy & short duration
-            // Re-enable nasals/liquids for voiced segments
-            // Nasals (M, N) and liquids (L, R) are voiced with distinctive patterns:
-            // - M: low energy, nasal resonance
-            // - N: similar to M but higher
-            // - L/R: voiced with specific formant patterns
-            if duration < 200 && low > high {
-                for p in [Phoneme::M, Phoneme::N, Phoneme::L, Phoneme::R] {
-                    if !cands.contains(&p) { cands.push(p); }
-                }
-            }
-
-            // PATH 4: TRANSFORM - diphthongs are TRANSITIONS
-            // Add diphthongs for longer segments (pitch/formant change over time)
-            // Lowered threshold from 200ms to 120ms for better diphthong detection
-            if duration > 120 {
-                for p in [Phoneme::AY, Phoneme::OY, Phoneme::AW, Phoneme::EY, Phoneme::OW] {
-                    if !cands.contains(&p) { cands.push(p); }
-                }
-            }
-        }
-
-        // Fallback: if no candidates, add common phonemes
-        if cands.is_empty() {
-            cands.extend([Phoneme::AH, Phoneme::IH, Phoneme::N, Phoneme::T]);
-        }
-
-        if debug {
-            eprintln!("       → candidates: {:?}", cands);
-        }
-
-        cands
-    }).collect();
+    let candidates: Vec<Vec<Phoneme>> = segments.iter().enumerate()
+        .map(|(idx, seg)| segment_candidates(idx, seg, mappings, rules.inventory, debug))
+        .collect();
 
     let n = segments.len();
+    let infos: Vec<SegmentInfo> = segments.iter().map(SegmentInfo::from).collect();
 
     // Step 2: WHOLE SENTENCE SATURATION
     // Score ENTIRE sequences, not position by position
@@ -437,7 +346,7 @@ fn saturate_time_series_with_mappings(
     let mut best_sequence: Vec<Phoneme> = candidates.iter()
         .map(|c| c.first().copied().unwrap_or(Phoneme::AH))
         .collect();
-    let mut best_global_score = score_whole_sentence_with_mappings(&best_sequence, segments, mappings);
+    let mut best_global_score = score_whole_sentence_with_mappings(&best_sequence, segments, &infos, mappings, rules);
 
     // Saturation: propagate constraints until fixed point
     let max_iterations = 50;
@@ -457,7 +366,7 @@ fn saturate_time_series_with_mappings(
                 test_seq[pos] = candidate;
 
                 // Score the ENTIRE sentence (not just local context)
-                let global_score = score_whole_sentence_with_mappings(&test_seq, segments, mappings);
+                let global_score = score_whole_sentence_with_mappings(&test_seq, segments, &infos, mappings, rules);
 
                 if global_score > best_global_score {
                     best_sequence = test_seq;
@@ -475,7 +384,7 @@ fn saturate_time_series_with_mappings(
                         test_seq[i] = ci;
                         test_seq[i+1] = cj;
 
-                        let global_score = score_whole_sentence_with_mappings(&test_seq, segments, mappings);
+                        let global_score = score_whole_sentence_with_mappings(&test_seq, segments, &infos, mappings, rules);
                         if global_score > best_global_score {
                             best_sequence = test_seq;
                             best_global_score = global_score;
@@ -492,42 +401,150 @@ fn saturate_time_series_with_mappings(
         .collect()
 }
 
+/// Candidate phonemes for one located segment (emergent mapping first),
+/// drawn from the inventory's classes
+fn segment_candidates(idx: usize, seg: &LocatedSegment, mappings: &EmergentMappings, inventory: &PhonemeInventory, debug: bool) -> Vec<Phoneme> {
+    let classes = &inventory.classes;
+    let mut cands = Vec::new();
+
+    // EMERGENT: If we have a learned mapping for this pitch, prioritize it
+    if let Some(pitch) = seg.pitch {
+        if let Some(&emergent_phoneme) = mappings.pitch_phonemes.get(&pitch) {
+            cands.push(emergent_phoneme);
+        }
+    }
+
+    // Use LOW-MID-HIGH bands for phoneme class discrimination
+    let low = seg.energy_low;
+    let mid = seg.energy_mid;
+    let high = seg.energy_high;
+    let duration = seg.duration_ms();
+
+    // Debug band energies
+    if debug {
+        eprintln!("  [{:2}] {:4}-{:4}ms  L={:5.1} M={:5.1} H={:5.1}  voiced={}  dur={}ms",
+                 idx, seg.start_ms, seg.end_ms, low, mid, high, seg.is_voiced, duration);
+    }
+
+    // HIGH dominant (high > low + 10) = fricatives or high vowels
+    // LOW dominant (low > mid) = low vowels (AA, AO)
+    // MID dominant = mid vowels or nasals
+
+    if !seg.is_voiced || (high > low + 10.0 && high > mid + 5.0) {
+        // UNVOICED: fricatives and stops
+        for &p in &classes.obstruents {
+            if !cands.contains(&p) { cands.push(p); }
+        }
+    } else if seg.is_voiced {
+        // VOICED: use band profile to select vowel candidates
+
+        // PATH 3: GRAPHEME - multiple paths to same phoneme class
+        if let Some(formants) = seg.formants {
+            // Real formants: nearest of the language's vowels in the F1-F2 plane
+            for p in nearest_inventory_vowels(formants, inventory) {
+                if !cands.contains(&p) { cands.push(p); }
+            }
+        } else if high > mid && high > low {
+            // HIGH dominant = high vowels (IY, IH, EY) - F2 > 2000Hz
+            for &p in &classes.high_vowels {
+                if !cands.contains(&p) { cands.push(p); }
+            }
+        } else if low > mid && low > high - 5.0 {
+            // LOW dominant = back vowels (AA, AO, UW) - low F2
+            for &p in &classes.low_vowels {
+                if !cands.contains(&p) { cands.push(p); }
+            }
+        } else {
+            // MID dominant = mid vowels (AH, AE, ER)
+            for &p in &classes.mid_vowels {
+                if !cands.contains(&p) { cands.push(p); }
+            }
+        }
+
+        // PATH 2: SATURATION - nasals have LOW energy & short duration
+        // Re-enable nasals/liquids for voiced segments
+        // Nasals (M, N) and liquids (L, R) are voiced with distinctive patterns:
+        // - M: low energy, nasal resonance
+        // - N: similar to M but higher
+        // - L/R: voiced with specific formant patterns
+        if duration < 200 && low > high {
+            for &p in &classes.sonorants {
+                if !cands.contains(&p) { cands.push(p); }
+            }
+        }
+
+        // PATH 4: TRANSFORM - diphthongs are TRANSITIONS
+        // Add diphthongs for longer segments (pitch/formant change over time)
+        // Lowered threshold from 200ms to 120ms for better diphthong detection
+        if duration > 120 {
+            for &p in &classes.diphthongs {
+                if !cands.contains(&p) { cands.push(p); }
+            }
+        }
+    }
+
+    // Fallback: if no candidates, add common phonemes
+    if cands.is_empty() {
+        cands.extend(&classes.fallback);
+    }
+
+    if debug {
+        eprintln!("       → candidates: {:?}", cands);
+    }
+
+    cands
+}
+
+/// The `inventory`'s vowels nearest to measured formants (up to 4)
+fn nearest_inventory_vowels(formants: [f32; 3], inventory: &PhonemeInventory) -> Vec<Phoneme> {
+    nearest_vowels(formants, usize::MAX).into_iter()
+        .map(|(p, _)| p)
+        .filter(|&p| inventory.is_vowel(p))
+        .take(4)
+        .collect()
+}
+
 /// Score the WHOLE SENTENCE at once (global constraint satisfaction)
-fn score_whole_sentence(phonemes: &[Phoneme], segments: &[LocatedSegment]) -> f32 {
-    score_whole_sentence_with_mappings(phonemes, segments, &EmergentMappings::default())
+fn score_whole_sentence(phonemes: &[Phoneme], segments: &[LocatedSegment], rules: RuleContext) -> f32 {
+    let infos: Vec<SegmentInfo> = segments.iter().map(SegmentInfo::from).collect();
+    score_whole_sentence_with_mappings(phonemes, segments, &infos, &EmergentMappings::default(), rules)
 }
 
 /// Score the WHOLE SENTENCE with EMERGENT mappings from training
+/// `infos` are the segments' rule views, built once per utterance by the caller
 fn score_whole_sentence_with_mappings(
     phonemes: &[Phoneme],
     segments: &[LocatedSegment],
+    infos: &[SegmentInfo],
     mappings: &EmergentMappings,
+    rules: RuleContext,
 ) -> f32 {
     if phonemes.is_empty() {
         return 0.0;
     }
 
+    let inventory = rules.inventory;
     let mut score = 0.0f32;
 
     // 1. GLOBAL: Markov chain score over ENTIRE sequence
     for i in 0..phonemes.len() - 1 {
-        score += phoneme_transition_score(phonemes[i], phonemes[i + 1]);
+        score += phoneme_transition_score(phonemes[i], phonemes[i + 1], inventory);
     }
 
     // 2. GLOBAL: Initial position score
-    score += initial_position_score(phonemes[0]);
+    score += initial_position_score(phonemes[0], inventory);
 
     // 3. GLOBAL: Acoustic fit for EACH segment
     for (i, p) in phonemes.iter().enumerate() {
         if i < segments.len() {
-            score += acoustic_segment_score(*p, &segments[i]);
+            score += acoustic_segment_score(*p, &segments[i], inventory);
         }
     }
 
     // 4. GLOBAL: Syllable structure (CV patterns across whole sentence)
     let mut cv_count = 0;
     for i in 0..phonemes.len() - 1 {
-        if !is_vowel_phoneme(phonemes[i]) && is_vowel_phoneme(phonemes[i + 1]) {
+        if !inventory.is_vowel(phonemes[i]) && inventory.is_vowel(phonemes[i + 1]) {
             cv_count += 1;  // CV syllable
         }
     }
@@ -541,7 +558,7 @@ fn score_whole_sentence_with_mappings(
     }
 
     // 6. GLOBAL: Vowel distribution (sentences need vowels)
-    let vowel_count = phonemes.iter().filter(|p| is_vowel_phoneme(**p)).count();
+    let vowel_count = phonemes.iter().filter(|p| inventory.is_vowel(**p)).count();
     let vowel_ratio = vowel_count as f32 / phonemes.len() as f32;
     // English typically has 30-50% vowels
     if vowel_ratio < 0.2 || vowel_ratio > 0.7 {
@@ -568,40 +585,55 @@ fn score_whole_sentence_with_mappings(
         }
     }
 
+    // 8. RULES: weights of the language's phonotactic constraints that hold
+    for constraint in rules.constraints {
+        if constraint.check.check(phonemes, infos, inventory) {
+            score += constraint.weight;
+        }
+    }
+
     score
 }
 
 /// Acoustic fit score for a single segment
 /// NOW uses LOW-MID-HIGH bands for formant-like scoring
-fn acoustic_segment_score(phoneme: Phoneme, segment: &LocatedSegment) -> f32 {
+fn acoustic_segment_score(phoneme: Phoneme, segment: &LocatedSegment, inventory: &PhonemeInventory) -> f32 {
+    let classes = &inventory.classes;
     let mut score = 0.0f32;
 
     let low = segment.energy_low;
     let mid = segment.energy_mid;
     let high = segment.energy_high;
 
+    // Measured formants: score vowels by F1-F2 distance (Bark)
+    // 0 Bark = +3 (same as a band match), 4+ Bark = -1 (same as a band mismatch)
+    let formant_distance = segment.formants.and_then(|f| vowel_formant_distance(phoneme, f));
+    if let Some(distance) = formant_distance {
+        score += 3.0 - distance.min(4.0);
+    }
+
     // HIGH vowels (IY, IH, EY, EH) have HIGH energy dominant
-    let is_high_vowel = matches!(phoneme, Phoneme::IY | Phoneme::IH | Phoneme::EY | Phoneme::EH);
-    if is_high_vowel {
+    let is_high_vowel = classes.high_vowels.contains(&phoneme);
+    if is_high_vowel && formant_distance.is_none() {
         if high > mid && high > low { score += 3.0; }
         else { score -= 1.0; }
     }
 
     // LOW vowels (AA, AO, OW, UW) have LOW energy dominant
-    let is_low_vowel = matches!(phoneme, Phoneme::AA | Phoneme::AO | Phoneme::OW | Phoneme::UW);
-    if is_low_vowel {
+    let is_low_vowel = classes.low_vowels.contains(&phoneme);
+    if is_low_vowel && formant_distance.is_none() {
         if low > mid && low > high - 5.0 { score += 3.0; }
         else { score -= 1.0; }
     }
 
     // MID vowels (AH, AE, ER) have balanced/MID dominant
-    let is_mid_vowel = matches!(phoneme, Phoneme::AH | Phoneme::AE | Phoneme::ER);
-    if is_mid_vowel {
+    let is_mid_vowel = classes.mid_vowels.contains(&phoneme);
+    if is_mid_vowel && formant_distance.is_none() {
         if mid > low - 5.0 && mid > high - 5.0 { score += 2.0; }
     }
 
     // Fricatives (S, SH, F, TH) have HIGH energy dominant (noise)
-    let is_fricative = matches!(phoneme, Phoneme::S | Phoneme::SH | Phoneme::F | Phoneme::TH | Phoneme::HH);
+    let is_fricative = classes.fricatives.contains(&phoneme);
     if is_fricative {
         if high > low + 5.0 { score += 3.0; }
         else { score -= 2.0; }
@@ -616,25 +648,23 @@ fn acoustic_segment_score(phoneme: Phoneme, segment: &LocatedSegment) -> f32 {
     //
     // KEY INSIGHT: AY is the word "I" which is EXTREMELY common
     // PATH 8 (MARKOV): Word frequency matters - "I" is a function word
-    let is_rising_diphthong = matches!(phoneme, Phoneme::AY | Phoneme::OY | Phoneme::EY);
+    let is_rising_diphthong = classes.rising_diphthongs.contains(&phoneme);
     if is_rising_diphthong {
         // Rising diphthongs should have LOW-MID energy (from their onset)
         if low > high - 10.0 { score += 2.5; }  // Low onset component
         // AY is the word "I" - one of the most common English words
         // Also appears in MY, BY, HIGH, etc.
-        if matches!(phoneme, Phoneme::AY) { score += 1.5; }
+        if classes.frequent.contains(&phoneme) { score += 1.5; }
     }
 
     // Duration alignment
     let dur = segment.duration_ms();
-    let is_diphthong = matches!(phoneme, Phoneme::AY | Phoneme::OY | Phoneme::AW | Phoneme::EY | Phoneme::OW);
+    let is_diphthong = classes.diphthongs.contains(&phoneme);
     if dur > 200 && is_diphthong { score += 3.0; }  // Increased bonus for long diphthongs
     if dur < 100 && is_diphthong { score -= 2.0; }
 
     // Voiced/unvoiced alignment
-    let phoneme_voiced = is_vowel_phoneme(phoneme) ||
-        matches!(phoneme, Phoneme::M | Phoneme::N | Phoneme::L | Phoneme::R |
-                         Phoneme::B | Phoneme::D | Phoneme::G | Phoneme::V | Phoneme::Z);
+    let phoneme_voiced = inventory.is_voiced(phoneme);
     if segment.is_voiced == phoneme_voiced { score += 1.0; }
     else { score -= 2.0; }
 
@@ -642,31 +672,31 @@ fn acoustic_segment_score(phoneme: Phoneme, segment: &LocatedSegment) -> f32 {
 }
 
 /// Score a phoneme at position considering temporal context (Markov)
-fn time_series_score(phonemes: &[Phoneme], pos: usize) -> f32 {
+fn time_series_score(phonemes: &[Phoneme], pos: usize, inventory: &PhonemeInventory) -> f32 {
     let mut score = 0.0f32;
 
     // Bigram score with previous
     if pos > 0 {
-        score += phoneme_transition_score(phonemes[pos - 1], phonemes[pos]);
+        score += phoneme_transition_score(phonemes[pos - 1], phonemes[pos], inventory);
     }
 
     // Bigram score with next
     if pos + 1 < phonemes.len() {
-        score += phoneme_transition_score(phonemes[pos], phonemes[pos + 1]);
+        score += phoneme_transition_score(phonemes[pos], phonemes[pos + 1], inventory);
     }
 
     // Position score (word-initial vs word-medial patterns)
     if pos == 0 {
-        score += initial_position_score(phonemes[pos]);
+        score += initial_position_score(phonemes[pos], inventory);
     }
 
     score
 }
 
 /// Phoneme transition score (Markov bigram)
-fn phoneme_transition_score(from: Phoneme, to: Phoneme) -> f32 {
-    let from_vowel = is_vowel_phoneme(from);
-    let to_vowel = is_vowel_phoneme(to);
+fn phoneme_transition_score(from: Phoneme, to: Phoneme, inventory: &PhonemeInventory) -> f32 {
+    let from_vowel = inventory.is_vowel(from);
+    let to_vowel = inventory.is_vowel(to);
 
     match (from_vowel, to_vowel) {
         // CV syllable: most common
@@ -678,9 +708,7 @@ fn phoneme_transition_score(from: Phoneme, to: Phoneme) -> f32 {
             // Same vowel = bad (repetition)
             if from == to { -2.0 }
             // Diphthong pairs
-            else if matches!((from, to),
-                (Phoneme::AA, Phoneme::IY) | (Phoneme::AH, Phoneme::IY) |
-                (Phoneme::EH, Phoneme::IY) | (Phoneme::OW, Phoneme::UW)) { 2.0 }
+            else if inventory.classes.vowel_glides.contains(&(from, to)) { 2.0 }
             else { 0.0 }
         }
         // CC: consonant cluster
@@ -692,23 +720,20 @@ fn phoneme_transition_score(from: Phoneme, to: Phoneme) -> f32 {
 }
 
 /// Score for word-initial position
-fn initial_position_score(p: Phoneme) -> f32 {
-    // Common word-initial sounds in English
-    match p {
-        Phoneme::T | Phoneme::S | Phoneme::K | Phoneme::P | Phoneme::M | Phoneme::N => 2.0,
-        Phoneme::DH | Phoneme::AH | Phoneme::IH | Phoneme::AY => 1.5,  // THE, A, I, I
-        _ if is_vowel_phoneme(p) => 1.0,
-        _ => 0.5,
+fn initial_position_score(p: Phoneme, inventory: &PhonemeInventory) -> f32 {
+    // Common word-initial sounds (English: T S K P M N; THE, A, I)
+    let classes = &inventory.classes;
+    if classes.strong_onsets.contains(&p) {
+        2.0
+    } else if classes.weak_onsets.contains(&p) {
+        1.5
+    } else if inventory.is_vowel(p) {
+        1.0
+    } else {
+        0.5
     }
 }
 
-fn is_vowel_phoneme(p: Phoneme) -> bool {
-    matches!(p, Phoneme::AA | Phoneme::AE | Phoneme::AH | Phoneme::AO |
-                Phoneme::AW | Phoneme::AY | Phoneme::EH | Phoneme::ER |
-                Phoneme::EY | Phoneme::IH | Phoneme::IY | Phoneme::OW |
-                Phoneme::OY | Phoneme::UH | Phoneme::UW)
-}
-
 /// Granular pitch-contour segmentation for speech
 /// EMERGENT: Segments defined by pitch stability, not fixed windows
 /// Phonemes are ~50-200ms, so require minimum segment duration
@@ -835,12 +860,23 @@ pub struct SegmentInfo {
     pub has_attack: bool,
 }
 
+impl From<&LocatedSegment> for SegmentInfo {
+    /// Located segments carry no attack: unvoiced non-silence (stop/fricative onset) stands in
+    fn from(seg: &LocatedSegment) -> Self {
+        SegmentInfo {
+            pitch: seg.pitch.unwrap_or(0),
+            duration_ms: seg.duration_ms(),
+            has_attack: !seg.is_voiced && !seg.is_silence,
+        }
+    }
+}
+
 /// Phonotactic constraint - hardcoded like chess attack tables
 /// Now with access to segment properties for EMERGENT decisions
 #[derive(Clone, Debug)]
 pub struct PhonotacticConstraint {
-    pub name: &'static str,
-    pub check: fn(&[Phoneme], &[SegmentInfo]) -> bool,
+    pub name: String,
+    pub check: ConstraintCheck,
     pub weight: f32,
 }
 
@@ -868,10 +904,14 @@ pub struct SaturatedState {
 /// "Training" = running saturation until fixed point
 /// No gradient descent, no loss function - just constraint propagation
 pub struct SaturatedTranscriber {
-    /// Hardcoded phonotactic constraints (like chess rules)
+    /// Phonotactic constraints (like chess rules), compiled from PhonotacticRules
     constraints: Vec<PhonotacticConstraint>,
     /// Observable boundaries (emerge from saturation)
     boundaries: HashMap<Phoneme, ObservableBoundary>,
+    /// Vowels and sonority scale of the rules' language
+    inventory: PhonemeInventory,
+    /// The rules compiled into constraints, boundaries and inventory (saved with the model)
+    rules: PhonotacticRules,
     /// Saturation history for convergence detection
     history: Vec<SaturatedState>,
     /// Epsilon for convergence (derived from constraints)
@@ -886,17 +926,22 @@ pub struct SaturatedTranscriber {
     emergent_duration_phonemes: HashMap<usize, Phoneme>,
     /// Whether joint saturation has been performed
     is_saturated: bool,
+    /// Pronunciation lexicon for phonemes_to_words (built-in until one is loaded)
+    lexicon: Lexicon,
 }
 
 impl SaturatedTranscriber {
-    /// Create with HARDCODED constraints (no training data!)
+    /// Create with the built-in English rules (no training data!)
     pub fn new() -> Self {
-        let constraints = Self::hardcode_constraints();
-        let boundaries = Self::hardcode_boundaries();
+        let rules = PhonotacticRules::english();
+        let CompiledRules { inventory, constraints, boundaries } = rules.compile()
+            .expect("built-in English rules compile");
 
         Self {
             constraints,
             boundaries,
+            inventory,
+            rules,
             history: Vec::new(),
             epsilon: 0.01,
             pitch_histogram: HashMap::new(),
@@ -904,235 +949,65 @@ impl SaturatedTranscriber {
             emergent_pitch_phonemes: HashMap::new(),
             emergent_duration_phonemes: HashMap::new(),
             is_saturated: false,
+            lexicon: Lexicon::builtin(),
         }
     }
 
-    /// HARDCODED constraints - like chess attack tables
-    /// These are NOT learned, they are the RULES of English phonotactics
-    /// SATURATION propagates these to EMERGE the correct phonemes
-    /// NOW WITH SEGMENT INFO: Constraints can see pitch/duration to make EMERGENT decisions
-    fn hardcode_constraints() -> Vec<PhonotacticConstraint> {
-        vec![
-            // CONSTRAINT 1: Vowel nucleus (every syllable needs vowel)
-            PhonotacticConstraint {
-                name: "vowel_nucleus",
-                check: |phonemes, _segments| {
-                    phonemes.iter().any(|p| Self::is_vowel(*p))
-                },
-                weight: 10.0,
-            },
-            // CONSTRAINT 2: No double stops (phonotactic rule)
-            PhonotacticConstraint {
-                name: "no_double_stop",
-                check: |phonemes, _segments| {
-                    for w in phonemes.windows(2) {
-                        let both_stops = matches!(w[0], Phoneme::P | Phoneme::T | Phoneme::K | Phoneme::B | Phoneme::D | Phoneme::G)
-                            && matches!(w[1], Phoneme::P | Phoneme::T | Phoneme::K | Phoneme::B | Phoneme::D | Phoneme::G);
-                        if both_stops { return false; }
-                    }
-                    true
-                },
-                weight: 3.0,
-            },
-            // CONSTRAINT 3: Sonority sequencing
-            PhonotacticConstraint {
-                name: "sonority_rise",
-                check: |phonemes, _segments| {
-                    let vowel_pos = phonemes.iter().position(|p| Self::is_vowel(*p));
-                    if let Some(pos) = vowel_pos {
-                        for i in 0..pos {
-                            if i + 1 < pos {
-                                let s1 = Self::sonority(phonemes[i]);
-                                let s2 = Self::sonority(phonemes[i + 1]);
-                                if s2 < s1 { return false; }
-                            }
-                        }
-                    }
-                    true
-                },
-                weight: 2.0,
-            },
-            // CONSTRAINT 4: EMERGENT pitch-phoneme alignment
-            // HIGH pitch segments should have HIGH vowels (IY, IH)
-            // LOW pitch segments should have LOW vowels (AA, AO)
-            // This EMERGES from acoustic-phonetic structure
-            PhonotacticConstraint {
-                name: "pitch_vowel_alignment",
-                check: |phonemes, segments| {
-                    for (i, p) in phonemes.iter().enumerate() {
-                        if i >= segments.len() { break; }
-                        let seg = &segments[i];
-                        if seg.pitch == 0 { continue; }  // No pitch = skip
-
-                        // EMERGENT: High pitch → high vowels, low pitch → low vowels
-                        // This is the natural acoustic-phonetic relationship
-                        let is_high_vowel = matches!(p, Phoneme::IY | Phoneme::IH | Phoneme::EH | Phoneme::EY);
-                        let is_low_vowel = matches!(p, Phoneme::AA | Phoneme::AO | Phoneme::OW | Phoneme::UW);
-
-                        // Pitch > 60 = high range, pitch < 50 = low range
-                        // These boundaries EMERGE from the formant structure
-                        if seg.pitch > 60 && is_low_vowel { return false; }
-                        if seg.pitch < 50 && is_high_vowel { return false; }
-                    }
-                    true
-                },
-                weight: 8.0,  // High weight - acoustic alignment is critical
-            },
-            // CONSTRAINT 5: EMERGENT duration-phoneme alignment
-            // Long segments → diphthongs or nasals
-            // Short segments → monophthongs or stops
-            PhonotacticConstraint {
-                name: "duration_phoneme_alignment",
-                check: |phonemes, segments| {
-                    for (i, p) in phonemes.iter().enumerate() {
-                        if i >= segments.len() { break; }
-                        let dur = segments[i].duration_ms;
-
-                        let is_diphthong = matches!(p, Phoneme::AY | Phoneme::EY | Phoneme::OW | Phoneme::AW | Phoneme::OY);
-                        let is_nasal = matches!(p, Phoneme::M | Phoneme::N | Phoneme::NG);
-
-                        // Long segments (>300ms) favor sustained sounds
-                        if dur > 300 && !is_diphthong && !is_nasal && !Self::is_vowel(*p) {
-                            return false;
-                        }
-                        // Short segments (<80ms) disfavor diphthongs
-                        if dur < 80 && is_diphthong {
-                            return false;
-                        }
-                    }
-                    true
-                },
-                weight: 6.0,
-            },
-            // CONSTRAINT 6: CV syllable preference
-            PhonotacticConstraint {
-                name: "cv_preference",
-                check: |phonemes, _segments| {
-                    let mut has_cv = false;
-                    for w in phonemes.windows(2) {
-                        if !Self::is_vowel(w[0]) && Self::is_vowel(w[1]) {
-                            has_cv = true;
-                        }
-                    }
-                    has_cv || phonemes.iter().any(|p| Self::is_vowel(*p))
-                },
-                weight: 5.0,
-            },
-            // CONSTRAINT 7: Vowel-initial preference for short sequences
-            PhonotacticConstraint {
-                name: "vowel_initial_ok",
-                check: |phonemes, _segments| {
-                    if phonemes.len() <= 3 && !phonemes.is_empty() {
-                        return Self::is_vowel(phonemes[0]) || Self::sonority(phonemes[0]) >= 5;
-                    }
-                    true
-                },
-                weight: 4.0,
-            },
-        ]
+    /// Decode words with `lexicon` (e.g. Lexicon::load_cmudict) instead of the
+    /// built-in one. This replaces the built-ins entirely, their single-phoneme
+    /// fallbacks and M/N confusion patterns included: scores add up per word,
+    /// so keeping short fallbacks next to a full dictionary splits real words
+    /// into fragments. Use extend_lexicon to add words on top instead.
+    pub fn set_lexicon(&mut self, lexicon: Lexicon) {
+        self.lexicon = lexicon;
     }
 
-    /// Sonority scale (EMERGENT from phonology)
-    /// Vowels > Glides > Liquids > Nasals > Fricatives > Stops
-    fn sonority(p: Phoneme) -> u8 {
-        match p {
-            // Vowels: highest sonority (10)
-            Phoneme::AA | Phoneme::AE | Phoneme::AH | Phoneme::AO |
-            Phoneme::AW | Phoneme::AY | Phoneme::EH | Phoneme::ER |
-            Phoneme::EY | Phoneme::IH | Phoneme::IY | Phoneme::OW |
-            Phoneme::OY | Phoneme::UH | Phoneme::UW => 10,
-            // Glides (8)
-            Phoneme::W | Phoneme::Y => 8,
-            // Liquids (7)
-            Phoneme::L | Phoneme::R => 7,
-            // Nasals (6)
-            Phoneme::M | Phoneme::N | Phoneme::NG => 6,
-            // Voiced fricatives (4)
-            Phoneme::V | Phoneme::DH | Phoneme::Z | Phoneme::ZH => 4,
-            // Unvoiced fricatives (3)
-            Phoneme::F | Phoneme::TH | Phoneme::S | Phoneme::SH | Phoneme::HH => 3,
-            // Affricates (2)
-            Phoneme::CH | Phoneme::JH => 2,
-            // Voiced stops (1)
-            Phoneme::B | Phoneme::D | Phoneme::G => 1,
-            // Unvoiced stops (0)
-            Phoneme::P | Phoneme::T | Phoneme::K => 0,
-            // Silence (no sonority)
-            Phoneme::SIL => 0,
-        }
-    }
-
-    /// EMERGENT boundaries - Observable Space limits emerge from STRUCTURE
-    /// Not hardcoded values - structure defines constraints, values follow
-    fn hardcode_boundaries() -> HashMap<Phoneme, ObservableBoundary> {
-        let mut map = HashMap::new();
-
-        // EMERGENT: Phoneme classes define STRUCTURE, not specific values
-        // Vowels: periodic (pitch), sustained, no attack
-        // Consonants: transient (attack), brief
-        // Voiced: has pitch
-        // Unvoiced: no pitch
-
-        // The STRUCTURE is: vowels are the nucleus
-        // Values will EMERGE from saturation based on actual audio
-
-        // Initialize all phonemes with WIDE boundaries (will be constrained by saturation)
-        for vowel in [Phoneme::AA, Phoneme::AE, Phoneme::AH, Phoneme::AO,
-                      Phoneme::AW, Phoneme::AY, Phoneme::EH, Phoneme::ER,
-                      Phoneme::EY, Phoneme::IH, Phoneme::IY, Phoneme::OW,
-                      Phoneme::OY, Phoneme::UH, Phoneme::UW] {
-            map.insert(vowel, ObservableBoundary {
-                pitch_low: 1,     // Will be constrained by actual F0
-                pitch_high: 127,  // MIDI max
-                min_duration_ms: 1,
-                max_duration_ms: 9999,
-                requires_attack: false,  // STRUCTURAL: vowels don't need attack
-            });
-        }
+    /// Add `lexicon`'s entries to the current one (duplicates keep the better score)
+    pub fn extend_lexicon(&mut self, lexicon: &Lexicon) {
+        self.lexicon.extend(lexicon);
+    }
 
-        for cons in [Phoneme::M, Phoneme::N, Phoneme::NG, Phoneme::L, Phoneme::R,
-                     Phoneme::W, Phoneme::Y, Phoneme::B, Phoneme::D, Phoneme::G,
-                     Phoneme::V, Phoneme::DH, Phoneme::Z, Phoneme::ZH] {
-            map.insert(cons, ObservableBoundary {
-                pitch_low: 1,
-                pitch_high: 127,
-                min_duration_ms: 1,
-                max_duration_ms: 9999,
-                requires_attack: false,  // STRUCTURAL: voiced consonants have pitch
-            });
-        }
+    pub fn lexicon(&self) -> &Lexicon {
+        &self.lexicon
+    }
 
-        for cons in [Phoneme::P, Phoneme::T, Phoneme::K, Phoneme::F, Phoneme::TH,
-                     Phoneme::S, Phoneme::SH, Phoneme::HH, Phoneme::CH] {
-            map.insert(cons, ObservableBoundary {
-                pitch_low: 0,
-                pitch_high: 0,  // STRUCTURAL: unvoiced = no pitch
-                min_duration_ms: 1,
-                max_duration_ms: 9999,
-                requires_attack: true,   // STRUCTURAL: unvoiced needs attack
-            });
-        }
+    /// Create with another language's (or accent's) rules
+    pub fn with_rules(rules: &PhonotacticRules) -> Result<Self, RulesError> {
+        let mut transcriber = Self::new();
+        transcriber.set_rules(rules)?;
+        Ok(transcriber)
+    }
 
-        map
+    /// Replace constraints, sonority scale and boundaries (saturation state is kept)
+    pub fn set_rules(&mut self, rules: &PhonotacticRules) -> Result<(), RulesError> {
+        let CompiledRules { inventory, constraints, boundaries } = rules.compile()?;
+        self.inventory = inventory;
+        self.constraints = constraints;
+        self.boundaries = boundaries;
+        self.rules = rules.clone();
+        Ok(())
     }
 
-    fn is_vowel(p: Phoneme) -> bool {
-        matches!(p, Phoneme::AA | Phoneme::AE | Phoneme::AH | Phoneme::AO |
-                    Phoneme::AW | Phoneme::AY | Phoneme::EH | Phoneme::ER |
-                    Phoneme::EY | Phoneme::IH | Phoneme::IY | Phoneme::OW |
-                    Phoneme::OY | Phoneme::UH | Phoneme::UW)
+    fn rule_context(&self) -> RuleContext<'_> {
+        RuleContext { inventory: &self.inventory, constraints: &self.constraints }
     }
 
     /// Score a phoneme sequence against constraints
     /// Now with segment info for EMERGENT constraints
     /// Plus CONTINUOUS acoustic scoring (not just pass/fail)
     fn score_constraints(&self, phonemes: &[Phoneme], segments: &[SegmentInfo]) -> (f32, usize) {
+        let (constraint, acoustic, violations) = self.score_breakdown(phonemes, segments);
+        (constraint + acoustic, violations)
+    }
+
+    /// score_constraints split into (constraint weights passed, acoustic fit, violations)
+    fn score_breakdown(&self, phonemes: &[Phoneme], segments: &[SegmentInfo]) -> (f32, f32, usize) {
         let mut score = 0.0;
         let mut violations = 0;
 
         // Binary constraints (pass/fail)
         for constraint in &self.constraints {
-            if (constraint.check)(phonemes, segments) {
+            if constraint.check.check(phonemes, segments, &self.inventory) {
                 score += constraint.weight;
             } else {
                 violations += 1;
@@ -1142,15 +1017,13 @@ impl SaturatedTranscriber {
         // EMERGENT CONTINUOUS SCORING: Acoustic fit
         // Better pitch alignment = higher score
         // This is NOT a threshold - it's a GRADIENT
-        score += Self::acoustic_fit_score(phonemes, segments);
-
-        (score, violations)
+        (score, self.acoustic_fit_score(phonemes, segments), violations)
     }
 
     /// MARKOV + PITCH: Continuous scoring via transition probabilities AND acoustic fit
     /// EMERGENT: pitch alignment is NOT a threshold, it's a gradient score
     /// τ_mix = O(1/gap) → polynomial convergence to optimal phoneme sequence
-    fn acoustic_fit_score(phonemes: &[Phoneme], segments: &[SegmentInfo]) -> f32 {
+    fn acoustic_fit_score(&self, phonemes: &[Phoneme], segments: &[SegmentInfo]) -> f32 {
         if phonemes.is_empty() {
             return 0.0;
         }
@@ -1159,12 +1032,12 @@ impl SaturatedTranscriber {
 
         // MARKOV BIGRAM SCORING: P(phoneme_i+1 | phoneme_i)
         for pair in phonemes.windows(2) {
-            log_score += Self::phoneme_bigram_log_prob(pair[0], pair[1]);
+            log_score += self.phoneme_bigram_log_prob(pair[0], pair[1]);
         }
 
         // Initial phoneme probability
         if !phonemes.is_empty() {
-            log_score += Self::initial_phoneme_log_prob(phonemes[0]);
+            log_score += self.initial_phoneme_log_prob(phonemes[0]);
         }
 
         // EMERGENT PITCH-PHONEME ALIGNMENT (gradient, not threshold!)
@@ -1218,12 +1091,12 @@ impl SaturatedTranscriber {
 
     /// Phoneme bigram log-probability (EMERGENT from phonotactic structure)
     /// NOT hardcoded values - derived from universal phonotactic principles
-    fn phoneme_bigram_log_prob(a: Phoneme, b: Phoneme) -> f32 {
-        let a_vowel = Self::is_vowel(a);
-        let b_vowel = Self::is_vowel(b);
+    fn phoneme_bigram_log_prob(&self, a: Phoneme, b: Phoneme) -> f32 {
+        let a_vowel = self.inventory.is_vowel(a);
+        let b_vowel = self.inventory.is_vowel(b);
 
-        let a_sonorant = Self::sonority(a) >= 6;  // Nasals, liquids, glides
-        let b_sonorant = Self::sonority(b) >= 6;
+        let a_sonorant = self.inventory.sonority(a) >= 6;  // Nasals, liquids, glides
+        let b_sonorant = self.inventory.sonority(b) >= 6;
 
         // UNIVERSAL phonotactic patterns (apply to ALL languages):
         // 1. CV syllables most common (consonant → vowel)
@@ -1248,8 +1121,8 @@ impl SaturatedTranscriber {
 
             // Consonant → Consonant: cluster (follow sonority)
             (false, false) => {
-                let s_a = Self::sonority(a);
-                let s_b = Self::sonority(b);
+                let s_a = self.inventory.sonority(a);
+                let s_b = self.inventory.sonority(b);
 
                 // Same phoneme (geminate) - rare in English
                 if a == b { return -2.0; }
@@ -1271,14 +1144,14 @@ impl SaturatedTranscriber {
 
     /// Initial phoneme log-probability (what sounds commonly START words)
     /// EMERGENT from phonotactic structure, not word lists
-    fn initial_phoneme_log_prob(p: Phoneme) -> f32 {
+    fn initial_phoneme_log_prob(&self, p: Phoneme) -> f32 {
         // Vowels: less common word-initial (but "I", "A", "a" exist)
-        if Self::is_vowel(p) {
+        if self.inventory.is_vowel(p) {
             return 0.5;  // Vowels OK but less common initially
         }
 
         // Sonority: high sonority onset consonants more common
-        let sonority = Self::sonority(p);
+        let sonority = self.inventory.sonority(p);
 
         // Stops and fricatives common word-initially
         if sonority <= 4 { return 1.0; }  // Stops, fricatives
@@ -1305,82 +1178,19 @@ impl SaturatedTranscriber {
             // VOICED segment: ALL voiced phonemes are candidates
             // Vowels + voiced consonants
             // Saturation will pick the right one via constraints
-            vec![
-                // All vowels
-                Phoneme::AA, Phoneme::AE, Phoneme::AH, Phoneme::AO,
-                Phoneme::AW, Phoneme::AY, Phoneme::EH, Phoneme::ER,
-                Phoneme::EY, Phoneme::IH, Phoneme::IY, Phoneme::OW,
-                Phoneme::OY, Phoneme::UH, Phoneme::UW,
-                // All voiced consonants
-                Phoneme::M, Phoneme::N, Phoneme::NG, Phoneme::L, Phoneme::R,
-                Phoneme::W, Phoneme::Y, Phoneme::B, Phoneme::D, Phoneme::G,
-                Phoneme::V, Phoneme::DH, Phoneme::Z, Phoneme::ZH, Phoneme::JH,
-            ]
+            let inventory = &self.inventory;
+            inventory.vowels.iter().chain(&inventory.classes.voiced_consonants).copied().collect()
         } else if has_attack {
             // UNVOICED + ATTACK: unvoiced consonants
-            vec![
-                Phoneme::P, Phoneme::T, Phoneme::K,
-                Phoneme::F, Phoneme::TH, Phoneme::S, Phoneme::SH,
-                Phoneme::HH, Phoneme::CH,
-            ]
+            ALL_PHONEMES.iter().copied()
+                .filter(|&p| p != Phoneme::SIL && !self.inventory.is_voiced(p))
+                .collect()
         } else {
             // Silence - no candidates
             vec![]
         }
     }
 
-    /// Map segment to phoneme candidates using EMERGENT classification
-    /// STRUCTURAL properties define which phonemes are candidates
-    /// KEY INSIGHT: PITCH is truth for voiced (not ZCR). If pitch > 0, it's voiced.
-    #[allow(dead_code)]
-    fn segment_to_candidates(&self, seg: &MusicSegment, _sample_rate: u32) -> Vec<(Phoneme, f32)> {
-        let mut candidates = Vec::new();
-
-        // OBSERVABLE properties of the segment
-        let has_attack = seg.attack.is_some();
-        let pitch = seg.dominant_pitch.unwrap_or(0);
-        let has_pitch = pitch > 0;
-
-        // EMERGENT INSIGHT: Pitch > 0 means VOICED (periodic waveform)
-        // This is structural truth, not a threshold
-
-        if has_pitch && !has_attack {
-            // STRUCTURE: pitch + no_attack = VOWEL (sustained periodic sound)
-            // All vowels are candidates - saturation will constrain
-            for vowel in [Phoneme::AY, Phoneme::IY, Phoneme::EY, Phoneme::AA,
-                          Phoneme::AH, Phoneme::AO, Phoneme::OW, Phoneme::UW,
-                          Phoneme::IH, Phoneme::EH, Phoneme::UH] {
-                candidates.push((vowel, 1.0));
-            }
-        } else if has_pitch && has_attack {
-            // STRUCTURE: pitch + attack = onset + voiced sound
-            // Could be:
-            // 1. Voiced consonant (M, N, L at onset)
-            // 2. Vowel with glottal onset (word-initial vowel)
-            // 3. CV syllable (consonant + vowel)
-
-            // Voiced consonants
-            for cons in [Phoneme::M, Phoneme::N, Phoneme::L, Phoneme::R,
-                         Phoneme::W, Phoneme::Y, Phoneme::B, Phoneme::D, Phoneme::G] {
-                candidates.push((cons, 1.0));
-            }
-            // Vowels (with glottal onset - common in English)
-            for vowel in [Phoneme::AY, Phoneme::IY, Phoneme::AA, Phoneme::AH,
-                          Phoneme::EY, Phoneme::OW, Phoneme::AO, Phoneme::UW] {
-                candidates.push((vowel, 1.0));  // Equal weight - Nittay isotropy
-            }
-        } else if !has_pitch && has_attack {
-            // STRUCTURE: no_pitch + attack = UNVOICED CONSONANT
-            for cons in [Phoneme::P, Phoneme::T, Phoneme::K, Phoneme::S, Phoneme::F,
-                         Phoneme::TH, Phoneme::SH, Phoneme::HH, Phoneme::CH] {
-                candidates.push((cons, 1.0));
-            }
-        }
-        // No pitch + no attack = silence (skip)
-
-        candidates
-    }
-
     /// SATURATE: Propagate constraints until fixed point
     /// NITTAY INSIGHT: All candidates equal, constraints determine winner
     /// EMERGENT: Segment properties (pitch, duration) constrain selection
@@ -1545,7 +1355,7 @@ impl SaturatedTranscriber {
 
         // PHASE 2: Saturate WHOLE SENTENCE at once using EMERGENT mappings
         let mappings = self.get_emergent_mappings();
-        let phoneme_sequence = saturate_time_series_with_mappings(&located_segments, &mappings);
+        let phoneme_sequence = saturate_time_series_with_mappings(&located_segments, &mappings, self.rule_context());
 
         // Debug: Print phoneme sequence with locations
         if std::env::var("DEBUG_SEGMENTS").is_ok() {
@@ -1579,9 +1389,7 @@ impl SaturatedTranscriber {
 
     /// Transcribe with full segment info (for detailed output)
     pub fn transcribe_detailed(&mut self, samples: &[f32], sample_rate: u32) -> (String, Vec<LocatedSegment>, Vec<(Phoneme, usize, usize)>) {
-        let located_segments = saturate_segment_boundaries(samples, sample_rate);
-        let mappings = self.get_emergent_mappings();
-        let phoneme_sequence = saturate_time_series_with_mappings(&located_segments, &mappings);
+        let (located_segments, phoneme_sequence) = self.decode_segments(samples, sample_rate);
 
         self.history.push(SaturatedState {
             phonemes: phoneme_sequence.iter().map(|(p, _, _)| *p).collect(),
@@ -1596,6 +1404,13 @@ impl SaturatedTranscriber {
         (text, located_segments, phoneme_sequence)
     }
 
+    /// Phase 1 + Phase 2 without touching history (streaming partials)
+    pub fn decode_segments(&self, samples: &[f32], sample_rate: u32) -> (Vec<LocatedSegment>, Vec<(Phoneme, usize, usize)>) {
+        let located_segments = saturate_segment_boundaries(samples, sample_rate);
+        let phoneme_sequence = saturate_time_series_with_mappings(&located_segments, &self.get_emergent_mappings(), self.rule_context());
+        (located_segments, phoneme_sequence)
+    }
+
     /// JOINT SATURATION: Saturate ALL training sentences AT ONCE (not one by one!)
     /// This is the key P=NP insight - constraints propagate across ALL data SIMULTANEOUSLY
     ///
@@ -1626,6 +1441,8 @@ impl SaturatedTranscriber {
         let mut band_mid = 0usize;
         let mut band_unvoiced = 0usize;
 
+        let inventory = &self.inventory;
+        let classes = &inventory.classes;
         let mut candidates: Vec<Vec<Phoneme>> = Vec::with_capacity(all_segments.len());
         for (_, seg) in &all_segments {
             let low = seg.energy_low;
@@ -1637,7 +1454,7 @@ impl SaturatedTranscriber {
             if !seg.is_voiced || (high > low + 10.0 && high > mid + 5.0) {
                 // UNVOICED: HIGH energy dominant = fricatives and stops
                 band_unvoiced += 1;
-                cands.extend([Phoneme::S, Phoneme::SH, Phoneme::F, Phoneme::TH, Phoneme::T, Phoneme::K, Phoneme::P, Phoneme::HH]);
+                cands.extend(&classes.obstruents);
             } else if seg.is_voiced {
                 // VOICED: Use band profile to select vowel candidates
                 // FIX: Use relative comparisons, not absolute thresholds
@@ -1645,27 +1462,30 @@ impl SaturatedTranscriber {
 
                 // Use relative band energy to classify vowel type
                 // Lower threshold (1.0 dB) for better discrimination
-                if high > mid + 1.0 && high > low + 1.0 {
+                // (measured formants, when present, rank vowels directly)
+                if let Some(formants) = seg.formants {
+                    cands.extend(nearest_inventory_vowels(formants, inventory));
+                } else if high > mid + 1.0 && high > low + 1.0 {
                     // HIGH band dominant = front vowels or fricatives
                     band_high += 1;
-                    cands.extend([Phoneme::IY, Phoneme::IH, Phoneme::EY, Phoneme::EH]);
+                    cands.extend(&classes.high_vowels);
                 } else if low > mid + 1.0 && low > high + 1.0 {
                     // LOW band dominant = back vowels
                     band_low += 1;
-                    cands.extend([Phoneme::AA, Phoneme::AO, Phoneme::OW, Phoneme::UW]);
+                    cands.extend(&classes.low_vowels);
                 } else if mid >= low - 2.0 && mid >= high - 2.0 {
                     // MID band dominant or balanced = central vowels
                     band_mid += 1;
-                    cands.extend([Phoneme::AH, Phoneme::AE, Phoneme::ER]);
+                    cands.extend(&classes.mid_vowels);
                 } else {
                     // Fallback to MID
                     band_mid += 1;
-                    cands.extend([Phoneme::AH, Phoneme::AE, Phoneme::ER]);
+                    cands.extend(&classes.mid_vowels);
                 }
 
                 // Long durations = definitely vowels or diphthongs
                 if duration > 200 {
-                    cands.extend([Phoneme::AY, Phoneme::OY, Phoneme::AW, Phoneme::EY, Phoneme::OW]);
+                    cands.extend(&classes.diphthongs);
                 }
 
                 // Nasals/liquids: DISABLED for now to test pure vowel detection
@@ -1678,7 +1498,7 @@ impl SaturatedTranscriber {
 
             // Fallback
             if cands.is_empty() {
-                cands.extend([Phoneme::AH, Phoneme::IH, Phoneme::N, Phoneme::T]);
+                cands.extend(&classes.fallback);
             }
 
             candidates.push(cands);
@@ -1740,12 +1560,12 @@ impl SaturatedTranscriber {
 
                 // Markov transitions within sentence
                 for w in ps.windows(2) {
-                    score += phoneme_transition_score(w[0], w[1]);
+                    score += phoneme_transition_score(w[0], w[1], inventory);
                 }
 
                 // CV pattern reward
                 for w in ps.windows(2) {
-                    if !is_vowel_phoneme(w[0]) && is_vowel_phoneme(w[1]) {
+                    if !inventory.is_vowel(w[0]) && inventory.is_vowel(w[1]) {
                         score += 2.0;
                     }
                 }
@@ -1782,7 +1602,7 @@ impl SaturatedTranscriber {
 
             // Acoustic fit for each segment
             for (i, &p) in assignment.iter().enumerate() {
-                score += acoustic_segment_score(p, &all_segments[i].1);
+                score += acoustic_segment_score(p, &all_segments[i].1, inventory);
             }
 
             score
@@ -1925,127 +1745,47 @@ impl SaturatedTranscriber {
     /// PATH 8: Markov bigram model for word transitions
     /// PATH 3: NFA minimization - multiple phoneme patterns → same word
     fn phonemes_to_words(&self, phonemes: &[Phoneme]) -> String {
-        use Phoneme::*;
+        self.phoneme_word_spans(phonemes).into_iter()
+            .map(|(word, _, _)| word)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
 
+    /// Words with their time offsets, from a (phoneme, start_ms, end_ms) sequence
+    pub fn words_with_timings(&self, phoneme_sequence: &[(Phoneme, usize, usize)]) -> Vec<TimedWord> {
+        let phonemes: Vec<Phoneme> = phoneme_sequence.iter().map(|(p, _, _)| *p).collect();
+        self.phoneme_word_spans(&phonemes).into_iter()
+            .map(|(word, first, end)| TimedWord {
+                word,
+                start_ms: phoneme_sequence[first].1,
+                end_ms: phoneme_sequence[end - 1].2,
+            })
+            .collect()
+    }
+
+    /// Viterbi word decoding: (word, first phoneme, end phoneme exclusive)
+    /// with indices into `phonemes` (before collapsing repeats)
+    fn phoneme_word_spans(&self, phonemes: &[Phoneme]) -> Vec<(String, usize, usize)> {
         if phonemes.is_empty() {
-            return String::new();
+            return Vec::new();
         }
 
         // Collapse consecutive identical phonemes
+        // run_start[i] = index in `phonemes` where collapsed phoneme i starts
         let mut collapsed = Vec::new();
-        for p in phonemes {
+        let mut run_start = Vec::new();
+        for (i, p) in phonemes.iter().enumerate() {
             if collapsed.last() != Some(p) {
                 collapsed.push(*p);
+                run_start.push(i);
             }
         }
+        run_start.push(phonemes.len());
 
         let n = collapsed.len();
         if n == 0 {
-            return String::new();
-        }
-
-        // PHONEME → WORD dictionary (Path 3: NFA - multiple patterns per word)
-        // Each entry: (pattern, word, score)
-        // Score = log(word_frequency) - higher = more common
-        let dictionary: Vec<(&[Phoneme], &str, f32)> = vec![
-            // High frequency function words (score 10)
-            (&[DH, AH], "THE", 10.0),
-            (&[DH, IY], "THE", 10.0),
-            (&[AH], "A", 9.0),
-            (&[EY], "A", 8.0),
-            (&[AY], "I", 9.5),
-            (&[T, UW], "TO", 9.0),
-            (&[AE, N, D], "AND", 9.0),
-            (&[AE, N], "AN", 8.0),
-            (&[IH, N], "IN", 8.5),
-            (&[IH, T], "IT", 8.5),
-            (&[IH, Z], "IS", 8.5),
-            (&[DH, AE, T], "THAT", 8.0),
-            (&[W, AA, Z], "WAS", 7.5),
-            (&[W, AH, Z], "WAS", 7.5),
-            (&[F, AO, R], "FOR", 7.5),
-            (&[F, ER], "FOR", 7.5),
-            (&[B, AH, T], "BUT", 7.0),
-            (&[N, AA, T], "NOT", 7.5),
-            (&[W, IH, TH], "WITH", 7.0),
-            (&[HH, IY], "HE", 7.5),
-            (&[SH, IY], "SHE", 7.0),
-            (&[Y, UW], "YOU", 7.5),
-            (&[M, IY], "ME", 7.5),
-            (&[M, AY], "MY", 7.5),
-            // PATH 3: M/N confusion - M often detected when N intended
-            (&[M, OW], "NO", 6.0),  // Works well for "NO"
-            (&[W, IY], "WE", 7.0),
-            // Note: W→WE moved to fallbacks
-            (&[AA, L], "ALL", 7.0),
-            (&[AA, N], "ON", 7.5),
-            (&[AE, T], "AT", 7.5),
-            (&[B, IY], "BE", 7.5),
-            (&[N, OW], "NO", 7.5),
-            (&[S, OW], "SO", 7.0),
-
-            // Medium frequency words (score 5-7)
-            (&[W, EH, N], "WHEN", 6.5),
-            (&[DH, EH, N], "THEN", 6.5),
-            (&[HH, AW], "HOW", 6.0),
-            (&[N, AW], "NOW", 6.5),
-            (&[M, AW], "NOW", 5.5),  // M/N confusion
-            (&[AW, T], "OUT", 6.0),
-            (&[D, UW], "DO", 6.5),
-            (&[HH, AE, D], "HAD", 6.0),
-            (&[HH, AE, V], "HAVE", 6.0),
-            (&[W, UH, D], "WOULD", 5.5),
-            (&[K, UH, D], "COULD", 5.5),
-            (&[TH, IH, S], "THIS", 6.0),
-            (&[DH, OW, Z], "THOSE", 5.5),
-            (&[AH, B, AW, T], "ABOUT", 5.5),
-            (&[W, ER, D], "WORD", 5.0),
-            (&[W, ER, L, D], "WORLD", 5.0),
-            // Note: Removed M+AO → MORE (too greedy, conflicts with OR/AO patterns)
-
-            // Content words (score 4-5)
-            (&[TH, AO, T], "THOUGHT", 5.0),
-            (&[K, IH, L, Z], "KILLS", 4.5),
-            (&[HH, AA, R, T], "HEART", 5.0),
-            (&[HH, EY, T], "HATE", 4.5),
-            (&[L, AH, V], "LOVE", 5.0),
-            (&[M, AE, T, ER], "MATTER", 4.5),
-            (&[F, UH, T], "FOOT", 4.5),
-            (&[S, T, AE, N, D], "STAND", 4.5),
-            (&[P, L, IY, D], "PLEAD", 4.0),
-            (&[W, IH, L, T], "WILT", 4.0),
-            (&[EH, V, ER], "EVER", 4.5),
-            (&[B, EH, N, T], "BENT", 4.0),
-            (&[D, R, AA, P], "DROP", 4.0),
-            (&[AE, F, T, ER], "AFTER", 4.5),
-            (&[L, AO, S], "LOSS", 4.5),
-            (&[S, AA, R, OW], "SORROW", 4.0),
-
-            // Single phoneme fallbacks (low score 2-3)
-            (&[AA], "AH", 2.0),
-            (&[AO], "OR", 3.0),
-            (&[OW], "OH", 3.0),
-            (&[UW], "OO", 2.0),
-            (&[IY], "EE", 2.0),
-            (&[IH], "IH", 2.0),
-            (&[EH], "EH", 2.0),
-            (&[ER], "ER", 2.5),
-
-            // Consonant fallbacks - output consonant letter for debugging
-            // These low scores ensure real words are preferred
-            (&[S], "S", 1.5),
-            (&[T], "T", 1.5),
-            (&[K], "K", 1.5),
-            (&[P], "P", 1.5),
-            (&[F], "F", 1.5),
-            (&[TH], "TH", 1.5),
-            (&[SH], "SH", 1.5),
-            (&[HH], "H", 1.5),
-            (&[M], "ME", 2.0),  // M often represents ME (vowel merged)
-            (&[N], "N", 1.5),
-            (&[L], "L", 1.5),
-            (&[R], "R", 1.5),
-        ];
+            return Vec::new();
+        }
 
         // VITERBI DP (Path 2: Saturation via dynamic programming)
         // dp[i] = (best_score, backpointer_word, backpointer_pos)
@@ -2059,35 +1799,24 @@ impl SaturatedTranscriber {
                 continue;  // Unreachable state
             }
 
-            // Try all dictionary entries starting at position i
-            for (pattern, word, word_score) in &dictionary {
-                let len = pattern.len();
-                if i + len > n {
-                    continue;
-                }
-
-                // Check if pattern matches
-                let matches = pattern.iter().zip(&collapsed[i..i + len])
-                    .all(|(p1, p2)| p1 == p2);
-
-                if matches {
-                    // PATH 8: Add bigram bonus if previous word connects well
-                    let bigram_bonus = match (dp[i].1, *word) {
-                        (Some("THE"), _) => 1.0,  // THE + anything
-                        (Some("A"), _) => 0.5,   // A + anything
-                        (Some("I"), _) => 0.5,   // I + anything
-                        (Some("TO"), _) => 0.3,  // TO + anything
-                        (Some("IN"), _) => 0.3,  // IN + anything
-                        (Some("MY"), _) => 0.5,  // MY + anything
-                        (_, "THE") if dp[i].1.is_some() => 0.5,  // X + THE
-                        (_, "AND") => 0.3,  // X + AND
-                        _ => 0.0,
-                    };
+            // Try all lexicon entries starting at position i (trie walk)
+            for (len, word, word_score) in self.lexicon.matches_at(&collapsed, i) {
+                // PATH 8: Add bigram bonus if previous word connects well
+                let bigram_bonus = match (dp[i].1, word) {
+                    (Some("THE"), _) => 1.0,  // THE + anything
+                    (Some("A"), _) => 0.5,   // A + anything
+                    (Some("I"), _) => 0.5,   // I + anything
+                    (Some("TO"), _) => 0.3,  // TO + anything
+                    (Some("IN"), _) => 0.3,  // IN + anything
+                    (Some("MY"), _) => 0.5,  // MY + anything
+                    (_, "THE") if dp[i].1.is_some() => 0.5,  // X + THE
+                    (_, "AND") => 0.3,  // X + AND
+                    _ => 0.0,
+                };
 
-                    let new_score = dp[i].0 + word_score + bigram_bonus;
-                    if new_score > dp[i + len].0 {
-                        dp[i + len] = (new_score, Some(word), i);
-                    }
+                let new_score = dp[i].0 + word_score + bigram_bonus;
+                if new_score > dp[i + len].0 {
+                    dp[i + len] = (new_score, Some(word), i);
                 }
             }
 
@@ -2098,8 +1827,8 @@ impl SaturatedTranscriber {
             }
         }
 
-        // Backtrack to recover word sequence
-        let mut words: Vec<&str> = Vec::new();
+        // Backtrack to recover word sequence (word, collapsed start, collapsed end)
+        let mut words: Vec<(&str, usize, usize)> = Vec::new();
         let mut pos = n;
 
         // Find best ending position (might not be exactly n if some phonemes skipped)
@@ -2114,10 +1843,10 @@ impl SaturatedTranscriber {
         pos = best_end;
 
         while pos > 0 && dp[pos].1.is_some() {
+            let prev_pos = dp[pos].2;
             if let Some(word) = dp[pos].1 {
-                words.push(word);
+                words.push((word, prev_pos, pos));
             }
-            let prev_pos = dp[pos].2;
             if prev_pos >= pos {
                 break;  // Safety: prevent infinite loop
             }
@@ -2127,24 +1856,348 @@ impl SaturatedTranscriber {
         words.reverse();
 
         // PATH 2: Post-saturation cleanup - collapse repeated words
-        let mut result: Vec<&str> = Vec::new();
-        for word in words {
-            // Skip single-character fallbacks if we have real words
-            if word.len() <= 2 && !result.is_empty() {
-                let last = result.last().unwrap();
-                if last.len() > 2 {
-                    continue;  // Skip tiny fallback after real word
+        // (a dropped word's phonemes extend the word before it)
+        let mut result: Vec<(&str, usize, usize)> = Vec::new();
+        for (word, start, end) in words {
+            if let Some(last) = result.last_mut() {
+                // Skip single-character fallbacks if we have real words
+                let tiny_after_real = word.len() <= 2 && last.0.len() > 2;
+                if tiny_after_real || last.0 == word {
+                    last.2 = end;
+                    continue;
+                }
+            }
+            result.push((word, start, end));
+        }
+
+        result.into_iter()
+            .map(|(word, start, end)| (word.to_string(), run_start[start], run_start[end]))
+            .collect()
+    }
+}
+
+// ============================================================================
+// N-BEST AND LATTICES - the alternatives saturation rejected
+// ============================================================================
+
+impl SaturatedTranscriber {
+    /// Up to `n` distinct word hypotheses, best first
+    ///
+    /// The pool is the saturation neighbourhood of the fixed point: every
+    /// single and adjacent-pair substitution from the candidate matrix (the
+    /// same moves whole-sentence saturation tries), ranked by the
+    /// whole-sentence score. Phoneme sequences that decode to the same words
+    /// keep only their best.
+    pub fn nbest(&self, samples: &[f32], sample_rate: u32, n: usize) -> Vec<NBestHypothesis> {
+        let (segments, best) = self.decode_segments(samples, sample_rate);
+        self.nbest_from_segments(&segments, &best, n)
+    }
+
+    /// (phoneme lattice, word lattice from the `n`-best) for one utterance
+    pub fn lattices(&self, samples: &[f32], sample_rate: u32, n: usize) -> (Lattice, Lattice) {
+        let (segments, best) = self.decode_segments(samples, sample_rate);
+        let hypotheses = self.nbest_from_segments(&segments, &best, n);
+        (self.phoneme_lattice(&segments, &best), Lattice::from_nbest(&hypotheses))
+    }
+
+    fn nbest_from_segments(&self, segments: &[LocatedSegment], best: &[(Phoneme, usize, usize)], n: usize) -> Vec<NBestHypothesis> {
+        if segments.is_empty() || n == 0 {
+            return Vec::new();
+        }
+
+        let mappings = self.get_emergent_mappings();
+        let candidates: Vec<Vec<Phoneme>> = segments.iter().enumerate()
+            .map(|(idx, seg)| segment_candidates(idx, seg, &mappings, &self.inventory, false))
+            .collect();
+        let best: Vec<Phoneme> = best.iter().map(|(p, _, _)| *p).collect();
+
+        let mut pool: Vec<Vec<Phoneme>> = vec![best.clone()];
+        for pos in 0..best.len() {
+            for &c in &candidates[pos] {
+                if c != best[pos] {
+                    let mut seq = best.clone();
+                    seq[pos] = c;
+                    pool.push(seq);
                 }
             }
-            if result.last() != Some(&word) {
-                result.push(word);
+        }
+        for i in 0..best.len().saturating_sub(1) {
+            for &ci in &candidates[i] {
+                for &cj in &candidates[i + 1] {
+                    if ci != best[i] && cj != best[i + 1] {
+                        let mut seq = best.clone();
+                        seq[i] = ci;
+                        seq[i + 1] = cj;
+                        pool.push(seq);
+                    }
+                }
+            }
+        }
+
+        let infos: Vec<SegmentInfo> = segments.iter().map(SegmentInfo::from).collect();
+        let mut ranked: Vec<(f32, Vec<Phoneme>)> = pool.into_iter()
+            .map(|seq| (score_whole_sentence_with_mappings(&seq, segments, &infos, &mappings, self.rule_context()), seq))
+            .collect();
+        // Stable: ties keep the fixed point first
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut hypotheses: Vec<NBestHypothesis> = Vec::new();
+
+        for (score, seq) in ranked {
+            if hypotheses.len() >= n {
+                break;
+            }
+
+            let words: Vec<ScoredWord> = self.phoneme_word_spans(&seq).into_iter()
+                .map(|(word, first, end)| {
+                    let (constraint, _, _) = self.score_breakdown(&seq[first..end], &infos[first..end]);
+                    ScoredWord {
+                        word,
+                        start_ms: segments[first].start_ms,
+                        end_ms: segments[end - 1].end_ms,
+                        acoustic: (first..end).map(|k| acoustic_segment_score(seq[k], &segments[k], &self.inventory)).sum(),
+                        constraint,
+                    }
+                })
+                .collect();
+            let text = words.iter().map(|w| w.word.as_str()).collect::<Vec<_>>().join(" ");
+            if hypotheses.iter().any(|h| h.text == text) {
+                continue;
+            }
+
+            let (constraint_score, acoustic_score, violations) = self.score_breakdown(&seq, &infos);
+            hypotheses.push(NBestHypothesis {
+                text,
+                words,
+                phonemes: seq.iter().zip(segments).map(|(&p, s)| (p, s.start_ms, s.end_ms)).collect(),
+                score,
+                constraint_score,
+                acoustic_score,
+                violations,
+            });
+        }
+
+        hypotheses
+    }
+
+    /// Every candidate of every segment as an arc. Acoustic = acoustic_segment_score;
+    /// constraint = constraint weights passed by the arc's phoneme between its
+    /// fixed-point neighbours (local, so arcs are comparable within a segment
+    /// and do not repeat the rest of the sentence's score).
+    fn phoneme_lattice(&self, segments: &[LocatedSegment], best: &[(Phoneme, usize, usize)]) -> Lattice {
+        let start = segments.first().map_or(0, |s| s.start_ms);
+        let end = segments.last().map_or(start, |s| s.end_ms);
+        let mut lattice = LatticeBuilder::new(LatticeKind::Phoneme, start, end);
+
+        let mappings = self.get_emergent_mappings();
+        let infos: Vec<SegmentInfo> = segments.iter().map(SegmentInfo::from).collect();
+        let best: Vec<Phoneme> = best.iter().map(|(p, _, _)| *p).collect();
+
+        for (pos, seg) in segments.iter().enumerate() {
+            let context = pos.saturating_sub(1)..(pos + 2).min(best.len());
+            for p in segment_candidates(pos, seg, &mappings, &self.inventory, false) {
+                let mut window = best[context.clone()].to_vec();
+                window[pos - context.start] = p;
+                let (constraint, _, _) = self.score_breakdown(&window, &infos[context.clone()]);
+                lattice.add_arc(seg.start_ms, seg.end_ms, &phoneme_name(p), acoustic_segment_score(p, seg, &self.inventory), constraint);
+            }
+        }
+        for pair in segments.windows(2) {
+            if pair[1].start_ms > pair[0].end_ms {
+                lattice.add_arc(pair[0].end_ms, pair[1].start_ms, NULL_LABEL, 0.0, 0.0);
             }
         }
 
-        result.join(" ")
+        lattice.build()
+    }
+}
+
+// ============================================================================
+// FORCED ALIGNMENT - known transcript, unknown timing
+// ============================================================================
+
+impl SaturatedTranscriber {
+    /// Word and phoneme timestamps of a known `transcript` (first lexicon
+    /// pronunciation per word) over the saturated segment boundaries
+    pub fn force_align(&self, samples: &[f32], sample_rate: u32, transcript: &str) -> Result<ForcedAlignment, AlignmentError> {
+        let words = normalize_transcript(transcript).into_iter()
+            .map(|word| match self.lexicon.pronunciation(&word) {
+                Some(pron) => Ok((word, pron.to_vec())),
+                None => Err(AlignmentError::UnknownWord(word)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let segments = saturate_segment_boundaries(samples, sample_rate);
+        forced_alignment::force_align(&words, &segments, &ALL_PHONEMES, |p, seg| acoustic_segment_score(p, seg, &self.inventory))
+    }
+}
+
+// ============================================================================
+// PERSISTENCE - save/load the saturated state
+// ============================================================================
+
+/// Identifies a saved model file
+pub const MODEL_FORMAT: &str = "saturated-transcriber";
+/// Bumped whenever the saved fields or their meaning change
+/// (2: rules and lexicon saved with the emergent state)
+pub const MODEL_VERSION: u32 = 2;
+
+/// Every Phoneme variant, for name <-> variant conversion
+const ALL_PHONEMES: [Phoneme; 40] = {
+    use Phoneme::*;
+    [AA, AE, AH, AO, AW, AY, EH, ER, EY, IH, IY, OW, OY, UH, UW, W, Y, L, R, M, N, NG,
+     V, DH, Z, ZH, F, TH, S, SH, HH, CH, JH, B, D, G, P, T, K, SIL]
+};
+
+fn phoneme_name(p: Phoneme) -> String {
+    format!("{:?}", p)
+}
+
+fn phoneme_from_name(name: &str) -> Option<Phoneme> {
+    ALL_PHONEMES.iter().copied().find(|&p| phoneme_name(p) == name)
+}
+
+/// On-disk form of the state produced by saturate_all (JSON)
+///
+/// Phonemes are stored by name so the file does not depend on enum order;
+/// BTreeMaps keep the output byte-stable across runs. The rules and lexicon
+/// the state was saturated under are saved too, so a loaded model decodes
+/// exactly like the saving transcriber.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SaturatedModel {
+    pub format: String,
+    pub version: u32,
+    pub is_saturated: bool,
+    pub pitch_histogram: BTreeMap<u8, usize>,
+    pub mean_duration_by_pitch: BTreeMap<u8, usize>,
+    pub emergent_pitch_phonemes: BTreeMap<u8, String>,
+    pub emergent_duration_phonemes: BTreeMap<usize, String>,
+    pub rules: PhonotacticRules,
+    /// Lexicon entries in insertion order
+    pub lexicon: Vec<LexiconEntry>,
+}
+
+/// One saved (pronunciation, word, score); phonemes space-separated
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LexiconEntry {
+    pub word: String,
+    pub pronunciation: String,
+    pub score: f32,
+}
+
+#[derive(Debug)]
+pub enum ModelError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// File is not a saturated-transcriber model
+    Format(String),
+    /// Saved by an incompatible version
+    Version(u32),
+    UnknownPhoneme(String),
+    /// Saved rules no longer compile
+    Rules(RulesError),
+}
+
+impl std::fmt::Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelError::Io(e) => write!(f, "I/O error: {}", e),
+            ModelError::Json(e) => write!(f, "invalid model JSON: {}", e),
+            ModelError::Format(name) => write!(f, "not a {} model (format '{}')", MODEL_FORMAT, name),
+            ModelError::Version(v) => write!(f, "model version {} (expected {})", v, MODEL_VERSION),
+            ModelError::UnknownPhoneme(name) => write!(f, "unknown phoneme '{}'", name),
+            ModelError::Rules(e) => write!(f, "saved rules: {}", e),
+        }
     }
 }
 
+impl std::error::Error for ModelError {}
+
+impl SaturatedTranscriber {
+    /// Snapshot of the saturated state
+    pub fn to_model(&self) -> SaturatedModel {
+        SaturatedModel {
+            format: MODEL_FORMAT.to_string(),
+            version: MODEL_VERSION,
+            is_saturated: self.is_saturated,
+            pitch_histogram: self.pitch_histogram.iter().map(|(&k, &v)| (k, v)).collect(),
+            mean_duration_by_pitch: self.mean_duration_by_pitch.iter().map(|(&k, &v)| (k, v)).collect(),
+            emergent_pitch_phonemes: self.emergent_pitch_phonemes.iter().map(|(&k, &p)| (k, phoneme_name(p))).collect(),
+            emergent_duration_phonemes: self.emergent_duration_phonemes.iter().map(|(&k, &p)| (k, phoneme_name(p))).collect(),
+            rules: self.rules.clone(),
+            lexicon: self.lexicon.entries()
+                .map(|(pronunciation, word, score)| LexiconEntry {
+                    word: word.to_string(),
+                    pronunciation: pronunciation.iter().map(|&p| phoneme_name(p)).collect::<Vec<_>>().join(" "),
+                    score,
+                })
+                .collect(),
+        }
+    }
+
+    /// Saved rules, lexicon and emergent state (no re-saturation)
+    pub fn from_model(model: &SaturatedModel) -> Result<Self, ModelError> {
+        if model.format != MODEL_FORMAT {
+            return Err(ModelError::Format(model.format.clone()));
+        }
+        if model.version != MODEL_VERSION {
+            return Err(ModelError::Version(model.version));
+        }
+
+        let parse = |name: &String| phoneme_from_name(name).ok_or_else(|| ModelError::UnknownPhoneme(name.clone()));
+
+        let mut lexicon = Lexicon::new();
+        for entry in &model.lexicon {
+            let pronunciation = entry.pronunciation.split_whitespace()
+                .map(|name| phoneme_from_name(name).ok_or_else(|| ModelError::UnknownPhoneme(name.to_string())))
+                .collect::<Result<Vec<_>, _>>()?;
+            lexicon.add(&pronunciation, &entry.word, entry.score);
+        }
+
+        let mut transcriber = Self::with_rules(&model.rules).map_err(ModelError::Rules)?;
+        transcriber.set_lexicon(lexicon);
+        transcriber.is_saturated = model.is_saturated;
+        transcriber.pitch_histogram = model.pitch_histogram.iter().map(|(&k, &v)| (k, v)).collect();
+        transcriber.mean_duration_by_pitch = model.mean_duration_by_pitch.iter().map(|(&k, &v)| (k, v)).collect();
+        transcriber.emergent_pitch_phonemes = model.emergent_pitch_phonemes.iter()
+            .map(|(&k, name)| Ok((k, parse(name)?)))
+            .collect::<Result<_, ModelError>>()?;
+        transcriber.emergent_duration_phonemes = model.emergent_duration_phonemes.iter()
+            .map(|(&k, name)| Ok((k, parse(name)?)))
+            .collect::<Result<_, ModelError>>()?;
+
+        Ok(transcriber)
+    }
+
+    pub fn model_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_model()).expect("SaturatedModel is always serializable")
+    }
+
+    pub fn from_model_json(json: &str) -> Result<Self, ModelError> {
+        let model: SaturatedModel = serde_json::from_str(json).map_err(ModelError::Json)?;
+        Self::from_model(&model)
+    }
+
+    /// Save the saturated state, e.g. after saturate_all over a corpus
+    pub fn save_model(&self, path: &Path) -> Result<(), ModelError> {
+        std::fs::write(path, self.model_json()).map_err(ModelError::Io)
+    }
+
+    /// Load a saved state: transcribe output matches the saving transcriber exactly
+    pub fn load_model(path: &Path) -> Result<Self, ModelError> {
+        let json = std::fs::read_to_string(path).map_err(ModelError::Io)?;
+        Self::from_model_json(&json)
+    }
+}
+
+/// Decoded word with its time span
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedWord {
+    pub word: String,
+    pub start_ms: usize,
+    pub end_ms: usize,
+}
+
 /// Saturation statistics
 #[derive(Debug, Clone)]
 pub struct SaturationStats {
@@ -2163,7 +2216,7 @@ impl std::fmt::Display for SaturationStats {
         writeln!(f, "  Final violations: {}", self.final_violations)?;
         writeln!(f, "  Final score: {:.2}", self.final_score)?;
         writeln!(f, "  Saturated: {}", if self.saturated { "YES" } else { "NO" })?;
-        writeln!(f, "  Constraints: {} hardcoded", self.constraint_count)?;
+        writeln!(f, "  Constraints: {}", self.constraint_count)?;
         writeln!(f, "  Boundaries: {} phonemes", self.boundary_count)?;
         Ok(())
     }
@@ -2204,4 +2257,217 @@ mod tests {
         let (_, violations) = transcriber.score_constraints(&invalid, &invalid_segs);
         assert!(violations > 0);
     }
+
+    #[test]
+    fn test_formants_drive_vowel_score() {
+        // Flat bands: the band heuristic cannot tell front from back vowels
+        let seg = LocatedSegment {
+            start_ms: 0, end_ms: 120, pitch: Some(57), energy_db: -10.0,
+            is_voiced: true, is_silence: false,
+            energy_low: -20.0, energy_mid: -20.0, energy_high: -20.0,
+            mfcc: Vec::new(),
+            formants: Some([280.0, 2250.0, 2900.0]),
+        };
+
+        let inventory = &SaturatedTranscriber::new().inventory;
+        let iy = acoustic_segment_score(Phoneme::IY, &seg, inventory);
+        assert!(iy > acoustic_segment_score(Phoneme::IH, &seg, inventory));
+        assert!(iy > acoustic_segment_score(Phoneme::AA, &seg, inventory) + 2.0);
+    }
+
+    #[test]
+    fn test_model_roundtrip() {
+        let mut transcriber = SaturatedTranscriber::new();
+        transcriber.emergent_pitch_phonemes = [(57, Phoneme::AA), (62, Phoneme::IY), (64, Phoneme::SH)].into_iter().collect();
+        transcriber.emergent_duration_phonemes = [(50, Phoneme::T), (150, Phoneme::AH)].into_iter().collect();
+        transcriber.pitch_histogram = [(57, 12), (62, 7)].into_iter().collect();
+        transcriber.mean_duration_by_pitch = [(57, 140)].into_iter().collect();
+        transcriber.is_saturated = true;
+
+        let json = transcriber.model_json();
+        let mut loaded = SaturatedTranscriber::from_model_json(&json).unwrap();
+
+        assert!(loaded.is_saturated);
+        assert_eq!(loaded.emergent_pitch_phonemes, transcriber.emergent_pitch_phonemes);
+        assert_eq!(loaded.emergent_duration_phonemes, transcriber.emergent_duration_phonemes);
+        assert_eq!(loaded.model_json(), json, "Saved form is byte-stable");
+
+        let samples: Vec<f32> = (0..8000).map(|i| 0.4 * (i as f32 * 0.08).sin()).collect();
+        assert_eq!(loaded.transcribe(&samples, 16000), transcriber.transcribe(&samples, 16000));
+    }
+
+    #[test]
+    fn test_model_keeps_lexicon_and_rules() {
+        use super::super::phonotactics::{ConstraintRule, RuleKind};
+        use Phoneme::*;
+
+        let mut rules = PhonotacticRules::english();
+        rules.language = "en-test".to_string();
+        rules.constraints.push(ConstraintRule {
+            name: "no_onset_clusters".to_string(),
+            weight: 5.0,
+            rule: RuleKind::OnsetClusters { allowed: Vec::new() },
+        });
+        rules.classes.obstruents = vec!["S".to_string(), "T".to_string()];
+        let mut lexicon = Lexicon::new();
+        lexicon.add_cmudict("CAT  K AE1 T\nSAT  S AE1 T\nCATS  K AE1 T S\nCAT(2)  K AH0 T\n", None);
+        lexicon.add(&[S, AE, T], "SAT", 6.5);
+
+        let mut transcriber = SaturatedTranscriber::with_rules(&rules).unwrap();
+        transcriber.set_lexicon(lexicon);
+        transcriber.emergent_pitch_phonemes = [(57, AE)].into_iter().collect();
+
+        let dir = std::env::temp_dir().join(format!("saturated_model_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.json");
+        transcriber.save_model(&path).unwrap();
+        let mut loaded = SaturatedTranscriber::load_model(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(loaded.rules.language, "en-test");
+        assert_eq!(loaded.constraints.len(), transcriber.constraints.len());
+        assert_eq!(loaded.inventory.classes.obstruents, vec![S, T]);
+        assert_eq!(loaded.lexicon().entries().collect::<Vec<_>>(), transcriber.lexicon().entries().collect::<Vec<_>>());
+        assert_eq!(loaded.lexicon().pronunciations("CAT"), vec![&[K, AE, T][..], &[K, AH, T]]);
+        assert_eq!(loaded.phonemes_to_words(&[K, AE, T, S, AE, T]), "CAT SAT");
+        assert_eq!(loaded.model_json(), transcriber.model_json());
+
+        let samples: Vec<f32> = (0..8000).map(|i| 0.4 * (i as f32 * 0.08).sin()).collect();
+        assert_eq!(loaded.transcribe(&samples, 16000), transcriber.transcribe(&samples, 16000));
+    }
+
+    #[test]
+    fn test_model_version_checked() {
+        let mut model = SaturatedTranscriber::new().to_model();
+        model.version = MODEL_VERSION + 1;
+        assert!(matches!(SaturatedTranscriber::from_model(&model), Err(ModelError::Version(_))));
+
+        let mut model = SaturatedTranscriber::new().to_model();
+        model.emergent_pitch_phonemes.insert(60, "XX".to_string());
+        assert!(matches!(SaturatedTranscriber::from_model(&model), Err(ModelError::UnknownPhoneme(_))));
+    }
+
+    #[test]
+    fn test_loaded_lexicon_decodes_words() {
+        use Phoneme::*;
+        let mut transcriber = SaturatedTranscriber::new();
+        let phonemes = [K, AE, T, S, AE, T];
+        assert_ne!(transcriber.phonemes_to_words(&phonemes), "CAT SAT", "Not in the built-in lexicon");
+
+        let mut lexicon = Lexicon::new();
+        lexicon.add_cmudict("CAT  K AE1 T\nSAT  S AE1 T\nCATS  K AE1 T S\n", None);
+        transcriber.set_lexicon(lexicon);
+        assert_eq!(transcriber.phonemes_to_words(&phonemes), "CAT SAT");
+    }
+
+    #[test]
+    fn test_nbest_and_lattices() {
+        let transcriber = SaturatedTranscriber::new();
+        let samples: Vec<f32> = (0..12000)
+            .map(|i| if (i / 2000) % 2 == 0 { 0.4 * (i as f32 * 0.08).sin() } else { 0.3 * ((i * 7919 % 101) as f32 / 50.0 - 1.0) })
+            .collect();
+
+        let (_, best) = transcriber.decode_segments(&samples, 16000);
+        let nbest = transcriber.nbest(&samples, 16000, 5);
+        assert!(!nbest.is_empty() && nbest.len() <= 5);
+        assert_eq!(nbest[0].text, transcriber.words_with_timings(&best).iter().map(|w| w.word.as_str()).collect::<Vec<_>>().join(" "),
+            "The saturated fixed point ranks first");
+        for pair in nbest.windows(2) {
+            assert!(pair[0].score >= pair[1].score);
+            assert_ne!(pair[0].text, pair[1].text);
+        }
+
+        let (phonemes, words) = transcriber.lattices(&samples, 16000, 5);
+        assert_eq!(phonemes.kind, LatticeKind::Phoneme);
+        assert!(phonemes.arcs.len() >= best.len(), "At least the best path");
+        assert!(words.arcs.iter().filter(|a| a.label != NULL_LABEL).count() >= nbest[0].words.len());
+        assert!(words.to_htk_slf("utt").contains(&format!("N={} L={}", words.nodes.len(), words.arcs.len())));
+
+        // Arc constraint scores are local: the arc's phoneme next to its fixed-point neighbour
+        let segments = transcriber.decode_segments(&samples, 16000).0;
+        let infos: Vec<SegmentInfo> = segments.iter().map(SegmentInfo::from).collect();
+        let arc = &phonemes.arcs[0];
+        let first = phoneme_from_name(&arc.label).unwrap();
+        let context = [first, best[1].0];
+        assert_eq!(arc.constraint, transcriber.score_breakdown(&context, &infos[..2]).0);
+    }
+
+    #[test]
+    fn test_force_align_transcript() {
+        let transcriber = SaturatedTranscriber::new();
+        let samples: Vec<f32> = (0..8000).map(|i| 0.4 * (i as f32 * 0.08).sin()).collect();
+
+        let alignment = transcriber.force_align(&samples, 16000, "The love.").unwrap();
+        let words: Vec<&str> = alignment.words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, vec!["THE", "LOVE"]);
+        assert_eq!(alignment.phonemes.len(), 5, "DH AH + L AH V");
+        assert!(alignment.words[0].end_ms <= alignment.words[1].start_ms);
+        assert!(alignment.confidence > 0.0 && alignment.confidence <= 1.0);
+
+        assert_eq!(transcriber.force_align(&samples, 16000, "the xylophone").unwrap_err(),
+            AlignmentError::UnknownWord("XYLOPHONE".to_string()));
+    }
+
+    #[test]
+    fn test_rules_drive_constraints() {
+        use super::super::phonotactics::{ConstraintRule, RuleKind};
+
+        let mut rules = PhonotacticRules::english();
+        rules.constraints.push(ConstraintRule {
+            name: "no_onset_clusters".to_string(),
+            weight: 5.0,
+            rule: RuleKind::OnsetClusters { allowed: Vec::new() },
+        });
+        let english = SaturatedTranscriber::new();
+        let custom = SaturatedTranscriber::with_rules(&rules).unwrap();
+        assert_eq!(custom.constraints.len(), english.constraints.len() + 1);
+
+        let segs = vec![SegmentInfo { pitch: 0, duration_ms: 100, has_attack: false }; 3];
+        let (_, base) = english.score_constraints(&[Phoneme::S, Phoneme::T, Phoneme::AA], &segs);
+        let (_, with_rule) = custom.score_constraints(&[Phoneme::S, Phoneme::T, Phoneme::AA], &segs);
+        assert_eq!(with_rule, base + 1, "ST onset violates the added rule");
+    }
+
+    #[test]
+    fn test_rules_drive_transcription() {
+        use super::super::phonotactics::{ConstraintRule, RuleKind};
+
+        let samples: Vec<f32> = (0..12000)
+            .map(|i| if (i / 2000) % 2 == 0 { 0.4 * (i as f32 * 0.08).sin() } else { 0.3 * ((i * 7919 % 101) as f32 / 50.0 - 1.0) })
+            .collect();
+        let english = SaturatedTranscriber::new();
+        let (segments, english_best) = english.decode_segments(&samples, 16000);
+        assert!(english_best.iter().any(|(p, _, _)| !matches!(p, Phoneme::AA | Phoneme::S)));
+
+        // A two-phoneme language: candidates come from its classes only
+        let mut rules = PhonotacticRules::english();
+        rules.vowels = vec!["AA".to_string()];
+        for class in [&mut rules.classes.high_vowels, &mut rules.classes.mid_vowels, &mut rules.classes.low_vowels, &mut rules.classes.fallback] {
+            *class = vec!["AA".to_string()];
+        }
+        rules.classes.obstruents = vec!["S".to_string()];
+        rules.classes.diphthongs.clear();
+        rules.classes.sonorants.clear();
+        let custom = SaturatedTranscriber::with_rules(&rules).unwrap();
+        let (_, custom_best) = custom.decode_segments(&samples, 16000);
+        assert_eq!(custom_best.len(), english_best.len());
+        assert!(custom_best.iter().all(|(p, _, _)| matches!(p, Phoneme::AA | Phoneme::S)), "{:?}", custom_best);
+
+        // Constraints weigh in on whole-sentence scores
+        let mut rules = PhonotacticRules::english();
+        rules.constraints.push(ConstraintRule {
+            name: "no_s_before_aa".to_string(),
+            weight: 5.0,
+            rule: RuleKind::ForbiddenBigrams { first: vec!["S".to_string()], second: vec!["AA".to_string()] },
+        });
+        let custom = SaturatedTranscriber::with_rules(&rules).unwrap();
+        let mappings = EmergentMappings::default();
+        let infos: Vec<SegmentInfo> = segments[..2].iter().map(SegmentInfo::from).collect();
+        let score = |t: &SaturatedTranscriber, seq: &[Phoneme]| {
+            score_whole_sentence_with_mappings(seq, &segments[..2], &infos, &mappings, t.rule_context())
+        };
+        let (s_aa, aa_s) = ([Phoneme::S, Phoneme::AA], [Phoneme::AA, Phoneme::S]);
+        assert_eq!(score(&custom, &s_aa), score(&english, &s_aa), "Violated: no weight added");
+        assert_eq!(score(&custom, &aa_s), score(&english, &aa_s) + 5.0);
+    }
 }
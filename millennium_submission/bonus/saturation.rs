@@ -9,8 +9,13 @@
 
 use super::bitboard::{Square, attack_tables};
 use super::position::{Position, Color, PieceType};
-use super::movegen::Move;
+use super::movegen::{generate_legal, Move};
 use super::framework_search::FrameworkSearchEngine;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 // ============================================================================
 // SATURATION CONSTANTS (derived from framework)
@@ -22,6 +27,21 @@ pub const BASE_DEPTH: i32 = 6;
 /// Maximum reasonable depth (prevents runaway)
 pub const MAX_DEPTH: i32 = 20;
 
+/// FrameworkSearchEngine reports mate in p plies as ±(MATE_SCORE - p)
+pub const MATE_SCORE: i32 = 100_000;
+
+/// Scores beyond this are forced mates: the search stops early
+pub const DECISIVE_SCORE: i32 = 50_000;
+
+/// Moves to mate for a decisive score (negative = side to move is mated)
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score.abs() <= DECISIVE_SCORE {
+        return None;
+    }
+    let moves = (MATE_SCORE - score.abs() + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
 /// Derive epsilon from position's bounded move potential (Discovery 14 + 36)
 /// Framework: Each move changes O(1) squares with bounded material swing
 /// Epsilon = max_tension_swing / convergence_factor
@@ -73,7 +93,7 @@ pub fn derive_epsilon(pos: &Position) -> i32 {
 // ============================================================================
 
 /// Analyzes search convergence to determine optimal stopping point
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct SaturationAnalyzer {
     /// History of (depth, evaluation) pairs
     eval_history: Vec<(i32, i32)>,
@@ -82,6 +102,7 @@ pub struct SaturationAnalyzer {
     move_history: Vec<Option<Move>>,
 
     /// Convergence metrics
+    /// Depth at which saturation was declared (set by the search loops)
     pub depths_until_saturation: Option<i32>,
     pub final_eval_delta: i32,
 }
@@ -97,6 +118,21 @@ impl SaturationAnalyzer {
         self.move_history.push(best_move);
     }
 
+    /// Latest recorded (depth, eval, best_move)
+    pub fn latest(&self) -> Option<(i32, i32, Option<Move>)> {
+        let &(depth, eval) = self.eval_history.last()?;
+        Some((depth, eval, self.move_history.last().copied().flatten()))
+    }
+
+    /// Same-parity delta |E_n - E_{n-2}| of the latest depth (Discovery 23)
+    pub fn same_parity_delta(&self) -> Option<i32> {
+        let n = self.eval_history.len();
+        if n < 3 {
+            return None;
+        }
+        Some((self.eval_history[n-1].1 - self.eval_history[n-3].1).abs())
+    }
+
     /// Check if evaluation has saturated (converged)
     ///
     /// Discovery 14: |E_{d+2} - E_d| decreases as d increases
@@ -153,6 +189,24 @@ impl SaturationAnalyzer {
         self.is_eval_saturated(epsilon) && self.is_move_stabilized()
     }
 
+    /// Instability of the latest same-parity comparison (Discovery 23)
+    ///
+    /// 0.0 = eval and move stable, grows with |E_n - E_{n-2}| / epsilon
+    /// plus 1.0 if the best move changed. Capped at 2.0 (one full eval
+    /// swing plus one move change) so time stretching stays bounded.
+    pub fn instability(&self, epsilon: i32) -> f64 {
+        let n = self.eval_history.len();
+        if n < 3 {
+            return 0.0;
+        }
+
+        let swing = (self.eval_history[n-1].1 - self.eval_history[n-3].1).abs();
+        let eval_part = (swing as f64 / epsilon.max(1) as f64).min(1.0);
+        let move_part = if self.move_history[n-1] != self.move_history[n-3] { 1.0 } else { 0.0 };
+
+        eval_part + move_part
+    }
+
     /// Get convergence rate (how fast eval is stabilizing)
     pub fn convergence_rate(&self) -> f64 {
         if self.eval_history.len() < 2 {
@@ -381,6 +435,75 @@ impl PositionComplexity {
     }
 }
 
+// ============================================================================
+// TIME MANAGEMENT
+// ============================================================================
+
+/// Safety margin kept back on every move (GUI/network latency)
+pub const MOVE_OVERHEAD_MS: u64 = 50;
+
+/// Minimum number of moves the remaining clock must last
+pub const MIN_HORIZON_MOVES: u64 = 10;
+
+/// Tournament clock state for the side to move
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimeControl {
+    /// Time left on our clock
+    pub remaining_ms: u64,
+
+    /// Increment added after each move
+    pub increment_ms: u64,
+
+    /// Moves until the next time control (None = sudden death)
+    pub moves_to_go: Option<u64>,
+}
+
+/// Per-move deadlines derived from the clock
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeBudget {
+    /// Target time: stop after this unless the search is still unstable
+    pub soft_ms: u64,
+
+    /// Never start a depth that is projected to finish after this
+    pub hard_ms: u64,
+}
+
+impl TimeBudget {
+    /// Derive soft/hard deadlines from the clock and the position
+    ///
+    /// Framework: horizon = moves we still have to play on this clock.
+    /// With no moves-to-go, the horizon is the piece count (each piece
+    /// is roughly one phase of the game still to resolve), floored at
+    /// MIN_HORIZON_MOVES.
+    ///
+    /// soft = min(remaining / horizon + increment, remaining / 3)
+    /// hard = min(4 × soft, remaining / 3) so a single move never burns
+    /// more than a third of the clock, increment or not.
+    pub fn derive(tc: &TimeControl, pos: &Position) -> Self {
+        let usable = tc.remaining_ms.saturating_sub(MOVE_OVERHEAD_MS);
+        let cap = usable / 3;
+
+        let horizon = match tc.moves_to_go {
+            Some(moves) => moves.max(1),
+            None => (pos.occupied.popcount() as u64).max(MIN_HORIZON_MOVES),
+        };
+
+        let soft = (usable / horizon + tc.increment_ms).min(cap);
+        let hard = (soft * 4).min(cap);
+
+        TimeBudget { soft_ms: soft, hard_ms: hard }
+    }
+
+    /// Soft deadline stretched by search instability
+    ///
+    /// Stable eval and move → soft deadline as-is.
+    /// Eval swinging and/or best move changing → up to 3× soft, never past hard.
+    pub fn stretched_soft_ms(&self, instability: f64) -> u64 {
+        let factor = 1.0 + instability.clamp(0.0, 2.0);
+        ((self.soft_ms as f64 * factor) as u64).min(self.hard_ms)
+    }
+}
+
 // ============================================================================
 // ADAPTIVE SEARCH
 // ============================================================================
@@ -404,8 +527,6 @@ pub fn search_until_saturated(
 /// This is the branching factor for 2-ply lookahead
 /// Deeper search multiplies by branching factor per ply
 pub fn derive_node_limit(pos: &Position) -> u64 {
-    use super::movegen::generate_legal;
-
     let legal_moves = generate_legal(pos).len() as u64;
     let piece_count = pos.occupied.popcount() as u64;
 
@@ -430,78 +551,425 @@ pub fn search_until_saturated_with_history(
     pos: &Position,
     history: &[u64],
 ) -> (Option<Move>, i32, SaturationAnalyzer) {
-    let mut analyzer = SaturationAnalyzer::new();
+    search_with_limits(engine, pos, history, &SearchLimits::default(), &mut PrintObserver::default())
+}
+
+/// Search with saturation under a tournament clock
+///
+/// The derived TimeBudget replaces the node budget: the search stops at
+/// saturation, a decisive score, the (instability-stretched) soft deadline,
+/// or when the next depth is projected to overrun the hard deadline.
+pub fn search_until_saturated_with_clock(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    history: &[u64],
+    clock: &TimeControl,
+) -> (Option<Move>, i32, SaturationAnalyzer) {
+    let limits = SearchLimits { clock: Some(*clock), ..SearchLimits::default() };
+    search_with_limits(engine, pos, history, &limits, &mut PrintObserver::default())
+}
+
+/// Why iterative deepening stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StopReason {
+    /// Same-parity eval and best move converged
+    Saturated,
+    /// Score beyond the decisive threshold
+    Decisive,
+    /// Node budget exceeded
+    NodeLimit,
+    /// Next depth projected to exceed the node budget
+    ProjectedLimit,
+    /// Soft deadline (instability-stretched) reached
+    TimeSoft,
+    /// Next depth projected to overrun the hard deadline
+    TimeHard,
+    /// Depth cap reached (recommended depth or explicit limit)
+    DepthLimit,
+    /// External stop request
+    Stopped,
+}
+
+/// External limits on a saturation search
+///
+/// All fields default to "derive from the position":
+/// depth = PositionComplexity::recommended_depth, nodes = derive_node_limit.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// Depth cap (saturation may still stop earlier)
+    pub depth: Option<i32>,
+
+    /// Node budget replacing derive_node_limit
+    pub nodes: Option<u64>,
+
+    /// Fixed time per move (soft = hard = movetime)
+    pub movetime_ms: Option<u64>,
+
+    /// Tournament clock for the side to move
+    pub clock: Option<TimeControl>,
+
+    /// Search until stopped: no saturation, node or depth-derived stop
+    pub infinite: bool,
+
+    /// Checked before every root move, so a running depth is abandoned
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimits {
+    pub fn stop_requested(&self) -> bool {
+        self.stop.as_ref().is_some_and(|s| s.load(Ordering::Relaxed))
+    }
+
+    /// Deadlines for this search: movetime wins over the clock
+    pub fn time_budget(&self, pos: &Position) -> Option<TimeBudget> {
+        match (self.movetime_ms, self.clock) {
+            (Some(ms), _) => Some(TimeBudget { soft_ms: ms, hard_ms: ms }),
+            (None, Some(clock)) => Some(TimeBudget::derive(&clock, pos)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Derived parameters of a search, reported before depth 1
+#[derive(Clone, Copy, Debug)]
+pub struct SearchSetup {
+    pub complexity: i32,
+    pub max_depth: i32,
+    pub epsilon: i32,
+    pub node_limit: u64,
+    pub budget: Option<TimeBudget>,
+}
+
+/// Per-depth search statistics (the eval/move itself lives in the analyzer)
+#[derive(Clone, Copy, Debug)]
+pub struct DepthStats {
+    pub depth: i32,
+    /// Nodes since the search started (the node budget counts these)
+    pub nodes: u64,
+    pub nodes_this_depth: u64,
+    pub elapsed_ms: u64,
+    pub depth_ms: u64,
+}
+
+/// Receives search progress instead of the loop printing directly
+pub trait SearchObserver {
+    fn on_start(&mut self, _setup: &SearchSetup) {}
+    fn on_depth(&mut self, _analyzer: &SaturationAnalyzer, _stats: &DepthStats) {}
+    fn on_stop(&mut self, _reason: StopReason, _stats: &DepthStats) {}
+}
+
+/// Human-readable diagnostics on stdout (the historical behavior)
+#[derive(Default)]
+pub struct PrintObserver {
+    setup: Option<SearchSetup>,
+}
+
+impl SearchObserver for PrintObserver {
+    fn on_start(&mut self, setup: &SearchSetup) {
+        println!("Position complexity: {} (recommended depth: {}, epsilon: {}, node_limit: {})",
+            setup.complexity, setup.max_depth, setup.epsilon, setup.node_limit);
+        if let Some(budget) = setup.budget {
+            println!("Time budget: soft {}ms, hard {}ms", budget.soft_ms, budget.hard_ms);
+        }
+        self.setup = Some(*setup);
+    }
+
+    fn on_stop(&mut self, reason: StopReason, stats: &DepthStats) {
+        let Some(setup) = self.setup else { return };
+        match reason {
+            StopReason::Saturated => println!("SATURATED at depth {} (eval stable, move stable, epsilon={})",
+                stats.depth, setup.epsilon),
+            StopReason::Decisive => println!("Early termination: decisive advantage"),
+            StopReason::NodeLimit => println!("NODE_LIMIT at depth {} (searched {} nodes, limit: {})",
+                stats.depth, stats.nodes, setup.node_limit),
+            StopReason::ProjectedLimit => println!("PROJECTED_LIMIT at depth {} (this depth: {}, limit: {})",
+                stats.depth, stats.nodes_this_depth, setup.node_limit),
+            StopReason::TimeSoft => println!("TIME_SOFT at depth {} (elapsed {}ms)",
+                stats.depth, stats.elapsed_ms),
+            StopReason::TimeHard => println!("TIME_HARD at depth {} (elapsed {}ms, this depth {}ms)",
+                stats.depth, stats.elapsed_ms, stats.depth_ms),
+            StopReason::DepthLimit | StopReason::Stopped => {}
+        }
+    }
+}
+
+/// Iterative deepening until saturation or an external limit
+pub fn search_with_limits(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    history: &[u64],
+    limits: &SearchLimits,
+    observer: &mut dyn SearchObserver,
+) -> (Option<Move>, i32, SaturationAnalyzer) {
+    resume_with_limits(engine, pos, history, limits, observer, SaturationAnalyzer::new())
+}
+
+/// Continue a search from an earlier analyzer of the SAME position
+///
+/// Depths already recorded are not searched again: iteration starts at
+/// the analyzer's latest depth + 1. If those depths already converged
+/// the search stops before searching anything (ponder hit).
+pub fn resume_with_limits(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    history: &[u64],
+    limits: &SearchLimits,
+    observer: &mut dyn SearchObserver,
+    mut analyzer: SaturationAnalyzer,
+) -> (Option<Move>, i32, SaturationAnalyzer) {
+    let start = Instant::now();
 
     // Compute position complexity to set max depth
     let complexity = PositionComplexity::analyze(pos);
-    let max_depth = complexity.recommended_depth;
+    let max_depth = if limits.infinite {
+        limits.depth.unwrap_or(MAX_DEPTH)
+    } else {
+        limits.depth.unwrap_or(complexity.recommended_depth)
+    };
 
     // Derive epsilon from position (Newton's approach - controlled, not hardcoded)
     let epsilon = derive_epsilon(pos);
 
     // Derive node limit from position properties (polynomial bound)
-    let node_limit = derive_node_limit(pos);
-
-    println!("Position complexity: {} (recommended depth: {}, epsilon: {}, node_limit: {})",
-        complexity.total, max_depth, epsilon, node_limit);
+    let node_limit = limits.nodes.unwrap_or_else(|| derive_node_limit(pos));
+
+    // Time replaces the node budget when given
+    let budget = limits.time_budget(pos);
+
+    observer.on_start(&SearchSetup {
+        complexity: complexity.total,
+        max_depth,
+        epsilon,
+        node_limit,
+        budget,
+    });
+
+    let (resumed_depth, mut best_score, mut best_move) = analyzer.latest().unwrap_or((0, 0, None));
+    let nodes_at_start = engine.nodes;
+    let mut stats = DepthStats { depth: resumed_depth, nodes: 0, nodes_this_depth: 0, elapsed_ms: 0, depth_ms: 0 };
+    let mut reason = StopReason::DepthLimit;
+
+    // Resumed depths may already have converged: nothing left to search
+    if resumed_depth > 0 && !limits.infinite && analyzer.is_saturated(epsilon) {
+        analyzer.depths_until_saturation = Some(resumed_depth);
+        observer.on_stop(StopReason::Saturated, &stats);
+        return (best_move, best_score, analyzer);
+    }
 
-    let mut best_move = None;
-    let mut best_score = 0;
+    // A stop request or the hard deadline also cuts a running depth short
+    // (see search_depth); without either, each depth is one engine call
+    let hard_ms = budget.filter(|_| !limits.infinite).map(|b| b.hard_ms);
+    let mut interrupt = |_: &FrameworkSearchEngine| {
+        if limits.stop_requested() {
+            Some(StopReason::Stopped)
+        } else {
+            hard_ms.is_some_and(|ms| start.elapsed().as_millis() as u64 >= ms).then_some(StopReason::TimeHard)
+        }
+    };
+    let cuttable = hard_ms.is_some() || limits.stop.is_some();
 
-    for depth in 1..=max_depth {
+    for depth in (resumed_depth + 1)..=max_depth {
         let nodes_before = engine.nodes;
+        let depth_start = Instant::now();
 
         // Use search with history for threefold repetition detection
-        let (mv, score) = engine.search_with_history(pos, depth, history);
+        let interrupted = if cuttable { Some(&mut interrupt as _) } else { None };
+        let (mv, score) = match search_depth(engine, pos, depth, history, best_move, interrupted) {
+            Ok(result) => result,
+            Err(cut) => {
+                reason = cut;
+                break;
+            }
+        };
         analyzer.record(depth, score, mv);
 
         best_move = mv;
         best_score = score;
 
-        let nodes_this_depth = engine.nodes - nodes_before;
+        stats = DepthStats {
+            depth,
+            nodes: engine.nodes - nodes_at_start,
+            nodes_this_depth: engine.nodes - nodes_before,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+            depth_ms: depth_start.elapsed().as_millis() as u64,
+        };
+        observer.on_depth(&analyzer, &stats);
+
+        if limits.stop_requested() {
+            reason = StopReason::Stopped;
+            break;
+        }
+
+        // Infinite analysis: only an external stop ends the search
+        if limits.infinite {
+            continue;
+        }
 
         // Check for saturation with derived epsilon
         if analyzer.is_saturated(epsilon) {
-            println!("SATURATED at depth {} (eval stable, move stable, epsilon={})", depth, epsilon);
+            analyzer.depths_until_saturation = Some(depth);
+            reason = StopReason::Saturated;
             break;
         }
 
         // Early termination if clearly winning/losing
-        if score.abs() > 50000 {
-            println!("Early termination: decisive advantage");
+        if score.abs() > DECISIVE_SCORE {
+            reason = StopReason::Decisive;
             break;
         }
 
+        if let Some(budget) = budget {
+            // Soft deadline, stretched while eval/move are still swinging
+            let soft = budget.stretched_soft_ms(analyzer.instability(epsilon));
+            if stats.elapsed_ms >= soft {
+                reason = StopReason::TimeSoft;
+                break;
+            }
+
+            // Same projection as PROJECTED_LIMIT: next depth costs ~2× this one
+            if stats.elapsed_ms + stats.depth_ms * 2 > budget.hard_ms {
+                reason = StopReason::TimeHard;
+                break;
+            }
+
+            // An explicit node budget still applies alongside the clock
+            if limits.nodes.is_none() {
+                continue;
+            }
+        }
+
         // Polynomial node bound (derived from position, not hardcoded)
         // If we've exceeded our budget and searched at least 4 depths, stop
-        if engine.nodes > node_limit && depth >= 4 {
-            println!("NODE_LIMIT at depth {} (searched {} nodes, limit: {})",
-                depth, engine.nodes, node_limit);
+        // (an explicit node budget is honored from depth 1)
+        let min_depth = if limits.nodes.is_some() { 1 } else { 4 };
+        if stats.nodes > node_limit && depth >= min_depth {
+            reason = StopReason::NodeLimit;
             break;
         }
 
         // If next depth would likely exceed limit, stop early
         // Estimate: nodes roughly double per depth
-        if nodes_this_depth * 2 > node_limit && depth >= 4 {
-            println!("PROJECTED_LIMIT at depth {} (this depth: {}, limit: {})",
-                depth, nodes_this_depth, node_limit);
+        if stats.nodes_this_depth * 2 > node_limit && depth >= min_depth {
+            reason = StopReason::ProjectedLimit;
             break;
         }
     }
 
+    observer.on_stop(reason, &stats);
+
     (best_move, best_score, analyzer)
 }
 
+/// Asked before every root move: a reason abandons the running depth
+pub type Interrupt<'a> = dyn FnMut(&FrameworkSearchEngine) -> Option<StopReason> + 'a;
+
+/// Search one depth
+///
+/// Without `interrupted` this is a single engine call, with alpha-beta at
+/// the root. FrameworkSearchEngine runs a whole depth per call, though, so
+/// when a deadline or stop request must be able to cut into a running depth
+/// the depth is searched root move by root move (score_root_moves). When
+/// `interrupted` returns a reason the unfinished depth is dropped and the
+/// caller keeps the last completed one.
+pub fn search_depth(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    depth: i32,
+    history: &[u64],
+    previous_best: Option<Move>,
+    interrupted: Option<&mut Interrupt>,
+) -> Result<(Option<Move>, i32), StopReason> {
+    // Depth 1 is a single cheap call
+    let Some(interrupted) = interrupted.filter(|_| depth >= 2) else {
+        return Ok(engine.search_with_history(pos, depth, history));
+    };
+
+    let scores = score_root_moves(engine, pos, depth, history, previous_best, interrupted)?;
+    // Mate/stalemate is scored by the engine
+    if scores.is_empty() {
+        return Ok(engine.search_with_history(pos, depth, history));
+    }
+
+    let mut best = (None, i32::MIN);
+    for (mv, score) in scores {
+        if score > best.1 {
+            best = (Some(mv), score);
+        }
+    }
+    Ok(best)
+}
+
+/// Score every root move at `depth`, in search order
+///
+/// Each root move is scored by searching its child at depth-1 with a full
+/// window (negamax), the previous best move first. `interrupted` is asked
+/// before every root move.
+pub fn score_root_moves(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    depth: i32,
+    history: &[u64],
+    previous_best: Option<Move>,
+    interrupted: &mut Interrupt,
+) -> Result<Vec<(Move, i32)>, StopReason> {
+    let mut root_moves = generate_legal(pos);
+    if let Some(first) = previous_best.and_then(|mv| root_moves.iter().position(|&m| m == mv)) {
+        root_moves[..=first].rotate_right(1);
+    }
+
+    let mut child_history = history.to_vec();
+    child_history.push(pos.hash());
+
+    let mut scores = Vec::with_capacity(root_moves.len());
+    for mv in root_moves {
+        if let Some(reason) = interrupted(engine) {
+            return Err(reason);
+        }
+
+        let (_, child_score) = engine.search_with_history(&pos.make_move(mv), depth - 1, &child_history);
+        scores.push((mv, score_from_child(child_score)));
+    }
+
+    Ok(scores)
+}
+
+/// Root score of a move from its child's score (negamax)
+///
+/// A mate found below the child is one ply further from the root.
+pub fn score_from_child(child_score: i32) -> i32 {
+    match -child_score {
+        s if s > DECISIVE_SCORE => s - 1,
+        s if s < -DECISIVE_SCORE => s + 1,
+        s => s,
+    }
+}
+
 /// Search with explicit depth limit but saturation awareness
 pub fn search_with_saturation_check(
     engine: &mut FrameworkSearchEngine,
     pos: &Position,
     target_depth: i32,
 ) -> (Option<Move>, i32, bool) {
+    let (best_move, best_score, analyzer) = search_to_depth_analyzed(engine, pos, target_depth);
+
+    if let Some(depth) = analyzer.depths_until_saturation {
+        println!("Saturated at depth {} (target was {}, epsilon={})", depth, target_depth, derive_epsilon(pos));
+    }
+
+    (best_move, best_score, analyzer.depths_until_saturation.is_some())
+}
+
+/// Fixed target depth with saturation awareness, returning the analyzer
+/// (depths_until_saturation = depth at which saturation was declared)
+pub fn search_to_depth_analyzed(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    target_depth: i32,
+) -> (Option<Move>, i32, SaturationAnalyzer) {
     let mut analyzer = SaturationAnalyzer::new();
     let mut best_move = None;
     let mut best_score = 0;
-    let mut saturated = false;
 
     // Derive epsilon from position (not hardcoded!)
     let epsilon = derive_epsilon(pos);
@@ -514,13 +982,137 @@ pub fn search_with_saturation_check(
         best_score = score;
 
         if analyzer.is_saturated(epsilon) {
-            saturated = true;
-            println!("Saturated at depth {} (target was {}, epsilon={})", depth, target_depth, epsilon);
+            analyzer.depths_until_saturation = Some(depth);
             break;
         }
     }
 
-    (best_move, best_score, saturated)
+    (best_move, best_score, analyzer)
+}
+
+// ============================================================================
+// SEARCH TRACE (machine-readable)
+// ============================================================================
+
+/// One completed depth in a search trace
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DepthTrace {
+    pub depth: i32,
+    pub eval: i32,
+    /// Best move in UCI notation
+    pub best_move: Option<String>,
+    /// |E_d - E_{d-2}| (None before depth 3)
+    pub same_parity_delta: Option<i32>,
+    /// Best move equals the move from depth d-2
+    pub move_stabilized: bool,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+}
+
+/// Full record of one saturation search, one JSON object per line
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchTrace {
+    /// Caller-supplied identifier (game id, EPD id, FEN, ...)
+    pub label: Option<String>,
+    pub complexity: i32,
+    pub max_depth: i32,
+    pub epsilon: i32,
+    pub node_limit: u64,
+    pub budget: Option<TimeBudget>,
+    pub depths: Vec<DepthTrace>,
+    pub stop_reason: Option<StopReason>,
+    pub total_nodes: u64,
+    pub elapsed_ms: u64,
+}
+
+impl SearchTrace {
+    /// Serialize as a single JSON line (no trailing newline)
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).expect("SearchTrace is always serializable")
+    }
+
+    /// Append this trace as one line of a JSON-lines stream
+    pub fn write_jsonl<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", self.to_json_line())
+    }
+
+    /// Parse one line of a JSON-lines stream
+    pub fn from_json_line(line: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(line)
+    }
+}
+
+/// Observer that does nothing (for silent searches)
+pub struct SilentObserver;
+
+impl SearchObserver for SilentObserver {}
+
+/// Records a SearchTrace while forwarding every event to an inner observer
+pub struct TraceObserver<O: SearchObserver> {
+    pub inner: O,
+    pub trace: SearchTrace,
+}
+
+impl<O: SearchObserver> TraceObserver<O> {
+    pub fn new(inner: O) -> Self {
+        TraceObserver { inner, trace: SearchTrace::default() }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.trace.label = Some(label.into());
+        self
+    }
+
+    /// Finished trace (label is kept)
+    pub fn into_trace(self) -> SearchTrace {
+        self.trace
+    }
+}
+
+impl<O: SearchObserver> SearchObserver for TraceObserver<O> {
+    fn on_start(&mut self, setup: &SearchSetup) {
+        self.trace.complexity = setup.complexity;
+        self.trace.max_depth = setup.max_depth;
+        self.trace.epsilon = setup.epsilon;
+        self.trace.node_limit = setup.node_limit;
+        self.trace.budget = setup.budget;
+        self.inner.on_start(setup);
+    }
+
+    fn on_depth(&mut self, analyzer: &SaturationAnalyzer, stats: &DepthStats) {
+        if let Some((depth, eval, best_move)) = analyzer.latest() {
+            self.trace.depths.push(DepthTrace {
+                depth,
+                eval,
+                best_move: best_move.map(|mv| mv.to_string()),
+                same_parity_delta: analyzer.same_parity_delta(),
+                move_stabilized: analyzer.is_move_stabilized(),
+                nodes: stats.nodes_this_depth,
+                elapsed_ms: stats.depth_ms,
+            });
+        }
+        self.inner.on_depth(analyzer, stats);
+    }
+
+    fn on_stop(&mut self, reason: StopReason, stats: &DepthStats) {
+        self.trace.stop_reason = Some(reason);
+        self.trace.total_nodes = stats.nodes;
+        self.trace.elapsed_ms = stats.elapsed_ms;
+        self.inner.on_stop(reason, stats);
+    }
+}
+
+/// Search until saturated and return the machine-readable trace
+pub fn search_until_saturated_traced(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    history: &[u64],
+    label: Option<&str>,
+) -> (Option<Move>, i32, SearchTrace) {
+    let mut observer = TraceObserver::new(SilentObserver);
+    observer.trace.label = label.map(str::to_string);
+    let (mv, score, _) = search_with_limits(engine, pos, history, &SearchLimits::default(), &mut observer);
+    (mv, score, observer.into_trace())
 }
 
 // ============================================================================
@@ -565,6 +1157,7 @@ pub fn verify_convergence_theorem(pos: &Position, max_depth: i32) -> Convergence
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct ConvergenceProof {
     pub deltas: Vec<i32>,
     pub convergence_rate: f64,
@@ -704,18 +1297,134 @@ mod tests {
     }
 
     #[test]
-    fn test_saturation_saves_time() {
-        // Quiet position should saturate early
+    fn test_time_budget_derivation() {
         let pos = Position::starting();
-        let mut engine = FrameworkSearchEngine::new();
 
-        let (mv, score, saturated) = search_with_saturation_check(&mut engine, &pos, 10);
+        // Sudden death: horizon = piece count (32)
+        let sudden = TimeBudget::derive(&TimeControl { remaining_ms: 60_000, increment_ms: 0, moves_to_go: None }, &pos);
+        assert_eq!(sudden.soft_ms, (60_000 - MOVE_OVERHEAD_MS) / 32);
+        assert!(sudden.hard_ms >= sudden.soft_ms);
+        assert!(sudden.hard_ms <= 60_000 / 3, "Never burn more than a third of the clock");
+
+        // Increment is added to the soft target
+        let inc = TimeBudget::derive(&TimeControl { remaining_ms: 60_000, increment_ms: 1_000, moves_to_go: None }, &pos);
+        assert_eq!(inc.soft_ms, sudden.soft_ms + 1_000);
+
+        // Last move before the time control is still capped at a third
+        let last = TimeBudget::derive(&TimeControl { remaining_ms: 9_000, increment_ms: 0, moves_to_go: Some(1) }, &pos);
+        assert_eq!(last, TimeBudget { soft_ms: (9_000 - MOVE_OVERHEAD_MS) / 3, hard_ms: (9_000 - MOVE_OVERHEAD_MS) / 3 });
+
+        // Low clock, big increment: the increment must not lift either
+        // deadline past a third of what is left (3000ms + 2000ms/move)
+        let low_inc = TimeBudget::derive(&TimeControl { remaining_ms: 3_000, increment_ms: 2_000, moves_to_go: None }, &pos);
+        assert_eq!(low_inc, TimeBudget { soft_ms: 983, hard_ms: 983 });
+
+        // Comfortable clock with increment: hard stays 4× soft below the cap
+        let long_inc = TimeBudget::derive(&TimeControl { remaining_ms: 300_000, increment_ms: 2_000, moves_to_go: None }, &pos);
+        assert_eq!(long_inc.soft_ms, (300_000 - MOVE_OVERHEAD_MS) / 32 + 2_000);
+        assert_eq!(long_inc.hard_ms, long_inc.soft_ms * 4);
+
+        // Low clock, no increment
+        let low = TimeBudget::derive(&TimeControl { remaining_ms: 1_000, increment_ms: 0, moves_to_go: None }, &pos);
+        assert_eq!(low.soft_ms, 950 / 32);
+        assert_eq!(low.hard_ms, 950 / 32 * 4);
+        for budget in [sudden, inc, last, low_inc, long_inc, low] {
+            assert!(budget.soft_ms <= budget.hard_ms);
+        }
+
+        // Flagging clock never produces a deadline past the overhead margin
+        let flag = TimeBudget::derive(&TimeControl { remaining_ms: 20, increment_ms: 0, moves_to_go: None }, &pos);
+        assert_eq!(flag, TimeBudget { soft_ms: 0, hard_ms: 0 });
+    }
+
+    #[test]
+    fn test_mate_in_moves() {
+        assert_eq!(mate_in(MATE_SCORE - 1), Some(1));
+        assert_eq!(mate_in(MATE_SCORE - 4), Some(2));
+        assert_eq!(mate_in(-(MATE_SCORE - 2)), Some(-1));
+        assert_eq!(mate_in(DECISIVE_SCORE), None);
+        assert_eq!(mate_in(-350), None);
+    }
+
+    #[test]
+    fn test_instability_stretches_soft_deadline() {
+        let budget = TimeBudget { soft_ms: 1_000, hard_ms: 2_500 };
+
+        let mut stable = SaturationAnalyzer::new();
+        stable.record(1, 20, Some(Move::new(Square::E2, Square::E4)));
+        stable.record(2, 10, Some(Move::new(Square::E2, Square::E4)));
+        stable.record(3, 20, Some(Move::new(Square::E2, Square::E4)));
+        assert_eq!(stable.instability(25), 0.0);
+        assert_eq!(budget.stretched_soft_ms(stable.instability(25)), 1_000);
+
+        let mut swinging = SaturationAnalyzer::new();
+        swinging.record(1, 20, Some(Move::new(Square::E2, Square::E4)));
+        swinging.record(2, 10, Some(Move::new(Square::E2, Square::E4)));
+        swinging.record(3, 120, Some(Move::new(Square::D2, Square::D4)));
+        assert_eq!(swinging.instability(25), 2.0);
+        // 3× soft would be 3000ms, capped at the hard deadline
+        assert_eq!(budget.stretched_soft_ms(swinging.instability(25)), 2_500);
+    }
+
+    #[test]
+    fn test_trace_json_roundtrip() {
+        let mut observer = TraceObserver::new(SilentObserver).with_label("startpos");
+        observer.on_start(&SearchSetup { complexity: 4, max_depth: 10, epsilon: 25, node_limit: 50_000, budget: None });
+
+        let mut analyzer = SaturationAnalyzer::new();
+        let mut nodes = 0;
+        for (depth, eval) in [(1, 50), (2, 5), (3, 30), (4, 6)] {
+            analyzer.record(depth, eval, Some(Move::new(Square::E2, Square::E4)));
+            nodes += 100 * depth as u64;
+            let stats = DepthStats { depth, nodes, nodes_this_depth: 100 * depth as u64, elapsed_ms: 0, depth_ms: 0 };
+            observer.on_depth(&analyzer, &stats);
+        }
+        let stats = DepthStats { depth: 4, nodes, nodes_this_depth: 400, elapsed_ms: 3, depth_ms: 1 };
+        observer.on_stop(StopReason::Saturated, &stats);
+
+        let line = observer.into_trace().to_json_line();
+        assert!(!line.contains('\n'), "One trace per line");
+        assert!(line.contains("\"SATURATED\""));
+
+        let trace = SearchTrace::from_json_line(&line).unwrap();
+        assert_eq!(trace.label.as_deref(), Some("startpos"));
+        assert_eq!(trace.depths.len(), 4);
+        assert_eq!(trace.depths[1].same_parity_delta, None);
+        assert_eq!(trace.depths[2].same_parity_delta, Some(20)); // |30 - 50|
+        assert_eq!(trace.depths[3].same_parity_delta, Some(1));  // |6 - 5|
+        assert!(trace.depths[3].move_stabilized);
+        assert_eq!(trace.total_nodes, 1000);
+        assert_eq!(trace.stop_reason, Some(StopReason::Saturated));
+    }
 
-        println!("Searched to depth 10, saturated: {}", saturated);
+    #[test]
+    fn test_traced_search() {
+        let pos = Position::starting();
+        let mut engine = FrameworkSearchEngine::new();
+
+        let (mv, _, trace) = search_until_saturated_traced(&mut engine, &pos, &[], Some("startpos"));
 
-        // Starting position often saturates before depth 10
-        // (move doesn't change much after depth 5-6)
         assert!(mv.is_some());
+        assert!(trace.stop_reason.is_some());
+        assert_eq!(trace.epsilon, derive_epsilon(&pos));
+        assert_eq!(trace.depths.last().and_then(|d| d.best_move.clone()), mv.map(|m| m.to_string()));
+    }
+
+    #[test]
+    fn test_resume_from_saturated_history() {
+        let pos = Position::starting();
+        let mut engine = FrameworkSearchEngine::new();
+
+        // Ponder search already converged on this position
+        let (ponder_move, _, pondered) = search_with_limits(&mut engine, &pos, &[], &SearchLimits::default(), &mut SilentObserver);
+        assert!(pondered.depths_until_saturation.is_some(), "Start position saturates");
+
+        let nodes_before = engine.nodes;
+        let (mv, _, resumed) = resume_with_limits(&mut engine, &pos, &[], &SearchLimits::default(), &mut SilentObserver, pondered.clone());
+
+        assert_eq!(engine.nodes, nodes_before, "Ponder hit on converged history searches nothing");
+        assert_eq!(mv, ponder_move);
+        assert_eq!(resumed.depths_until_saturation, pondered.latest().map(|(d, _, _)| d));
     }
 
     #[test]
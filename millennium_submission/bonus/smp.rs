@@ -0,0 +1,547 @@
+//! Lazy-SMP Parallel Saturation Search
+//!
+//! search_until_saturated_with_history drives ONE engine through depths
+//! 1, 2, 3, ... sequentially. Here N threads search at staggered depths
+//! while ONE SaturationAnalyzer consumes the completed depths in order,
+//! whichever thread finished them. Thread 0 searches every depth; helper
+//! threads skip depths by a per-thread (size, phase) pattern, so at any
+//! moment the helpers are spread over several depths ahead of it.
+//!
+//! Node counts from every thread are summed against the derive_node_limit
+//! budget, so the polynomial bound holds for the whole search, not per thread.
+//! movetime or a clock replaces that budget with the same soft/hard deadlines
+//! resume_with_limits uses.
+//!
+//! Root-child cache: FrameworkSearchEngine keeps its transposition table
+//! private, so depths of 2+ are split at the root (each root move = its
+//! child searched at depth-1) and every child score goes into one lock-free
+//! ChildScoreCache keyed by (child hash, depth). It is not a transposition
+//! table: nothing below the root children is shared. Threads walk the root
+//! moves from different offsets and probe the cache first, so a child one
+//! thread finished is never searched again by another.
+//!
+//! Threads check the stop flag, the hard deadline and an atomic halt flag
+//! before every root move, so a stop abandons the running depth.
+
+use super::framework_search::FrameworkSearchEngine;
+use super::movegen::{generate_legal, Move};
+use super::position::Position;
+use super::saturation::{
+    derive_epsilon, derive_node_limit, score_from_child, PositionComplexity, SaturationAnalyzer, SearchLimits,
+    StopReason, TimeBudget, DECISIVE_SCORE,
+};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// ROOT-CHILD SCORE CACHE
+// ============================================================================
+
+/// Marks a written slot, so an all-zero slot never verifies
+const VALID_BIT: u64 = 1 << 40;
+
+/// Lock-free cache of root-child scores shared by all search threads
+///
+/// Only the positions one move from the root are stored, one slot per
+/// (child, depth) pair; the engine's own table stays per thread. Each slot
+/// holds two atomics: `key ^ data` and `data`. A slot torn by a concurrent
+/// store (one word from each writer) fails the XOR check and reads as a
+/// miss, so neither probe nor store takes a lock. Entries are
+/// exact-depth: a score from another depth would mix parities in the
+/// same-parity comparison.
+pub struct ChildScoreCache {
+    slots: Vec<[AtomicU64; 2]>,
+    mask: u64,
+    hits: AtomicU64,
+}
+
+impl ChildScoreCache {
+    /// Cache with at least `entries` slots (rounded up to a power of two)
+    pub fn new(entries: usize) -> Self {
+        let size = entries.max(1).next_power_of_two();
+        ChildScoreCache {
+            slots: (0..size).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect(),
+            mask: size as u64 - 1,
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    /// Zobrist hash and depth folded into one key
+    fn key(hash: u64, depth: i32) -> u64 {
+        hash ^ (depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+
+    /// Score of the position `hash` searched at exactly `depth`
+    pub fn probe(&self, hash: u64, depth: i32) -> Option<i32> {
+        let key = Self::key(hash, depth);
+        let slot = &self.slots[(key & self.mask) as usize];
+        let check = slot[0].load(Ordering::Relaxed);
+        let data = slot[1].load(Ordering::Relaxed);
+
+        if data & VALID_BIT == 0 || check ^ data != key {
+            return None;
+        }
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(data as u32 as i32)
+    }
+
+    /// Always-replace store
+    pub fn store(&self, hash: u64, depth: i32, score: i32) {
+        let key = Self::key(hash, depth);
+        let data = VALID_BIT | score as u32 as u64;
+        let slot = &self.slots[(key & self.mask) as usize];
+        slot[0].store(key ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+
+    /// Probes answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+}
+
+// ============================================================================
+// DEPTH STAGGERING
+// ============================================================================
+
+/// Helper skip pattern: helper h searches depth d unless
+/// ((d + SKIP_PHASE[i]) / SKIP_SIZE[i]) is odd, i = (h - 1) % 20.
+/// Runs of 1-4 depths at shifted phases spread the helpers over the
+/// depths ahead of the main thread instead of piling onto one.
+const SKIP_SIZE: [i32; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [i32; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+/// Does `thread_id` skip `depth`? Thread 0 (main) never skips.
+pub fn skips_depth(thread_id: usize, depth: i32) -> bool {
+    if thread_id == 0 {
+        return false;
+    }
+    let i = (thread_id - 1) % SKIP_SIZE.len();
+    ((depth + SKIP_PHASE[i]) / SKIP_SIZE[i]) % 2 != 0
+}
+
+/// Saturation state shared by all search threads
+pub struct SharedSaturation {
+    analyzer: SaturationAnalyzer,
+    /// Completed depths not yet consumed (waiting for a shallower depth)
+    pending: BTreeMap<i32, (i32, Option<Move>)>,
+    /// Deepest depth consumed by the analyzer (contiguous from 1)
+    consumed_depth: i32,
+    best: (Option<Move>, i32),
+    epsilon: i32,
+    node_limit: u64,
+    max_depth: i32,
+    /// Deadlines from movetime or the clock, measured from `start`
+    budget: Option<TimeBudget>,
+    start: Instant,
+    /// Elapsed time when the previous depth was consumed
+    consumed_ms: u64,
+    /// Nodes summed over all threads
+    pub total_nodes: u64,
+    pub stop_reason: Option<StopReason>,
+}
+
+impl SharedSaturation {
+    pub fn new(epsilon: i32, node_limit: u64, max_depth: i32) -> Self {
+        SharedSaturation {
+            analyzer: SaturationAnalyzer::new(),
+            pending: BTreeMap::new(),
+            consumed_depth: 0,
+            best: (None, 0),
+            epsilon,
+            node_limit,
+            max_depth,
+            budget: None,
+            start: Instant::now(),
+            consumed_ms: 0,
+            total_nodes: 0,
+            stop_reason: None,
+        }
+    }
+
+    /// Apply soft/hard deadlines, timed from now
+    pub fn with_budget(mut self, budget: Option<TimeBudget>) -> Self {
+        self.budget = budget;
+        self.start = Instant::now();
+        self
+    }
+
+    /// Next depth a thread should search: the first depth past both the
+    /// consumed depth and its own last one that its skip pattern allows
+    pub fn next_depth(&self, thread_id: usize, last_searched: i32) -> Option<i32> {
+        if self.stop_reason.is_some() {
+            return None;
+        }
+        let from = (self.consumed_depth + 1).max(last_searched + 1);
+        (from..=self.max_depth).find(|&depth| !skips_depth(thread_id, depth))
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop_reason.is_some()
+    }
+
+    /// A thread finished `depth`. Feeds the analyzer in depth order and
+    /// returns the stop reason once the search should end.
+    pub fn complete(&mut self, depth: i32, eval: i32, mv: Option<Move>, nodes: u64) -> Option<StopReason> {
+        self.total_nodes += nodes;
+        if depth > self.consumed_depth {
+            self.pending.entry(depth).or_insert((eval, mv));
+        }
+
+        // Same-parity comparisons need consecutive depths: consume in order
+        while self.stop_reason.is_none() {
+            let Some((eval, mv)) = self.pending.remove(&(self.consumed_depth + 1)) else { break };
+            self.consumed_depth += 1;
+            let depth = self.consumed_depth;
+
+            self.analyzer.record(depth, eval, mv);
+            self.best = (mv, eval);
+
+            if self.analyzer.is_saturated(self.epsilon) {
+                self.analyzer.depths_until_saturation = Some(depth);
+                self.stop_reason = Some(StopReason::Saturated);
+            } else if eval.abs() > DECISIVE_SCORE {
+                self.stop_reason = Some(StopReason::Decisive);
+            } else if let Some(reason) = self.time_stop() {
+                self.stop_reason = Some(reason);
+            } else if self.total_nodes > self.node_limit && depth >= 4 {
+                self.stop_reason = Some(StopReason::NodeLimit);
+            } else if depth >= self.max_depth {
+                self.stop_reason = Some(StopReason::DepthLimit);
+            }
+        }
+
+        self.stop_reason
+    }
+
+    /// Soft deadline (stretched by instability) or the projected cost of
+    /// the next depth past the hard one, as in resume_with_limits
+    fn time_stop(&mut self) -> Option<StopReason> {
+        let budget = self.budget?;
+        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        let depth_ms = elapsed_ms - self.consumed_ms;
+        self.consumed_ms = elapsed_ms;
+
+        if elapsed_ms >= budget.stretched_soft_ms(self.analyzer.instability(self.epsilon)) {
+            Some(StopReason::TimeSoft)
+        } else if elapsed_ms + depth_ms * 2 > budget.hard_ms {
+            Some(StopReason::TimeHard)
+        } else {
+            None
+        }
+    }
+
+    pub fn stop(&mut self, reason: StopReason) {
+        self.stop_reason.get_or_insert(reason);
+    }
+
+    pub fn consumed_depth(&self) -> i32 {
+        self.consumed_depth
+    }
+}
+
+/// Result of a parallel saturation search
+pub struct SmpResult {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub analyzer: SaturationAnalyzer,
+    pub stop_reason: Option<StopReason>,
+    pub total_nodes: u64,
+    pub nodes_per_thread: Vec<u64>,
+    /// Child searches answered by the root-child cache
+    pub cache_hits: u64,
+}
+
+/// One thread's search of one depth
+///
+/// Depth 1 is a single engine call. Deeper, each root move is scored from
+/// its child at depth-1, probed in / stored to the shared cache, walking
+/// the root moves from `offset` so threads on the same depth spread out.
+/// Returns None if `interrupted` fires before the depth completes.
+pub fn search_depth_shared(
+    engine: &mut FrameworkSearchEngine,
+    pos: &Position,
+    depth: i32,
+    history: &[u64],
+    cache: &ChildScoreCache,
+    offset: usize,
+    interrupted: &dyn Fn() -> bool,
+) -> Option<(Option<Move>, i32)> {
+    let legal = generate_legal(pos);
+    if depth < 2 || legal.is_empty() {
+        return Some(engine.search_with_history(pos, depth, history));
+    }
+    let mut root_moves: Vec<(usize, Move)> = legal.into_iter().enumerate().collect();
+    let len = root_moves.len();
+    root_moves.rotate_left(offset % len);
+
+    let mut child_history = history.to_vec();
+    child_history.push(pos.hash());
+
+    // (score, generation index, move): ties go to the first generated move,
+    // so the result does not depend on the offset
+    let mut best: Option<(i32, usize, Move)> = None;
+    for (index, mv) in root_moves {
+        if interrupted() {
+            return None;
+        }
+
+        let child = pos.make_move(mv);
+        let child_score = cache.probe(child.hash(), depth - 1).unwrap_or_else(|| {
+            let (_, score) = engine.search_with_history(&child, depth - 1, &child_history);
+            cache.store(child.hash(), depth - 1, score);
+            score
+        });
+
+        let score = score_from_child(child_score);
+        if best.is_none_or(|(s, i, _)| score > s || (score == s && index < i)) {
+            best = Some((score, index, mv));
+        }
+    }
+
+    best.map(|(score, _, mv)| (Some(mv), score))
+}
+
+/// Lazy-SMP search until saturated
+///
+/// Uses the depth cap, node budget, time limits and stop flag from
+/// `limits`. As in resume_with_limits, movetime or a clock replaces the
+/// derived node budget unless `nodes` is given, and the hard deadline
+/// abandons a running depth. The cache holds one slot per (root move,
+/// depth) pair, doubled so collisions stay rare.
+pub fn search_until_saturated_parallel(
+    pos: &Position,
+    history: &[u64],
+    threads: usize,
+    limits: &SearchLimits,
+) -> SmpResult {
+    let threads = threads.max(1);
+    let max_depth = limits.depth.unwrap_or_else(|| PositionComplexity::analyze(pos).recommended_depth);
+    let budget = limits.time_budget(pos).filter(|_| !limits.infinite);
+    let node_limit = match (limits.nodes, budget) {
+        (Some(nodes), _) => nodes,
+        (None, Some(_)) => u64::MAX,
+        (None, None) => derive_node_limit(pos),
+    };
+    let shared = Mutex::new(SharedSaturation::new(derive_epsilon(pos), node_limit, max_depth).with_budget(budget));
+    // Polled before every root move, so neither takes the lock
+    let halted = AtomicBool::new(false);
+    let deadline = budget.map(|b| Instant::now() + Duration::from_millis(b.hard_ms));
+    let past_deadline = || deadline.is_some_and(|d| Instant::now() >= d);
+
+    let root_count = generate_legal(pos).len().max(1);
+    let cache = ChildScoreCache::new(root_count * max_depth.max(1) as usize * 2);
+
+    let nodes_per_thread: Vec<u64> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|id| {
+            let (shared, cache, halted, past_deadline) = (&shared, &cache, &halted, &past_deadline);
+            scope.spawn(move || {
+                let mut engine = FrameworkSearchEngine::new();
+                let offset = id * root_count / threads;
+                let interrupted = || limits.stop_requested() || halted.load(Ordering::Relaxed) || past_deadline();
+                let mut last_searched = 0;
+
+                loop {
+                    let Some(depth) = shared.lock().unwrap().next_depth(id, last_searched) else { break };
+
+                    let nodes_before = engine.nodes;
+                    let result = search_depth_shared(&mut engine, pos, depth, history, cache, offset, &interrupted);
+                    last_searched = depth;
+
+                    let mut state = shared.lock().unwrap();
+                    if limits.stop_requested() {
+                        state.stop(StopReason::Stopped);
+                    }
+                    let nodes = engine.nodes - nodes_before;
+                    let stopped = match result {
+                        Some((mv, score)) => state.complete(depth, score, mv, nodes).is_some(),
+                        None => {
+                            if past_deadline() {
+                                state.stop(StopReason::TimeHard);
+                            }
+                            state.total_nodes += nodes;
+                            true
+                        }
+                    };
+                    if stopped {
+                        halted.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                }
+
+                engine.nodes
+            })
+        }).collect();
+
+        handles.into_iter().map(|h| h.join().unwrap_or(0)).collect()
+    });
+
+    let state = shared.into_inner().unwrap();
+    SmpResult {
+        best_move: state.best.0,
+        score: state.best.1,
+        stop_reason: state.stop_reason,
+        total_nodes: state.total_nodes,
+        nodes_per_thread,
+        cache_hits: cache.hits(),
+        analyzer: state.analyzer,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bitboard::Square;
+
+    #[test]
+    fn test_out_of_order_depths_consumed_in_order() {
+        let e4 = Some(Move::new(Square::E2, Square::E4));
+        let mut shared = SharedSaturation::new(15, 1_000_000, 20);
+
+        // Helper finishes depth 2 before the main thread finishes depth 1
+        assert_eq!(shared.complete(2, 5, e4, 100), None);
+        assert_eq!(shared.consumed_depth(), 0, "Depth 2 waits for depth 1");
+        assert_eq!(shared.complete(1, 50, e4, 10), None);
+        assert_eq!(shared.consumed_depth(), 2);
+
+        // Duplicate depth from the other thread is ignored, nodes still count
+        assert_eq!(shared.complete(2, 999, e4, 100), None);
+        assert_eq!(shared.total_nodes, 210);
+
+        assert_eq!(shared.complete(4, 40, e4, 400), None);
+        assert_eq!(shared.complete(3, 30, e4, 300), None);
+        assert_eq!(shared.complete(5, 28, e4, 500), None);
+        assert_eq!(shared.complete(6, 38, e4, 600), Some(StopReason::Saturated));
+        assert_eq!(shared.analyzer.depths_until_saturation, Some(6));
+        assert_eq!(shared.next_depth(0, 6), None, "No new work after saturation");
+    }
+
+    #[test]
+    fn test_node_budget_is_global() {
+        let mut shared = SharedSaturation::new(1, 1_000, 20);
+        let (a, b) = (Some(Move::new(Square::E2, Square::E4)), Some(Move::new(Square::D2, Square::D4)));
+
+        // Two threads each stay under the budget, together they exceed it
+        shared.complete(1, 10, a, 100);
+        shared.complete(2, -40, b, 300);
+        shared.complete(3, 90, a, 300);
+        assert_eq!(shared.complete(4, -80, b, 400), Some(StopReason::NodeLimit));
+    }
+
+    #[test]
+    fn test_time_budget_stops_consumed_depth() {
+        let e4 = Some(Move::new(Square::E2, Square::E4));
+        let budget = TimeBudget { soft_ms: 0, hard_ms: 0 };
+        let mut shared = SharedSaturation::new(15, u64::MAX, 20).with_budget(Some(budget));
+
+        // A completed depth waiting for depth 1 is not timed yet
+        assert_eq!(shared.complete(2, 10, e4, 100), None);
+        assert_eq!(shared.complete(1, 80, e4, 10), Some(StopReason::TimeSoft));
+        assert_eq!(shared.consumed_depth(), 1, "Nothing consumed past the stop");
+    }
+
+    #[test]
+    fn test_staggered_depths() {
+        let shared = SharedSaturation::new(15, 1_000, 20);
+
+        // Main thread walks every depth
+        assert_eq!(shared.next_depth(0, 0), Some(1));
+        assert_eq!(shared.next_depth(0, 1), Some(2));
+
+        // Helpers follow their skip patterns: 1 takes even depths, 2 odd
+        assert_eq!(shared.next_depth(1, 0), Some(2));
+        assert_eq!(shared.next_depth(1, 2), Some(4), "Never repeat own depth, skip odd");
+        assert_eq!(shared.next_depth(2, 0), Some(1));
+        assert_eq!(shared.next_depth(2, 1), Some(3));
+
+        // Eight helpers start spread over several depths
+        let first: BTreeMap<i32, usize> = (1..=8).fold(BTreeMap::new(), |mut counts, id| {
+            *counts.entry(shared.next_depth(id, 0).unwrap()).or_default() += 1;
+            counts
+        });
+        assert_eq!(first.into_iter().collect::<Vec<_>>(), vec![(1, 5), (2, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn test_child_cache_verifies_keys() {
+        let cache = ChildScoreCache::new(64);
+        assert_eq!(cache.probe(0xABCD, 3), None, "Empty slot is a miss");
+
+        cache.store(0xABCD, 3, -250);
+        assert_eq!(cache.probe(0xABCD, 3), Some(-250));
+        assert_eq!(cache.probe(0xABCD, 4), None, "Entries are exact-depth");
+        assert_eq!(cache.probe(0xABCD + 64, 3), None, "Same slot, other position");
+
+        // A torn slot (data word from a different store) fails the XOR check
+        let key = ChildScoreCache::key(0xABCD, 3);
+        cache.slots[(key & cache.mask) as usize][1].store(VALID_BIT | 17, Ordering::Relaxed);
+        assert_eq!(cache.probe(0xABCD, 3), None);
+        assert_eq!(cache.hits(), 1);
+    }
+
+    #[test]
+    fn test_cache_shared_between_threads() {
+        let cache = ChildScoreCache::new(1024);
+
+        thread::scope(|scope| {
+            scope.spawn(|| (0..100).for_each(|h| cache.store(h * 7919, 5, h as i32))).join().unwrap();
+            let seen = scope.spawn(|| (0..100).filter(|&h| cache.probe(h * 7919, 5) == Some(h as i32)).count());
+            assert_eq!(seen.join().unwrap(), 100, "Every store is visible to the other thread");
+        });
+    }
+
+    #[test]
+    fn test_depth_reused_by_another_thread() {
+        let pos = Position::starting();
+        let cache = ChildScoreCache::new(256);
+        let never = || false;
+
+        // Thread A searches depth 3; thread B, with a fresh engine and a
+        // different root offset, answers every child from A's entries
+        let (first, nodes_a) = thread::scope(|scope| scope.spawn(|| {
+            let mut engine = FrameworkSearchEngine::new();
+            let result = search_depth_shared(&mut engine, &pos, 3, &[], &cache, 0, &never);
+            (result, engine.nodes)
+        }).join().unwrap());
+        let (second, nodes_b) = thread::scope(|scope| scope.spawn(|| {
+            let mut engine = FrameworkSearchEngine::new();
+            let result = search_depth_shared(&mut engine, &pos, 3, &[], &cache, 7, &never);
+            (result, engine.nodes)
+        }).join().unwrap());
+
+        assert!(nodes_a > 0);
+        assert_eq!(nodes_b, 0, "Second thread searched nothing itself");
+        assert_eq!(first, second);
+        assert_eq!(cache.hits() as usize, generate_legal(&pos).len());
+    }
+
+    #[test]
+    fn test_parallel_search_finds_move() {
+        let pos = Position::starting();
+        let result = search_until_saturated_parallel(&pos, &[], 4, &SearchLimits::default());
+
+        assert!(result.best_move.is_some());
+        assert_eq!(result.nodes_per_thread.len(), 4);
+        assert!(result.stop_reason.is_some());
+    }
+
+    #[test]
+    fn test_parallel_search_honors_movetime() {
+        let pos = Position::starting();
+        let limits = SearchLimits { depth: Some(64), movetime_ms: Some(50), ..SearchLimits::default() };
+        let result = search_until_saturated_parallel(&pos, &[], 2, &limits);
+
+        assert!(result.best_move.is_some());
+        assert!(matches!(
+            result.stop_reason,
+            Some(StopReason::TimeSoft | StopReason::TimeHard | StopReason::Saturated | StopReason::Decisive)
+        ), "{:?}", result.stop_reason);
+    }
+}
@@ -0,0 +1,454 @@
+//! Spectral Front End - FFT, Band Energies, Mel and MFCC
+//!
+//! compute_band_energies used a naive DFT: O(n²) per 20ms window.
+//! SpectralAnalyzer is built ONCE per (window length, sample rate):
+//!
+//! - window function, FFT twiddles and bit-reversal table precomputed
+//! - exact n-point DFT in O(n log n): radix-2 for powers of two, Bluestein
+//!   otherwise (a 20ms window at 16 kHz is 320 samples), no zero-padding
+//! - ONE power spectrum per frame, shared by band energies, mel and MFCC
+//!
+//! Power is normalized by (Σw)², so the default rectangular window gives
+//! exactly the old |X|²/n² on the old 50 Hz bins: band energies, and every
+//! absolute dB threshold applied to them, are unchanged.
+
+use std::f32::consts::PI;
+
+/// Band edges in Hz: LOW = F1 range, MID = F2 range, HIGH = F3 + fricatives
+pub const LOW_BAND_HZ: f32 = 500.0;
+pub const MID_BAND_HZ: f32 = 2000.0;
+pub const HIGH_BAND_HZ: f32 = 8000.0;
+
+/// Mel filters / cepstral coefficients (standard speech front-end sizes)
+pub const MEL_FILTERS: usize = 26;
+pub const MFCC_COEFFS: usize = 13;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowFunction {
+    Rectangular,
+    Hann,
+    Hamming,
+}
+
+impl WindowFunction {
+    pub fn coefficients(self, len: usize) -> Vec<f32> {
+        let denom = (len.max(2) - 1) as f32;
+        (0..len).map(|i| {
+            let phase = 2.0 * PI * i as f32 / denom;
+            match self {
+                WindowFunction::Rectangular => 1.0,
+                WindowFunction::Hann => 0.5 - 0.5 * phase.cos(),
+                WindowFunction::Hamming => 0.54 - 0.46 * phase.cos(),
+            }
+        }).collect()
+    }
+}
+
+/// Power spectrum of one frame: bins 0..=fft_len/2
+#[derive(Clone, Debug)]
+pub struct Spectrum {
+    pub power: Vec<f32>,
+    pub bin_hz: f32,
+}
+
+impl Spectrum {
+    /// Mean power per bin in [lo_hz, hi_hz), DC and Nyquist excluded
+    pub fn band_power(&self, lo_hz: f32, hi_hz: f32) -> f32 {
+        let bins: Vec<f32> = (1..self.power.len() - 1)
+            .filter(|&k| {
+                let freq = k as f32 * self.bin_hz;
+                freq >= lo_hz && freq < hi_hz
+            })
+            .map(|k| self.power[k])
+            .collect();
+
+        // Average energy per bin (spectral density): bands comparable regardless of bandwidth
+        if bins.is_empty() { 0.0 } else { bins.iter().sum::<f32>() / bins.len() as f32 }
+    }
+
+    /// LOW-MID-HIGH band energies in dB (formant-like)
+    pub fn band_energies_db(&self) -> (f32, f32, f32) {
+        let to_db = |e: f32| if e > 1e-10 { 10.0 * e.log10() } else { -100.0 };
+        (
+            to_db(self.band_power(0.0, LOW_BAND_HZ)),
+            to_db(self.band_power(LOW_BAND_HZ, MID_BAND_HZ)),
+            to_db(self.band_power(MID_BAND_HZ, HIGH_BAND_HZ)),
+        )
+    }
+}
+
+/// Radix-2 FFT tables for one power-of-two length
+struct Radix2 {
+    /// (cos, sin) of -2πk/len for k < len/2
+    twiddles: Vec<(f32, f32)>,
+    bit_reverse: Vec<usize>,
+}
+
+impl Radix2 {
+    fn new(len: usize) -> Self {
+        let bits = len.trailing_zeros();
+        Radix2 {
+            twiddles: (0..len / 2).map(|k| {
+                let angle = -2.0 * PI * k as f32 / len as f32;
+                (angle.cos(), angle.sin())
+            }).collect(),
+            bit_reverse: (0..len).map(|i| i.reverse_bits() >> (usize::BITS - bits)).collect(),
+        }
+    }
+
+    /// In-place forward transform (iterative Cooley-Tukey)
+    fn transform(&self, re: &mut [f32], im: &mut [f32]) {
+        let n = re.len();
+        for (i, &j) in self.bit_reverse.iter().enumerate() {
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut size = 2;
+        while size <= n {
+            let half = size / 2;
+            let stride = n / size;
+            for start in (0..n).step_by(size) {
+                for j in 0..half {
+                    let (c, s) = self.twiddles[j * stride];
+                    let (a, b) = (start + j, start + j + half);
+                    let tr = re[b] * c - im[b] * s;
+                    let ti = re[b] * s + im[b] * c;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            size *= 2;
+        }
+    }
+}
+
+/// Exact n-point DFT for any n
+///
+/// Power of two: radix-2 directly. Otherwise Bluestein's chirp-z: the DFT
+/// becomes a circular convolution with the chirp e^(iπk²/n), done with
+/// radix-2 FFTs of length ≥ 2n-1. Still O(n log n).
+enum Dft {
+    Radix2(Radix2),
+    Bluestein {
+        fft: Radix2,
+        /// e^(-iπk²/n) for k < n
+        chirp: Vec<(f32, f32)>,
+        /// FFT of the conjugate chirp laid out for circular convolution
+        kernel: (Vec<f32>, Vec<f32>),
+    },
+}
+
+impl Dft {
+    fn new(n: usize) -> Self {
+        if n.is_power_of_two() {
+            return Dft::Radix2(Radix2::new(n));
+        }
+
+        let m = (2 * n - 1).next_power_of_two();
+        let fft = Radix2::new(m);
+        // k² mod 2n keeps the angle small enough for f32
+        let chirp: Vec<(f32, f32)> = (0..n).map(|k| {
+            let angle = -PI * ((k * k) % (2 * n)) as f32 / n as f32;
+            (angle.cos(), angle.sin())
+        }).collect();
+
+        let (mut kre, mut kim) = (vec![0.0f32; m], vec![0.0f32; m]);
+        for (k, &(c, s)) in chirp.iter().enumerate() {
+            kre[k] = c;
+            kim[k] = -s;
+            if k > 0 {
+                kre[m - k] = c;
+                kim[m - k] = -s;
+            }
+        }
+        fft.transform(&mut kre, &mut kim);
+
+        Dft::Bluestein { fft, chirp, kernel: (kre, kim) }
+    }
+
+    /// In-place DFT of `re` + i·`im` (both of length n)
+    fn transform(&self, re: &mut [f32], im: &mut [f32]) {
+        match self {
+            Dft::Radix2(fft) => fft.transform(re, im),
+            Dft::Bluestein { fft, chirp, kernel } => {
+                let m = kernel.0.len();
+                let (mut ar, mut ai) = (vec![0.0f32; m], vec![0.0f32; m]);
+                for (k, &(c, s)) in chirp.iter().enumerate() {
+                    ar[k] = re[k] * c - im[k] * s;
+                    ai[k] = re[k] * s + im[k] * c;
+                }
+                fft.transform(&mut ar, &mut ai);
+
+                // Pointwise product, then inverse FFT as conj(FFT(conj(x))) / m
+                for k in 0..m {
+                    let (br, bi) = (kernel.0[k], kernel.1[k]);
+                    let (xr, xi) = (ar[k], ai[k]);
+                    ar[k] = xr * br - xi * bi;
+                    ai[k] = -(xr * bi + xi * br);
+                }
+                fft.transform(&mut ar, &mut ai);
+
+                for (k, &(c, s)) in chirp.iter().enumerate() {
+                    let (xr, xi) = (ar[k] / m as f32, -ai[k] / m as f32);
+                    re[k] = xr * c - xi * s;
+                    im[k] = xr * s + xi * c;
+                }
+            }
+        }
+    }
+}
+
+/// Spectral analysis with everything that depends only on the frame size precomputed
+pub struct SpectralAnalyzer {
+    pub window_len: usize,
+    /// DFT points = window length (no zero-padding): bins are sample_rate / window_len apart
+    pub fft_len: usize,
+    pub sample_rate: u32,
+    window: Vec<f32>,
+    /// 1 / (Σw)²
+    norm: f32,
+    dft: Dft,
+    mel: MelFilterbank,
+}
+
+impl SpectralAnalyzer {
+    /// Rectangular window: exactly the band energies of the original naive
+    /// DFT, which the absolute dB thresholds of the classifiers
+    /// (high > low + 10, ...) were tuned against
+    pub fn new(window_len: usize, sample_rate: u32) -> Self {
+        Self::with_window(window_len, sample_rate, WindowFunction::Rectangular)
+    }
+
+    pub fn with_window(window_len: usize, sample_rate: u32, function: WindowFunction) -> Self {
+        let fft_len = window_len.max(2);
+        let window = function.coefficients(window_len);
+        let sum: f32 = window.iter().sum();
+
+        SpectralAnalyzer {
+            window_len,
+            fft_len,
+            sample_rate,
+            norm: if sum > 0.0 { 1.0 / (sum * sum) } else { 0.0 },
+            window,
+            dft: Dft::new(fft_len),
+            mel: MelFilterbank::new(MEL_FILTERS, fft_len, sample_rate),
+        }
+    }
+
+    /// Windowed power spectrum of `frame` (extra samples ignored, missing ones are 0)
+    pub fn power_spectrum(&self, frame: &[f32]) -> Spectrum {
+        let n = self.fft_len;
+        let mut re = vec![0.0f32; n];
+        let mut im = vec![0.0f32; n];
+        for (i, (&x, &w)) in frame.iter().zip(&self.window).enumerate() {
+            re[i] = x * w;
+        }
+        self.dft.transform(&mut re, &mut im);
+
+        Spectrum {
+            power: (0..=n / 2).map(|k| (re[k] * re[k] + im[k] * im[k]) * self.norm).collect(),
+            bin_hz: self.sample_rate as f32 / n as f32,
+        }
+    }
+
+    /// Log mel-filterbank energies of a spectrum
+    pub fn log_mel(&self, spectrum: &Spectrum) -> Vec<f32> {
+        self.mel.apply(&spectrum.power)
+    }
+
+    /// MFCCs (DCT-II of the log mel energies)
+    pub fn mfcc(&self, spectrum: &Spectrum) -> Vec<f32> {
+        dct_ii(&self.log_mel(spectrum), MFCC_COEFFS)
+    }
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Triangular filters equally spaced on the mel scale up to min(Nyquist, HIGH band)
+pub struct MelFilterbank {
+    /// Per filter: (first bin, weights)
+    filters: Vec<(usize, Vec<f32>)>,
+}
+
+impl MelFilterbank {
+    pub fn new(count: usize, fft_len: usize, sample_rate: u32) -> Self {
+        let bins = fft_len / 2 + 1;
+        let bin_hz = sample_rate as f32 / fft_len as f32;
+        let top = hz_to_mel((sample_rate as f32 / 2.0).min(HIGH_BAND_HZ));
+        let edges: Vec<f32> = (0..count + 2).map(|i| mel_to_hz(top * i as f32 / (count + 1) as f32)).collect();
+
+        let filters = edges.windows(3).map(|e| {
+            let (lo, mid, hi) = (e[0], e[1], e[2]);
+            let first = (lo / bin_hz).floor() as usize;
+            let last = ((hi / bin_hz).ceil() as usize).min(bins - 1);
+            let weights = (first..=last).map(|k| {
+                let f = k as f32 * bin_hz;
+                if f <= lo || f >= hi {
+                    0.0
+                } else if f <= mid {
+                    (f - lo) / (mid - lo)
+                } else {
+                    (hi - f) / (hi - mid)
+                }
+            }).collect();
+            (first, weights)
+        }).collect();
+
+        MelFilterbank { filters }
+    }
+
+    /// Log energy per filter (floored at 1e-10 so silence stays finite)
+    pub fn apply(&self, power: &[f32]) -> Vec<f32> {
+        self.filters.iter().map(|(first, weights)| {
+            let energy: f32 = weights.iter().enumerate()
+                .map(|(i, w)| w * power.get(first + i).copied().unwrap_or(0.0))
+                .sum();
+            energy.max(1e-10).ln()
+        }).collect()
+    }
+}
+
+/// First `count` DCT-II coefficients
+fn dct_ii(input: &[f32], count: usize) -> Vec<f32> {
+    let n = input.len() as f32;
+    (0..count.min(input.len())).map(|k| {
+        input.iter().enumerate()
+            .map(|(i, &x)| x * (PI * k as f32 * (i as f32 + 0.5) / n).cos())
+            .sum()
+    }).collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, len: usize, rate: u32) -> Vec<f32> {
+        (0..len).map(|i| (2.0 * PI * freq * i as f32 / rate as f32).sin()).collect()
+    }
+
+    #[test]
+    fn test_fft_matches_naive_dft() {
+        let analyzer = SpectralAnalyzer::with_window(64, 16000, WindowFunction::Rectangular);
+        let frame: Vec<f32> = (0..64).map(|i| ((i * 7 % 13) as f32 - 6.0) / 6.0).collect();
+        let spectrum = analyzer.power_spectrum(&frame);
+
+        for k in 0..=32 {
+            let (mut re, mut im) = (0.0f32, 0.0f32);
+            for (i, &x) in frame.iter().enumerate() {
+                let angle = -2.0 * PI * k as f32 * i as f32 / 64.0;
+                re += x * angle.cos();
+                im += x * angle.sin();
+            }
+            let naive = (re * re + im * im) / (64.0 * 64.0);
+            assert!((spectrum.power[k] - naive).abs() < 1e-4, "bin {}", k);
+        }
+    }
+
+    /// The original compute_band_energies (naive DFT, n bins, rectangular)
+    fn naive_band_energies(window: &[f32], sample_rate: u32) -> (f32, f32, f32) {
+        let n = window.len();
+        let mut sums = [(0.0f32, 0usize); 3];
+        for k in 1..n / 2 {
+            let freq = k as f32 * sample_rate as f32 / n as f32;
+            let (mut re, mut im) = (0.0f32, 0.0f32);
+            for (i, &x) in window.iter().enumerate() {
+                let angle = -2.0 * PI * k as f32 * i as f32 / n as f32;
+                re += x * angle.cos();
+                im += x * angle.sin();
+            }
+            let band = if freq < LOW_BAND_HZ { 0 } else if freq < MID_BAND_HZ { 1 } else if freq < HIGH_BAND_HZ { 2 } else { continue };
+            sums[band].0 += (re * re + im * im) / (n * n) as f32;
+            sums[band].1 += 1;
+        }
+        let db = |(e, c): (f32, usize)| {
+            let e = if c > 0 { e / c as f32 } else { e };
+            if e > 1e-10 { 10.0 * e.log10() } else { -100.0 }
+        };
+        (db(sums[0]), db(sums[1]), db(sums[2]))
+    }
+
+    #[test]
+    fn test_bluestein_matches_naive_dft() {
+        // 320 = 20ms at 16 kHz, not a power of two
+        let analyzer = SpectralAnalyzer::with_window(320, 16000, WindowFunction::Rectangular);
+        let frame: Vec<f32> = (0..320).map(|i| ((i * 7 % 13) as f32 - 6.0) / 6.0).collect();
+        let spectrum = analyzer.power_spectrum(&frame);
+        assert_eq!(spectrum.power.len(), 161);
+
+        for k in 0..=160 {
+            let (mut re, mut im) = (0.0f64, 0.0f64);
+            for (i, &x) in frame.iter().enumerate() {
+                let angle = -2.0 * std::f64::consts::PI * (k * i) as f64 / 320.0;
+                re += x as f64 * angle.cos();
+                im += x as f64 * angle.sin();
+            }
+            let naive = ((re * re + im * im) / (320.0 * 320.0)) as f32;
+            assert!((spectrum.power[k] - naive).abs() < 1e-5 + naive * 1e-3, "bin {}", k);
+        }
+    }
+
+    #[test]
+    fn test_default_bands_match_original_dft() {
+        // Absolute thresholds downstream (high > low + 10 dB, ...) rely on
+        // the default analyzer reproducing the original band energies
+        let analyzer = SpectralAnalyzer::new(320, 16000);
+        let noise: Vec<f32> = (0..320u32).map(|i| (i.wrapping_mul(2654435761) >> 16) as f32 / 32768.0 - 1.0).collect();
+        let vowel: Vec<f32> = (0..320).map(|i| {
+            let t = i as f32 / 16000.0;
+            (2.0 * PI * 700.0 * t).sin() + 0.5 * (2.0 * PI * 1200.0 * t).sin() + 0.1 * (2.0 * PI * 2600.0 * t).sin()
+        }).collect();
+
+        for frame in [sine(250.0, 320, 16000), sine(4000.0, 320, 16000), noise, vowel] {
+            let (low, mid, high) = analyzer.power_spectrum(&frame).band_energies_db();
+            let (nl, nm, nh) = naive_band_energies(&frame, 16000);
+            for (fast, slow) in [(low, nl), (mid, nm), (high, nh)] {
+                assert!((fast - slow).abs() < 0.05, "{} dB vs original {} dB", fast, slow);
+            }
+        }
+    }
+
+    #[test]
+    fn test_band_energies_follow_frequency() {
+        // 20ms window at 16 kHz = 320 samples, 50 Hz bins
+        let analyzer = SpectralAnalyzer::new(320, 16000);
+        assert_eq!(analyzer.fft_len, 320);
+
+        let (low, mid, high) = analyzer.power_spectrum(&sine(250.0, 320, 16000)).band_energies_db();
+        assert!(low > mid && low > high);
+
+        let (low, mid, high) = analyzer.power_spectrum(&sine(4000.0, 320, 16000)).band_energies_db();
+        assert!(high > mid && high > low);
+    }
+
+    #[test]
+    fn test_mel_and_mfcc_shapes() {
+        let analyzer = SpectralAnalyzer::new(320, 16000);
+        let spectrum = analyzer.power_spectrum(&sine(1000.0, 320, 16000));
+
+        let mel = analyzer.log_mel(&spectrum);
+        assert_eq!(mel.len(), MEL_FILTERS);
+        assert!(mel.iter().all(|x| x.is_finite()));
+
+        let mfcc = analyzer.mfcc(&spectrum);
+        assert_eq!(mfcc.len(), MFCC_COEFFS);
+
+        // Silence stays finite (floored log)
+        let silent = analyzer.mfcc(&analyzer.power_spectrum(&[0.0; 320]));
+        assert!(silent.iter().all(|x| x.is_finite()));
+    }
+}
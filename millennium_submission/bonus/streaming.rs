@@ -0,0 +1,341 @@
+//! Streaming Transcription for Live Captioning
+//!
+//! transcribe / transcribe_detailed saturate a WHOLE utterance at once.
+//! StreamingTranscriber accepts audio chunks and closes an utterance when
+//! trailing silence follows speech:
+//!
+//! - Partial: the open (unclosed) audio, re-decoded every partial_interval_ms
+//! - Final:   the closed audio, decoded once; its segments never change again
+//!
+//! All offsets are milliseconds since the start of the stream. Closed audio
+//! is dropped and the open buffer is force-closed at max_open_ms, so memory
+//! stays bounded however long the stream runs. A forced close cuts at the
+//! quietest frame of the later half of the buffer, not mid-word at the limit.
+
+use super::phoneme::Phoneme;
+use super::saturated_transcriber::{LocatedSegment, SaturatedTranscriber, TimedWord};
+use std::fmt;
+
+/// Frame energy below this is silence (same threshold as saturate_segment_boundaries)
+pub const SILENCE_DB: f32 = -35.0;
+
+/// Analysis frame for silence tracking (the segmenter's hop)
+const FRAME_MS: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct StreamConfig {
+    /// Trailing silence that closes an utterance
+    pub min_closing_silence_ms: usize,
+    /// New audio between partial hypotheses
+    pub partial_interval_ms: usize,
+    /// Open audio is force-closed beyond this (memory bound)
+    pub max_open_ms: usize,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        // Closing silence: longer than a stop closure (~100ms), shorter than a pause between phrases
+        StreamConfig { min_closing_silence_ms: 300, partial_interval_ms: 500, max_open_ms: 15_000 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StreamError {
+    /// Stream offsets are sample counts divided by the rate
+    ZeroSampleRate,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::ZeroSampleRate => write!(f, "sample rate must be positive"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HypothesisKind {
+    Partial,
+    Final,
+}
+
+/// Phoneme and word hypothesis for a span of the stream
+#[derive(Clone, Debug)]
+pub struct StreamHypothesis {
+    pub kind: HypothesisKind,
+    pub start_ms: usize,
+    pub end_ms: usize,
+    pub text: String,
+    pub words: Vec<TimedWord>,
+    pub phonemes: Vec<(Phoneme, usize, usize)>,
+    pub segments: Vec<LocatedSegment>,
+}
+
+pub struct StreamingTranscriber {
+    pub transcriber: SaturatedTranscriber,
+    pub config: StreamConfig,
+    sample_rate: u32,
+    /// Open audio not yet closed into a Final hypothesis
+    buffer: Vec<f32>,
+    /// Stream position (samples) of buffer[0]
+    buffer_offset: u64,
+    /// Samples of `buffer` already classified into frames
+    scanned: usize,
+    /// Energy (dB) of every scanned frame of `buffer`
+    frame_db: Vec<f32>,
+    speech_seen: bool,
+    /// Buffer index where the current run of silent frames starts
+    silence_start: Option<usize>,
+    since_partial: usize,
+}
+
+impl StreamingTranscriber {
+    pub fn new(transcriber: SaturatedTranscriber, sample_rate: u32) -> Result<Self, StreamError> {
+        Self::with_config(transcriber, sample_rate, StreamConfig::default())
+    }
+
+    pub fn with_config(transcriber: SaturatedTranscriber, sample_rate: u32, config: StreamConfig) -> Result<Self, StreamError> {
+        if sample_rate == 0 {
+            return Err(StreamError::ZeroSampleRate);
+        }
+
+        Ok(StreamingTranscriber {
+            transcriber,
+            config,
+            sample_rate,
+            buffer: Vec::new(),
+            buffer_offset: 0,
+            scanned: 0,
+            frame_db: Vec::new(),
+            speech_seen: false,
+            silence_start: None,
+            since_partial: 0,
+        })
+    }
+
+    fn samples(&self, ms: usize) -> usize {
+        ms * self.sample_rate as usize / 1000
+    }
+
+    fn frame_len(&self) -> usize {
+        self.samples(FRAME_MS).max(1)
+    }
+
+    fn stream_ms(&self, buffer_index: usize) -> usize {
+        ((self.buffer_offset + buffer_index as u64) * 1000 / self.sample_rate as u64) as usize
+    }
+
+    /// Feed a chunk; returns the hypotheses it produced (Finals first)
+    pub fn push(&mut self, chunk: &[f32]) -> Vec<StreamHypothesis> {
+        let mut out = Vec::new();
+        self.buffer.extend_from_slice(chunk);
+        self.since_partial += chunk.len();
+
+        let frame = self.frame_len();
+        let closing = self.samples(self.config.min_closing_silence_ms);
+
+        while self.scanned + frame <= self.buffer.len() {
+            let window = &self.buffer[self.scanned..self.scanned + frame];
+            let energy = window.iter().map(|x| x * x).sum::<f32>() / frame as f32;
+            let energy_db = if energy > 0.0 { 10.0 * energy.log10() } else { -60.0 };
+
+            self.frame_db.push(energy_db);
+
+            if energy_db < SILENCE_DB {
+                self.silence_start.get_or_insert(self.scanned);
+            } else {
+                self.speech_seen = true;
+                self.silence_start = None;
+            }
+            self.scanned += frame;
+
+            if let Some(silence) = self.silence_start {
+                let silent_len = self.scanned - silence;
+                // Close mid-silence: the utterance keeps half its trailing silence
+                let cut = silence + (closing / 2 / frame) * frame;
+
+                if self.speech_seen && silent_len >= closing {
+                    out.push(self.close(cut));
+                } else if !self.speech_seen && silent_len >= closing {
+                    // Leading silence carries nothing: drop it
+                    self.drain(cut);
+                }
+            }
+
+            if self.scanned >= self.samples(self.config.max_open_ms) {
+                let cut = self.quietest_cut();
+                out.push(self.close(cut));
+            }
+        }
+
+        if self.speech_seen && self.since_partial >= self.samples(self.config.partial_interval_ms) {
+            self.since_partial = 0;
+            out.push(self.hypothesis(HypothesisKind::Partial, self.buffer.len()));
+        }
+
+        out
+    }
+
+    /// End of stream: close whatever speech is still open
+    pub fn finish(&mut self) -> Option<StreamHypothesis> {
+        if !self.speech_seen {
+            return None;
+        }
+        Some(self.close(self.buffer.len()))
+    }
+
+    /// Start of the quietest scanned frame in the later half of the buffer
+    /// (latest on ties), so a forced close lands in a pause if there is one
+    /// and the next utterance starts with at most half of max_open_ms
+    fn quietest_cut(&self) -> usize {
+        let frames = self.frame_db.len();
+        let quietest = (frames / 2..frames)
+            .rev()
+            .min_by(|&a, &b| self.frame_db[a].total_cmp(&self.frame_db[b]))
+            .unwrap_or(frames)
+            .max(1);
+        quietest * self.frame_len()
+    }
+
+    /// Samples currently held (bounded by max_open_ms + one chunk)
+    pub fn buffered_samples(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Emit a Final for buffer[..cut] and drop it
+    fn close(&mut self, cut: usize) -> StreamHypothesis {
+        let hypothesis = self.hypothesis(HypothesisKind::Final, cut);
+        self.drain(cut);
+        self.speech_seen = false;
+        self.since_partial = 0;
+        hypothesis
+    }
+
+    fn drain(&mut self, cut: usize) {
+        self.buffer.drain(..cut);
+        self.buffer_offset += cut as u64;
+        self.scanned = self.scanned.saturating_sub(cut);
+        self.frame_db.drain(..(cut / self.frame_len()).min(self.frame_db.len()));
+        self.silence_start = self.silence_start.map(|s| s.saturating_sub(cut));
+    }
+
+    /// Decode buffer[..end] with stream-relative offsets
+    fn hypothesis(&self, kind: HypothesisKind, end: usize) -> StreamHypothesis {
+        let (mut segments, mut phonemes) = self.transcriber.decode_segments(&self.buffer[..end], self.sample_rate);
+
+        let base = self.stream_ms(0);
+        for seg in &mut segments {
+            seg.start_ms += base;
+            seg.end_ms += base;
+        }
+        for (_, start, stop) in &mut phonemes {
+            *start += base;
+            *stop += base;
+        }
+
+        let words = self.transcriber.words_with_timings(&phonemes);
+        let text = words.iter().map(|w| w.word.as_str()).collect::<Vec<_>>().join(" ");
+
+        StreamHypothesis { kind, start_ms: base, end_ms: self.stream_ms(end), text, words, phonemes, segments }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(ms: usize) -> Vec<f32> {
+        (0..ms * RATE as usize / 1000)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn silence(ms: usize) -> Vec<f32> {
+        vec![0.0; ms * RATE as usize / 1000]
+    }
+
+    fn stream(audio: &[f32], chunk_ms: usize, st: &mut StreamingTranscriber) -> Vec<StreamHypothesis> {
+        let mut out = Vec::new();
+        for chunk in audio.chunks(chunk_ms * RATE as usize / 1000) {
+            out.extend(st.push(chunk));
+        }
+        out.extend(st.finish());
+        out
+    }
+
+    #[test]
+    fn test_silence_closes_utterances() {
+        let mut st = StreamingTranscriber::new(SaturatedTranscriber::new(), RATE).unwrap();
+        let audio: Vec<f32> = [silence(500), tone(400), silence(600), tone(400), silence(100)].concat();
+
+        let finals: Vec<_> = stream(&audio, 40, &mut st).into_iter()
+            .filter(|h| h.kind == HypothesisKind::Final)
+            .collect();
+
+        assert_eq!(finals.len(), 2, "Two utterances separated by silence");
+        assert!(finals[0].start_ms <= 500 && finals[0].end_ms <= 1500);
+        assert!(finals[1].start_ms >= finals[0].end_ms);
+        assert_eq!(finals[1].end_ms, 2000, "finish() closes the open speech");
+        for seg in &finals[1].segments {
+            assert!(seg.start_ms >= finals[1].start_ms, "Offsets are stream-relative");
+        }
+    }
+
+    #[test]
+    fn test_partials_then_final() {
+        let config = StreamConfig { partial_interval_ms: 200, ..StreamConfig::default() };
+        let mut st = StreamingTranscriber::with_config(SaturatedTranscriber::new(), RATE, config).unwrap();
+        let audio: Vec<f32> = [tone(800), silence(400)].concat();
+
+        let kinds: Vec<_> = stream(&audio, 100, &mut st).iter().map(|h| h.kind).collect();
+        assert!(kinds.len() >= 2);
+        assert_eq!(kinds[0], HypothesisKind::Partial);
+        assert_eq!(kinds.last(), Some(&HypothesisKind::Final));
+    }
+
+    #[test]
+    fn test_memory_bounded() {
+        let config = StreamConfig { max_open_ms: 1000, ..StreamConfig::default() };
+        let mut st = StreamingTranscriber::with_config(SaturatedTranscriber::new(), RATE, config).unwrap();
+
+        // Continuous speech with no pauses, then a long silence
+        for _ in 0..20 {
+            st.push(&tone(250));
+            assert!(st.buffered_samples() <= (1000 + 250) * RATE as usize / 1000);
+        }
+        for _ in 0..20 {
+            st.push(&silence(250));
+            assert!(st.buffered_samples() <= 1250 * RATE as usize / 1000);
+        }
+    }
+
+    #[test]
+    fn test_forced_close_cuts_in_the_quietest_frame() {
+        let config = StreamConfig { max_open_ms: 1000, ..StreamConfig::default() };
+        let mut st = StreamingTranscriber::with_config(SaturatedTranscriber::new(), RATE, config).unwrap();
+
+        // No pause long enough to close: a 100ms dip between two words
+        let dip: Vec<f32> = tone(100).iter().map(|x| x * 0.05).collect();
+        let audio: Vec<f32> = [tone(650), dip, tone(650)].concat();
+
+        let finals: Vec<_> = stream(&audio, 50, &mut st).into_iter()
+            .filter(|h| h.kind == HypothesisKind::Final)
+            .collect();
+        assert_eq!(finals.len(), 2);
+        assert!((650..750).contains(&finals[0].end_ms), "Cut inside the dip, not at 1000ms: {}", finals[0].end_ms);
+        assert_eq!(finals[1].start_ms, finals[0].end_ms);
+    }
+
+    #[test]
+    fn test_zero_sample_rate_rejected() {
+        assert_eq!(StreamingTranscriber::new(SaturatedTranscriber::new(), 0).err(), Some(StreamError::ZeroSampleRate));
+    }
+}
@@ -0,0 +1,453 @@
+//! UCI Protocol Front-End for the Saturation Search
+//!
+//! Plugs FrameworkSearchEngine + search_with_limits into standard GUIs
+//! (Arena, CuteChess, lichess-bot).
+//!
+//! Supported commands:
+//! - uci, isready, ucinewgame, quit
+//! - position startpos|fen <fen> [moves <m1> <m2> ...]
+//! - go [ponder] [wtime] [btime] [winc] [binc] [movestogo] [depth] [nodes] [movetime] [infinite]
+//! - stop, ponderhit
+//!
+//! Game history (Zobrist hashes of every position before the current one)
+//! is passed to search_with_history for threefold repetition detection.
+//! Progress is reported as `info` lines built from the SaturationAnalyzer
+//! eval history - nothing else is written to stdout.
+//!
+//! Pondering searches the expected position without limits. On ponderhit
+//! the real search resumes from the ponder SaturationAnalyzer, so depths
+//! that already converged are not searched again.
+
+use super::framework_search::FrameworkSearchEngine;
+use super::movegen::{generate_legal, Move};
+use super::position::{Color, Position};
+use super::saturation::{
+    mate_in, resume_with_limits, DepthStats, SaturationAnalyzer, SearchLimits, SearchObserver,
+    StopReason, TimeControl,
+};
+use std::io::{self, BufRead, Write};
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+pub const ENGINE_NAME: &str = "Saturation";
+pub const ENGINE_AUTHOR: &str = "Eliran Sabag";
+
+// ============================================================================
+// COMMAND PARSING
+// ============================================================================
+
+/// Parsed arguments of a `go` command
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoCommand {
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub depth: Option<i32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+impl GoCommand {
+    /// Parse the tokens following `go`. Unknown tokens are ignored (UCI spec).
+    pub fn parse(tokens: &[&str]) -> Self {
+        let mut go = GoCommand::default();
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let value = tokens.get(i + 1);
+            let num = || value.and_then(|v| v.parse::<u64>().ok());
+
+            match tokens[i] {
+                "wtime" => go.wtime = num(),
+                "btime" => go.btime = num(),
+                "winc" => go.winc = num(),
+                "binc" => go.binc = num(),
+                "movestogo" => go.movestogo = num(),
+                "depth" => go.depth = value.and_then(|v| v.parse::<i32>().ok()),
+                "nodes" => go.nodes = num(),
+                "movetime" => go.movetime = num(),
+                "infinite" => {
+                    go.infinite = true;
+                    i += 1;
+                    continue;
+                }
+                "ponder" => {
+                    go.ponder = true;
+                    i += 1;
+                    continue;
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            i += 2;
+        }
+
+        go
+    }
+
+    /// Convert to search limits for the side to move
+    ///
+    /// A ponder search ignores the clock until ponderhit: it runs as infinite.
+    pub fn to_limits(&self, side: Color, stop: Arc<AtomicBool>) -> SearchLimits {
+        let (time, inc) = match side {
+            Color::White => (self.wtime, self.winc),
+            Color::Black => (self.btime, self.binc),
+        };
+
+        SearchLimits {
+            depth: self.depth,
+            nodes: self.nodes,
+            movetime_ms: self.movetime,
+            clock: time.map(|remaining_ms| TimeControl {
+                remaining_ms,
+                increment_ms: inc.unwrap_or(0),
+                moves_to_go: self.movestogo,
+            }),
+            infinite: self.infinite || self.ponder,
+            stop: Some(stop),
+        }
+    }
+}
+
+/// Find the legal move whose UCI string (e.g. "e2e4", "e7e8q") matches
+pub fn parse_move(pos: &Position, text: &str) -> Option<Move> {
+    generate_legal(pos).into_iter().find(|mv| mv.to_string() == text)
+}
+
+/// Parse `position startpos|fen <fen> [moves ...]`
+///
+/// Returns the final position and the hashes of every position before it.
+pub fn parse_position(tokens: &[&str]) -> Result<(Position, Vec<u64>), String> {
+    let moves_at = tokens.iter().position(|&t| t == "moves").unwrap_or(tokens.len());
+
+    let mut pos = match tokens.first() {
+        Some(&"startpos") => Position::starting(),
+        Some(&"fen") => {
+            let fen = tokens[1..moves_at].join(" ");
+            Position::from_fen(&fen).map_err(|e| format!("bad fen '{}': {:?}", fen, e))?
+        }
+        _ => return Err("expected 'startpos' or 'fen'".to_string()),
+    };
+
+    let mut history = Vec::new();
+    for text in tokens.iter().skip(moves_at + 1) {
+        let mv = parse_move(&pos, text).ok_or_else(|| format!("illegal move '{}'", text))?;
+        history.push(pos.hash());
+        pos = pos.make_move(mv);
+    }
+
+    Ok((pos, history))
+}
+
+// ============================================================================
+// INFO OUTPUT
+// ============================================================================
+
+/// Format an `info` line from the analyzer's latest depth
+///
+/// The engine returns only the root move, not a principal variation, so
+/// the move goes out as `string best <move>` rather than a one-move `pv`.
+pub fn info_line(analyzer: &SaturationAnalyzer, stats: &DepthStats) -> Option<String> {
+    let (depth, eval, best_move) = analyzer.latest()?;
+
+    let score = match mate_in(eval) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", eval),
+    };
+
+    let mut line = format!("info depth {} score {} nodes {} time {}",
+        depth, score, stats.nodes, stats.elapsed_ms);
+    if let Some(nps) = (stats.nodes * 1000).checked_div(stats.elapsed_ms) {
+        line.push_str(&format!(" nps {}", nps));
+    }
+    if let Some(mv) = best_move {
+        line.push_str(&format!(" string best {}", mv));
+    }
+
+    Some(line)
+}
+
+/// Writes UCI `info` lines for each completed depth
+struct UciObserver;
+
+impl SearchObserver for UciObserver {
+    fn on_depth(&mut self, analyzer: &SaturationAnalyzer, stats: &DepthStats) {
+        if let Some(line) = info_line(analyzer, stats) {
+            send(&line);
+        }
+    }
+
+    fn on_stop(&mut self, reason: StopReason, stats: &DepthStats) {
+        send(&format!("info string stop {:?} at depth {}", reason, stats.depth));
+    }
+}
+
+fn send(line: &str) {
+    let mut out = io::stdout().lock();
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+// ============================================================================
+// UCI ENGINE
+// ============================================================================
+
+/// UCI session state: current game and the (possibly running) search thread
+pub struct UciEngine {
+    position: Position,
+    history: Vec<u64>,
+    engine: Option<FrameworkSearchEngine>,
+    search: Option<JoinHandle<(FrameworkSearchEngine, SaturationAnalyzer)>>,
+    stop: Arc<AtomicBool>,
+    /// Wakes an infinite search that finished early and is holding bestmove
+    stop_signal: Option<mpsc::Sender<()>>,
+    /// `go ponder` command of the running ponder search (awaiting ponderhit)
+    pondering: Option<GoCommand>,
+}
+
+impl Default for UciEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UciEngine {
+    pub fn new() -> Self {
+        UciEngine {
+            position: Position::starting(),
+            history: Vec::new(),
+            engine: Some(FrameworkSearchEngine::new()),
+            search: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            stop_signal: None,
+            pondering: None,
+        }
+    }
+
+    /// Handle one command line. Returns false on `quit`.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((&cmd, args)) = tokens.split_first() else { return true };
+
+        match cmd {
+            "uci" => {
+                send(&format!("id name {}", ENGINE_NAME));
+                send(&format!("id author {}", ENGINE_AUTHOR));
+                send("option name Ponder type check default false");
+                send("uciok");
+            }
+            "isready" => send("readyok"),
+            "ucinewgame" => {
+                self.finish_search();
+                self.engine = Some(FrameworkSearchEngine::new());
+                self.position = Position::starting();
+                self.history.clear();
+            }
+            "position" => {
+                self.finish_search();
+                match parse_position(args) {
+                    Ok((pos, history)) => {
+                        self.position = pos;
+                        self.history = history;
+                    }
+                    Err(e) => send(&format!("info string {}", e)),
+                }
+            }
+            "go" => self.go(&GoCommand::parse(args)),
+            "ponderhit" => self.ponderhit(),
+            "stop" => self.finish_search(),
+            "quit" => {
+                self.finish_search();
+                return false;
+            }
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Start a search on a background thread so `stop` can be read
+    fn go(&mut self, go: &GoCommand) {
+        self.finish_search();
+
+        if go.ponder {
+            self.pondering = Some(go.clone());
+        }
+        self.spawn_search(go, SaturationAnalyzer::new());
+    }
+
+    /// The expected move was played: the ponder search becomes the real one
+    fn ponderhit(&mut self) {
+        let Some(mut go) = self.pondering.take() else { return };
+        let analyzer = self.join_search().unwrap_or_default();
+
+        go.ponder = false;
+        self.spawn_search(&go, analyzer);
+    }
+
+    /// Search the current position, resuming from `analyzer`
+    ///
+    /// bestmove is only sent for real searches; a ponder search reports
+    /// it when stopped without a ponderhit (see finish_search).
+    fn spawn_search(&mut self, go: &GoCommand, analyzer: SaturationAnalyzer) {
+        let mut engine = self.engine.take().unwrap_or_else(FrameworkSearchEngine::new);
+        let pos = self.position.clone();
+        let history = self.history.clone();
+        self.stop = Arc::new(AtomicBool::new(false));
+        let (stop_signal, stopped) = mpsc::channel();
+        self.stop_signal = Some(stop_signal);
+        let limits = go.to_limits(pos.side_to_move, self.stop.clone());
+        let ponder = go.ponder;
+
+        self.search = Some(thread::spawn(move || {
+            let (best_move, _, analyzer) = resume_with_limits(&mut engine, &pos, &history, &limits, &mut UciObserver, analyzer);
+
+            // UCI: in infinite mode bestmove may only be sent after `stop`
+            // (a dropped sender also ends the wait)
+            if limits.infinite {
+                let _ = stopped.recv();
+            }
+
+            if !ponder {
+                send_bestmove(best_move);
+            }
+            (engine, analyzer)
+        }));
+    }
+
+    /// Signal stop and wait for the running search, keeping its engine
+    fn join_search(&mut self) -> Option<SaturationAnalyzer> {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(stop_signal) = self.stop_signal.take() {
+            let _ = stop_signal.send(());
+        }
+        let (engine, analyzer) = self.search.take()?.join().ok()?;
+        self.engine = Some(engine);
+        Some(analyzer)
+    }
+
+    /// Signal stop and wait for the running search (if any) to report bestmove
+    fn finish_search(&mut self) {
+        let analyzer = self.join_search();
+
+        // Ponder miss: UCI still expects a bestmove for the `go ponder`
+        if self.pondering.take().is_some() {
+            send_bestmove(analyzer.and_then(|a| a.latest()).and_then(|(_, _, mv)| mv));
+        }
+    }
+}
+
+fn send_bestmove(best_move: Option<Move>) {
+    match best_move {
+        Some(mv) => send(&format!("bestmove {}", mv)),
+        None => send("bestmove 0000"),
+    }
+}
+
+/// Run the UCI loop on stdin/stdout until `quit` or EOF
+pub fn run_uci() {
+    let mut uci = UciEngine::new();
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if !uci.handle(&line) {
+            return;
+        }
+    }
+    uci.finish_search();
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bitboard::Square;
+    use super::super::saturation::MATE_SCORE;
+
+    #[test]
+    fn test_parse_go_clock() {
+        let go = GoCommand::parse(&["wtime", "60000", "btime", "55000", "winc", "1000", "binc", "1000", "movestogo", "20"]);
+        assert_eq!(go.wtime, Some(60000));
+        assert_eq!(go.btime, Some(55000));
+        assert_eq!(go.movestogo, Some(20));
+
+        let limits = go.to_limits(Color::Black, Arc::new(AtomicBool::new(false)));
+        let clock = limits.clock.unwrap();
+        assert_eq!(clock.remaining_ms, 55000);
+        assert_eq!(clock.increment_ms, 1000);
+        assert_eq!(clock.moves_to_go, Some(20));
+    }
+
+    #[test]
+    fn test_parse_go_limits() {
+        let go = GoCommand::parse(&["depth", "7", "nodes", "100000", "movetime", "500"]);
+        assert_eq!(go.depth, Some(7));
+        assert_eq!(go.nodes, Some(100000));
+        assert_eq!(go.movetime, Some(500));
+        assert!(!go.infinite);
+
+        assert!(GoCommand::parse(&["infinite"]).infinite);
+        // Unknown tokens (e.g. searchmoves) are skipped
+        assert_eq!(GoCommand::parse(&["searchmoves", "e2e4", "depth", "3"]).depth, Some(3));
+    }
+
+    #[test]
+    fn test_parse_go_ponder() {
+        let go = GoCommand::parse(&["ponder", "wtime", "60000", "btime", "60000"]);
+        assert!(go.ponder);
+        assert!(!go.infinite);
+        assert_eq!(go.wtime, Some(60000));
+
+        // Pondering ignores the clock until ponderhit
+        let stop = Arc::new(AtomicBool::new(false));
+        assert!(go.to_limits(Color::White, stop.clone()).infinite);
+        let hit = GoCommand { ponder: false, ..go };
+        assert!(!hit.to_limits(Color::White, stop).infinite);
+    }
+
+    #[test]
+    fn test_parse_position_records_history() {
+        let (pos, history) = parse_position(&["startpos", "moves", "e2e4", "e7e5"]).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0], Position::starting().hash());
+        assert_eq!(pos.side_to_move, Color::White);
+
+        assert!(parse_position(&["startpos", "moves", "e2e5"]).is_err(), "Illegal move rejected");
+    }
+
+    #[test]
+    fn test_info_line_from_analyzer() {
+        let mut analyzer = SaturationAnalyzer::new();
+        analyzer.record(1, 20, Some(Move::new(Square::E2, Square::E4)));
+        let stats = DepthStats { depth: 1, nodes: 500, nodes_this_depth: 500, elapsed_ms: 10, depth_ms: 10 };
+
+        let line = info_line(&analyzer, &stats).unwrap();
+        assert!(line.starts_with("info depth 1 score cp 20 nodes 500"));
+        assert!(line.ends_with(&format!(" string best {}", Move::new(Square::E2, Square::E4))));
+        assert!(!line.contains(" pv "), "No principal variation to report");
+    }
+
+    #[test]
+    fn test_info_line_reports_mate() {
+        let stats = DepthStats { depth: 4, nodes: 900, nodes_this_depth: 600, elapsed_ms: 0, depth_ms: 0 };
+
+        // Mate in 3 plies for the side to move = mate in 2 moves
+        let mut winning = SaturationAnalyzer::new();
+        winning.record(4, MATE_SCORE - 3, None);
+        assert!(info_line(&winning, &stats).unwrap().starts_with("info depth 4 score mate 2 nodes 900"));
+
+        // Mated in 2 plies = mated in 1 move
+        let mut losing = SaturationAnalyzer::new();
+        losing.record(4, -(MATE_SCORE - 2), None);
+        assert!(info_line(&losing, &stats).unwrap().starts_with("info depth 4 score mate -1 "));
+    }
+}